@@ -1,5 +1,7 @@
+use crate::invariant::InvariantViolation;
+use crate::tree_render::{self, Shape};
 use std::convert::TryFrom;
-use std::fmt::Debug;
+use std::fmt::{Display, Write};
 use std::mem;
 
 struct Node<T> {
@@ -43,7 +45,7 @@ where
 impl BTreeProps {
     fn new(degree: usize) -> Self {
         BTreeProps {
-            degree: degree,
+            degree,
             max_keys: degree - 1,
             mid_key_index: (degree - 1) / 2,
         }
@@ -53,8 +55,352 @@ impl BTreeProps {
         node.keys.len() == self.max_keys
     }
 
-    // Splite Child expects the Child Node to be full
-    /// Move the middle_key to parent node and splite the child_node's
-    /// keys/chilren_nodes into halt
-    fn splite_child<T: Ord + Copy + Default>(&self, parent: &mut Node<T>) {}
+    // Split Child expects the Child Node to be full
+    /// Move the middle_key to parent node and split the child_node's
+    /// keys/chilren_nodes into half
+    fn split_child<T: Ord + Copy>(&self, parent: &mut Node<T>, child_index: usize) {
+        let child = &mut parent.children[child_index];
+        let middle_key = child.keys[self.mid_key_index];
+        let right_keys = child.keys.split_off(self.mid_key_index + 1);
+        child.keys.truncate(self.mid_key_index);
+        let right_children = if !child.is_leaf() {
+            Some(child.children.split_off(self.mid_key_index + 1))
+        } else {
+            None
+        };
+        let new_child_node = Node::new(self.degree, Some(right_keys), right_children);
+
+        parent.keys.insert(child_index, middle_key);
+        parent.children.insert(child_index + 1, new_child_node);
+    }
+
+    fn insert_non_full<T: Ord + Copy>(&self, node: &mut Node<T>, key: T) {
+        let mut index: isize = isize::try_from(node.keys.len()).unwrap() - 1;
+        while index >= 0 && node.keys[index as usize] >= key {
+            index -= 1;
+        }
+
+        let mut u_index = usize::try_from(index + 1).unwrap();
+        if node.is_leaf() {
+            // Just insert it, as we know this method will be called only when node is not full
+            node.keys.insert(u_index, key);
+        } else {
+            if self.is_maxed_out(&node.children[u_index]) {
+                self.split_child(node, u_index);
+                if node.keys[u_index] < key {
+                    u_index += 1;
+                }
+            }
+            self.insert_non_full(&mut node.children[u_index], key);
+        }
+    }
+
+    /// Check one subtree: keys are sorted and inside (lower, upper), the node
+    /// is neither over- nor under-full, and every leaf sits at the same depth.
+    /// Returns the depth of the leaves below this node.
+    fn validate_node<T: Ord + Copy>(
+        &self,
+        node: &Node<T>,
+        is_root: bool,
+        lower: Option<T>,
+        upper: Option<T>,
+    ) -> Result<usize, InvariantViolation> {
+        if node.keys.len() > self.max_keys {
+            return Err(InvariantViolation::new(
+                "BTree",
+                format!(
+                    "node holds {} keys, more than {}",
+                    node.keys.len(),
+                    self.max_keys
+                ),
+            ));
+        }
+        if !is_root && node.keys.len() < self.mid_key_index {
+            return Err(InvariantViolation::new(
+                "BTree",
+                format!(
+                    "node holds {} keys, fewer than {}",
+                    node.keys.len(),
+                    self.mid_key_index
+                ),
+            ));
+        }
+        if node.keys.windows(2).any(|pair| pair[0] > pair[1])
+            || node.keys.iter().any(|&key| {
+                lower.is_some_and(|lower| key < lower) || upper.is_some_and(|upper| key > upper)
+            })
+        {
+            return Err(InvariantViolation::new("BTree", "keys are out of order"));
+        }
+        if node.is_leaf() {
+            return Ok(0);
+        }
+        if node.children.len() != node.keys.len() + 1 {
+            return Err(InvariantViolation::new(
+                "BTree",
+                format!(
+                    "node with {} keys has {} children",
+                    node.keys.len(),
+                    node.children.len()
+                ),
+            ));
+        }
+
+        let mut leaf_depth = None;
+        for (i, child) in node.children.iter().enumerate() {
+            let child_lower = if i == 0 {
+                lower
+            } else {
+                Some(node.keys[i - 1])
+            };
+            let child_upper = node.keys.get(i).copied().or(upper);
+            let depth = self.validate_node(child, false, child_lower, child_upper)?;
+            if leaf_depth.is_some_and(|leaf_depth| leaf_depth != depth) {
+                return Err(InvariantViolation::new(
+                    "BTree",
+                    "leaves are not all at the same depth",
+                ));
+            }
+            leaf_depth = Some(depth);
+        }
+        Ok(leaf_depth.unwrap() + 1)
+    }
+}
+
+impl<T> BTree<T>
+where
+    T: Ord + Copy,
+{
+    pub fn new(branch_factor: usize) -> Self {
+        let degree = 2 * branch_factor;
+        BTree {
+            root: Node::new(degree, None, None),
+            props: BTreeProps::new(degree),
+        }
+    }
+
+    pub fn insert(&mut self, key: T) {
+        if self.props.is_maxed_out(&self.root) {
+            // Create an empty root and split the old root...
+            let mut new_root = Node::new(self.props.degree, None, None);
+            mem::swap(&mut new_root, &mut self.root);
+            self.root.children.insert(0, new_root);
+            self.props.split_child(&mut self.root, 0);
+        }
+        self.props.insert_non_full(&mut self.root, key);
+    }
+
+    pub fn search(&self, key: T) -> bool {
+        let mut current_node = &self.root;
+        loop {
+            let index = current_node.keys.partition_point(|&k| k < key);
+            if index < current_node.keys.len() && current_node.keys[index] == key {
+                return true;
+            }
+            if current_node.is_leaf() {
+                return false;
+            }
+            current_node = &current_node.children[index];
+        }
+    }
+
+    /// Returns all keys in order
+    pub fn keys(&self) -> Vec<T> {
+        fn collect<T: Copy>(node: &Node<T>, out: &mut Vec<T>) {
+            for (i, &key) in node.keys.iter().enumerate() {
+                if let Some(child) = node.children.get(i) {
+                    collect(child, out);
+                }
+                out.push(key);
+            }
+            if let Some(child) = node.children.get(node.keys.len()) {
+                collect(child, out);
+            }
+        }
+        let mut out = Vec::new();
+        collect(&self.root, &mut out);
+        out
+    }
+
+    /// Check the occupancy of every node, the key order and that all leaves
+    /// are at the same depth
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        self.props
+            .validate_node(&self.root, true, None, None)
+            .map(|_| ())
+    }
+}
+
+impl<T> BTree<T>
+where
+    T: Ord + Copy + Display,
+{
+    /// Draw the tree with box-drawing characters, one node per line
+    pub fn render_ascii(&self) -> String {
+        if self.root.keys.is_empty() {
+            return "(empty)\n".to_string();
+        }
+        tree_render::render_ascii(&Self::shape(&self.root))
+    }
+
+    /// Emit the tree as a Graphviz digraph of record nodes; each edge leaves
+    /// from the port between the two keys that bound the child
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph BTree {\n    node [shape=record];\n");
+        if !self.root.keys.is_empty() {
+            Self::write_dot(&self.root, &mut 0, &mut out);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn shape(node: &Node<T>) -> Shape {
+        let keys: Vec<String> = node.keys.iter().map(|key| key.to_string()).collect();
+        Shape {
+            label: format!("[{}]", keys.join(" | ")),
+            children: node
+                .children
+                .iter()
+                .map(|child| Some(Self::shape(child)))
+                .collect(),
+        }
+    }
+
+    // Write this subtree, numbering nodes in pre-order; returns this node's id
+    fn write_dot(node: &Node<T>, next_id: &mut usize, out: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        let mut fields = Vec::new();
+        for (i, key) in node.keys.iter().enumerate() {
+            if !node.is_leaf() {
+                fields.push(format!("<c{}>", i));
+            }
+            fields.push(tree_render::escape_record(&key.to_string()));
+        }
+        if !node.is_leaf() {
+            fields.push(format!("<c{}>", node.keys.len()));
+        }
+        writeln!(out, "    n{} [label=\"{}\"];", id, fields.join("|")).unwrap();
+        for (i, child) in node.children.iter().enumerate() {
+            let child_id = Self::write_dot(child, next_id, out);
+            writeln!(out, "    n{}:c{} -> n{};", id, i, child_id).unwrap();
+        }
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invariant::run_random_ops;
+    use rand::Rng;
+
+    #[test]
+    fn test_insert_and_search() {
+        let mut tree = BTree::new(2);
+        for key in [10, 20, 30, 5, 6, 12, 30, 7, 17] {
+            tree.insert(key);
+        }
+        assert_eq!(tree.keys(), vec![5, 6, 7, 10, 12, 17, 20, 30, 30]);
+        assert!(tree.search(17));
+        assert!(tree.search(30));
+        assert!(!tree.search(8));
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let mut tree = BTree::new(2);
+        assert_eq!(tree.render_ascii(), "(empty)\n");
+        for key in 1..=10 {
+            tree.insert(key);
+        }
+        assert_eq!(
+            tree.render_ascii(),
+            "[4]\n\
+             ├── [2]\n\
+             │   ├── [1]\n\
+             │   └── [3]\n\
+             └── [6 | 8]\n\
+             \x20   ├── [5]\n\
+             \x20   ├── [7]\n\
+             \x20   └── [9 | 10]\n"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut tree = BTree::new(2);
+        for key in 1..=5 {
+            tree.insert(key);
+        }
+        assert_eq!(
+            tree.to_dot(),
+            r#"digraph BTree {
+    node [shape=record];
+    n0 [label="<c0>|2|<c1>"];
+    n1 [label="1"];
+    n0:c0 -> n1;
+    n2 [label="3|4|5"];
+    n0:c1 -> n2;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_validate_random_ops() {
+        for branch_factor in 2..5 {
+            let mut tree = BTree::new(branch_factor);
+            let mut sorted: Vec<i32> = Vec::new();
+            run_random_ops(
+                48 + branch_factor as u64,
+                1500,
+                &mut tree,
+                |rng, tree| {
+                    let key = rng.gen_range(0..300);
+                    if rng.gen_bool(0.7) {
+                        tree.insert(key);
+                        let at = sorted.partition_point(|&x| x <= key);
+                        sorted.insert(at, key);
+                        format!("insert({})", key)
+                    } else {
+                        assert_eq!(tree.search(key), sorted.binary_search(&key).is_ok());
+                        format!("search({})", key)
+                    }
+                },
+                BTree::validate,
+            );
+            assert_eq!(tree.keys(), sorted);
+        }
+    }
+
+    #[test]
+    fn test_validate_detects_corruption() {
+        let build = || {
+            let mut tree = BTree::new(2);
+            for key in 0..50 {
+                tree.insert(key);
+            }
+            tree
+        };
+        let tree = build();
+        assert_eq!(tree.validate(), Ok(()));
+
+        // The first child of the root is an internal node; dropping its
+        // children turns it into a leaf that is shallower than the others
+        let mut broken = build();
+        assert!(!broken.root.children[0].is_leaf());
+        broken.root.children[0].children.clear();
+        assert!(broken.validate().is_err());
+
+        // Overfill a node
+        let mut broken = build();
+        let max_keys = broken.props.max_keys;
+        let node = broken.root.children.last_mut().unwrap();
+        while node.keys.len() <= max_keys {
+            node.keys.push(100);
+        }
+        assert!(broken.validate().is_err());
+    }
 }
@@ -0,0 +1,67 @@
+use data_structure::flat_hash_map::{FlatHashMap, FxBuildHasher};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const SLOTS: usize = 1 << 20;
+
+fn report(name: &str, ops: usize, elapsed: Duration) {
+    println!(
+        "  {:<28} {:>8.1} Mops/s",
+        name,
+        ops as f64 / elapsed.as_secs_f64() / 1e6
+    );
+}
+
+// 在相同的键序列和哈希函数下，比较 FlatHashMap 与 std HashMap 在不同装载率下的吞吐量。
+// 两者都预先分配好容量，装载率 = 元素个数 / FlatHashMap 的槽位数
+fn main() {
+    let mut rng = StdRng::seed_from_u64(43);
+    for load in [0.25, 0.5, 0.75, 0.85] {
+        let n = (SLOTS as f64 * load) as usize;
+        let keys: Vec<u64> = (0..n).map(|_| rng.gen()).collect();
+        let misses: Vec<u64> = (0..n).map(|_| rng.gen()).collect();
+        println!("load factor {:.2} ({} keys)", load, n);
+
+        let mut flat: FlatHashMap<u64, u64, FxBuildHasher> =
+            FlatHashMap::with_capacity_and_hasher(SLOTS / 8 * 7, FxBuildHasher::default());
+        let start = Instant::now();
+        for &k in &keys {
+            flat.insert(k, k);
+        }
+        report("FlatHashMap insert", n, start.elapsed());
+        let start = Instant::now();
+        let hits = keys.iter().filter(|k| flat.contains_key(k)).count();
+        report("FlatHashMap lookup hit", n, start.elapsed());
+        let start = Instant::now();
+        let missed = misses.iter().filter(|k| flat.contains_key(k)).count();
+        report("FlatHashMap lookup miss", n, start.elapsed());
+        let start = Instant::now();
+        for k in &keys {
+            flat.remove(k);
+        }
+        report("FlatHashMap remove", n, start.elapsed());
+        assert!(flat.is_empty());
+
+        let mut std_map: HashMap<u64, u64, FxBuildHasher> =
+            HashMap::with_capacity_and_hasher(SLOTS / 8 * 7, FxBuildHasher::default());
+        let start = Instant::now();
+        for &k in &keys {
+            std_map.insert(k, k);
+        }
+        report("std HashMap insert", n, start.elapsed());
+        let start = Instant::now();
+        let std_hits = keys.iter().filter(|k| std_map.contains_key(k)).count();
+        report("std HashMap lookup hit", n, start.elapsed());
+        let start = Instant::now();
+        let std_missed = misses.iter().filter(|k| std_map.contains_key(k)).count();
+        report("std HashMap lookup miss", n, start.elapsed());
+        let start = Instant::now();
+        for k in &keys {
+            std_map.remove(k);
+        }
+        report("std HashMap remove", n, start.elapsed());
+        assert_eq!((hits, missed), (std_hits, std_missed));
+    }
+}
@@ -1,4 +1,7 @@
+use crate::invariant::InvariantViolation;
+use crate::tree_render::{self, Shape};
 use std::cmp::Ordering;
+use std::fmt::{Display, Write};
 use std::ops::Deref;
 
 pub struct BinarySearchTree<T>
@@ -153,6 +156,129 @@ where
             None => None,
         }
     }
+
+    /// Check the ordering invariant: every value in the left subtree is
+    /// smaller than its node, every value in the right subtree is not
+    /// smaller, and only an empty root may hold no value
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        if self.value.is_none() {
+            return if self.left.is_none() && self.right.is_none() {
+                Ok(())
+            } else {
+                Err(InvariantViolation::new(
+                    "BinarySearchTree",
+                    "empty root has children",
+                ))
+            };
+        }
+        self.validate_range(None, None)
+    }
+
+    // Check that every value in this subtree lies in [lower, upper)
+    fn validate_range(
+        &self,
+        lower: Option<&T>,
+        upper: Option<&T>,
+    ) -> Result<(), InvariantViolation> {
+        let key = match &self.value {
+            Some(key) => key,
+            None => {
+                return Err(InvariantViolation::new(
+                    "BinarySearchTree",
+                    "non-root node holds no value",
+                ))
+            }
+        };
+        if lower.is_some_and(|lower| key < lower) || upper.is_some_and(|upper| key >= upper) {
+            return Err(InvariantViolation::new(
+                "BinarySearchTree",
+                "value is out of order with respect to an ancestor",
+            ));
+        }
+        if let Some(left) = &self.left {
+            left.validate_range(lower, Some(key))?;
+        }
+        if let Some(right) = &self.right {
+            right.validate_range(Some(key), upper)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> BinarySearchTree<T>
+where
+    T: Ord + Display,
+{
+    /// Draw the tree with box-drawing characters, left child first.
+    /// A missing child whose sibling exists is drawn as ∅
+    pub fn render_ascii(&self) -> String {
+        match self.shape() {
+            Some(shape) => tree_render::render_ascii(&shape),
+            None => "(empty)\n".to_string(),
+        }
+    }
+
+    /// Emit the tree as a Graphviz digraph. Every node is labelled with its
+    /// value and its balance factor, height(right) - height(left)
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph BinarySearchTree {\n    node [shape=circle];\n");
+        if self.value.is_some() {
+            self.write_dot(&mut 0, &mut out);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn shape(&self) -> Option<Shape> {
+        let value = self.value.as_ref()?;
+        let left = self.left.as_ref().and_then(|node| node.shape());
+        let right = self.right.as_ref().and_then(|node| node.shape());
+        let children = if left.is_none() && right.is_none() {
+            Vec::new()
+        } else {
+            vec![left, right]
+        };
+        Some(Shape {
+            label: value.to_string(),
+            children,
+        })
+    }
+
+    // Write this subtree, numbering nodes in pre-order; returns this node's
+    // id and the height of the subtree. A node's balance is only known once
+    // both children are done, so its line follows the lines of its subtree.
+    fn write_dot(&self, next_id: &mut usize, out: &mut String) -> (usize, i64) {
+        let id = *next_id;
+        *next_id += 1;
+        let mut edges = Vec::new();
+        let mut heights = [0, 0];
+        for (i, (child, side)) in [(&self.left, "L"), (&self.right, "R")]
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(child) = child {
+                let (child_id, height) = child.write_dot(next_id, out);
+                edges.push((child_id, side));
+                heights[i] = height;
+            }
+        }
+        let label = format!(
+            "{}\nbalance {:+}",
+            self.value.as_ref().unwrap(),
+            heights[1] - heights[0]
+        );
+        writeln!(
+            out,
+            "    n{} [label=\"{}\"];",
+            id,
+            tree_render::escape_dot(&label)
+        )
+        .unwrap();
+        for (child_id, side) in edges {
+            writeln!(out, "    n{} -> n{} [label=\"{}\"];", id, child_id, side).unwrap();
+        }
+        (id, 1 + heights[0].max(heights[1]))
+    }
 }
 
 struct BinarySearchTreeIter<'a, T>
@@ -198,3 +324,98 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invariant::run_random_ops;
+    use rand::Rng;
+
+    #[test]
+    fn test_validate_random_ops() {
+        let mut tree = BinarySearchTree::new();
+        let mut sorted: Vec<i32> = Vec::new();
+        assert_eq!(tree.validate(), Ok(()));
+        run_random_ops(
+            48,
+            1000,
+            &mut tree,
+            |rng, tree| {
+                let value = rng.gen_range(0..200);
+                if rng.gen_bool(0.7) {
+                    tree.insert(value);
+                    let at = sorted.partition_point(|&x| x <= value);
+                    sorted.insert(at, value);
+                    format!("insert({})", value)
+                } else {
+                    assert_eq!(tree.search(&value), sorted.binary_search(&value).is_ok());
+                    assert_eq!(tree.minimum(), sorted.first());
+                    assert_eq!(tree.maximum(), sorted.last());
+                    assert!(tree.iter().eq(sorted.iter()));
+                    format!("search({})", value)
+                }
+            },
+            BinarySearchTree::validate,
+        );
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let mut tree = BinarySearchTree::new();
+        assert_eq!(tree.render_ascii(), "(empty)\n");
+        for value in [5, 3, 8, 4, 9, 7, 1] {
+            tree.insert(value);
+        }
+        tree.left.as_mut().unwrap().left = None;
+        assert_eq!(
+            tree.render_ascii(),
+            "5\n\
+             ├── 3\n\
+             │   ├── ∅\n\
+             │   └── 4\n\
+             └── 8\n\
+             \x20   ├── 7\n\
+             \x20   └── 9\n"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut tree = BinarySearchTree::new();
+        for value in [5, 3, 8, 4] {
+            tree.insert(value);
+        }
+        assert_eq!(
+            tree.to_dot(),
+            r#"digraph BinarySearchTree {
+    node [shape=circle];
+    n2 [label="4\nbalance +0"];
+    n1 [label="3\nbalance +1"];
+    n1 -> n2 [label="R"];
+    n3 [label="8\nbalance +0"];
+    n0 [label="5\nbalance -1"];
+    n0 -> n1 [label="L"];
+    n0 -> n3 [label="R"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_validate_detects_corruption() {
+        let mut tree = BinarySearchTree::new();
+        for value in [5, 3, 8, 4] {
+            tree.insert(value);
+        }
+        assert_eq!(tree.validate(), Ok(()));
+
+        // 4 sits in the right subtree of 3; 6 would be larger than the ancestor 5
+        let left = tree.left.as_mut().unwrap();
+        left.right.as_mut().unwrap().value = Some(6);
+        assert!(tree.validate().is_err());
+
+        let mut empty: BinarySearchTree<i32> = BinarySearchTree::new();
+        empty.left = Some(Box::new(BinarySearchTree::new()));
+        assert!(empty.validate().is_err());
+    }
+}
@@ -0,0 +1,475 @@
+/*
+    稠密位集合（BitSet）
+
+    用 u64 数组保存一个非负整数集合，第 i 个元素对应 words[i / 64] 的第 i % 64 位。
+    插入超出当前范围的元素时自动扩展数组，集合运算按字（64 位）进行。
+
+    - rank(x)：集合中小于 x 的元素个数，按字统计 popcount
+    - select(k)：第 k 小的元素（从 0 开始）
+    - 序列化格式：字数（u64，小端序）+ 每个字（小端序），末尾的全零字不会写出
+*/
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Range, Sub};
+
+const WORD_BITS: usize = 64;
+
+/// 反序列化时字节格式不正确
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MalformedBitmap;
+
+impl fmt::Display for MalformedBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "malformed bitmap bytes")
+    }
+}
+
+// 依次读取小端序整数，字节不够时报错
+pub(crate) struct ByteReader<'a> {
+    pub(crate) bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], MalformedBitmap> {
+        if self.bytes.len() < n {
+            return Err(MalformedBitmap);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, MalformedBitmap> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, MalformedBitmap> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, MalformedBitmap> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, MalformedBitmap> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn finish(self) -> Result<(), MalformedBitmap> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(MalformedBitmap)
+        }
+    }
+}
+
+// 在 word 中找第 k 个（从 0 开始）为 1 的位，要求 k < word.count_ones()
+fn select_in_word(mut word: u64, k: u32) -> u32 {
+    for _ in 0..k {
+        word &= word - 1;
+    }
+    word.trailing_zeros()
+}
+
+#[derive(Clone, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet { words: Vec::new() }
+    }
+
+    /// 预先为 [0, bits) 范围内的元素分配空间
+    pub fn with_capacity(bits: usize) -> Self {
+        BitSet {
+            words: vec![0; bits.div_ceil(WORD_BITS)],
+        }
+    }
+
+    pub(crate) fn from_words(words: Vec<u64>) -> Self {
+        BitSet { words }
+    }
+
+    pub(crate) fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// 不扩容就能保存的元素范围上界
+    pub fn capacity(&self) -> usize {
+        self.words.len() * WORD_BITS
+    }
+
+    /// 元素个数，O(capacity / 64)
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    fn grow(&mut self, words: usize) {
+        if self.words.len() < words {
+            self.words.resize(words, 0);
+        }
+    }
+
+    /// 插入元素，原来不存在时返回 true
+    pub fn insert(&mut self, i: usize) -> bool {
+        let (w, mask) = (i / WORD_BITS, 1 << (i % WORD_BITS));
+        self.grow(w + 1);
+        let absent = self.words[w] & mask == 0;
+        self.words[w] |= mask;
+        absent
+    }
+
+    /// 删除元素，原来存在时返回 true
+    pub fn remove(&mut self, i: usize) -> bool {
+        let (w, mask) = (i / WORD_BITS, 1 << (i % WORD_BITS));
+        match self.words.get_mut(w) {
+            Some(word) if *word & mask != 0 => {
+                *word &= !mask;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.words
+            .get(i / WORD_BITS)
+            .is_some_and(|w| w >> (i % WORD_BITS) & 1 == 1)
+    }
+
+    /// 插入区间内的所有元素，整字一次写入
+    pub fn insert_range(&mut self, range: Range<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let (start, end) = (range.start, range.end);
+        self.grow(end.div_ceil(WORD_BITS));
+        let (first, last) = (start / WORD_BITS, (end - 1) / WORD_BITS);
+        let head = !0u64 << (start % WORD_BITS);
+        let tail = !0u64 >> (WORD_BITS - 1 - (end - 1) % WORD_BITS);
+        if first == last {
+            self.words[first] |= head & tail;
+        } else {
+            self.words[first] |= head;
+            self.words[first + 1..last].iter_mut().for_each(|w| *w = !0);
+            self.words[last] |= tail;
+        }
+    }
+
+    /// 从小到大遍历所有元素
+    pub fn iter_ones(&self) -> IterOnes<'_> {
+        IterOnes {
+            words: &self.words,
+            index: 0,
+            current: self.words.first().copied().unwrap_or(0),
+        }
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        self.grow(other.words.len());
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        self.words.truncate(other.words.len());
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+    }
+
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= !b;
+        }
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        self.grow(other.words.len());
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+
+    /// 交集的大小，不分配新的集合
+    pub fn intersection_len(&self, other: &BitSet) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words.iter().enumerate().all(|(i, &a)| {
+            let b = other.words.get(i).copied().unwrap_or(0);
+            a & !b == 0
+        })
+    }
+
+    /// 集合中小于 x 的元素个数
+    pub fn rank(&self, x: usize) -> usize {
+        let (w, bit) = (x / WORD_BITS, x % WORD_BITS);
+        let full: usize = self.words[..w.min(self.words.len())]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum();
+        let partial = match self.words.get(w) {
+            Some(word) if bit > 0 => (word << (WORD_BITS - bit)).count_ones() as usize,
+            _ => 0,
+        };
+        full + partial
+    }
+
+    /// 第 k 小的元素（从 0 开始），k 不小于元素个数时返回 None
+    pub fn select(&self, k: usize) -> Option<usize> {
+        let mut k = k;
+        for (i, &word) in self.words.iter().enumerate() {
+            let ones = word.count_ones() as usize;
+            if k < ones {
+                return Some(i * WORD_BITS + select_in_word(word, k as u32) as usize);
+            }
+            k -= ones;
+        }
+        None
+    }
+
+    // 去掉末尾的全零字之后的有效部分
+    fn trimmed(&self) -> &[u64] {
+        let len = self
+            .words
+            .iter()
+            .rposition(|&w| w != 0)
+            .map_or(0, |i| i + 1);
+        &self.words[..len]
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let words = self.trimmed();
+        let mut bytes = Vec::with_capacity(8 + 8 * words.len());
+        bytes.extend_from_slice(&(words.len() as u64).to_le_bytes());
+        for word in words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MalformedBitmap> {
+        let mut reader = ByteReader { bytes };
+        let len = usize::try_from(reader.u64()?).map_err(|_| MalformedBitmap)?;
+        if len > reader.bytes.len() / 8 {
+            return Err(MalformedBitmap);
+        }
+        let words = (0..len).map(|_| reader.u64()).collect::<Result<_, _>>()?;
+        reader.finish()?;
+        Ok(BitSet { words })
+    }
+}
+
+pub struct IterOnes<'a> {
+    words: &'a [u64],
+    index: usize,
+    current: u64,
+}
+
+impl Iterator for IterOnes<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.index += 1;
+            self.current = *self.words.get(self.index)?;
+        }
+        let bit = self.current.trailing_zeros() as usize;
+        // 清掉最低位的 1
+        self.current &= self.current - 1;
+        Some(self.index * WORD_BITS + bit)
+    }
+}
+
+// 末尾的全零字不影响集合的内容
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.trimmed() == other.trimmed()
+    }
+}
+
+impl Eq for BitSet {}
+
+impl fmt::Debug for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter_ones()).finish()
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for i in iter {
+            self.insert(i);
+        }
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = BitSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+macro_rules! impl_set_operator {
+    ($trait:ident, $method:ident, $with:ident) => {
+        impl $trait<&BitSet> for &BitSet {
+            type Output = BitSet;
+
+            fn $method(self, other: &BitSet) -> BitSet {
+                let mut result = self.clone();
+                result.$with(other);
+                result
+            }
+        }
+    };
+}
+
+impl_set_operator!(BitOr, bitor, union_with);
+impl_set_operator!(BitAnd, bitand, intersect_with);
+impl_set_operator!(Sub, sub, difference_with);
+impl_set_operator!(BitXor, bitxor, symmetric_difference_with);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_insert_remove_contains() {
+        let mut set = BitSet::with_capacity(10);
+        assert_eq!(set.capacity(), 64);
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        assert!(set.insert(200));
+        assert!(set.capacity() >= 201);
+        assert!(set.contains(3) && set.contains(200));
+        assert!(!set.contains(4) && !set.contains(10_000));
+        assert_eq!(set.len(), 2);
+        assert!(set.remove(200));
+        assert!(!set.remove(200));
+        assert!(!set.remove(10_000));
+        assert_eq!(set.iter_ones().collect::<Vec<_>>(), vec![3]);
+        set.clear();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_insert_range() {
+        for (start, end) in [(0, 0), (0, 1), (5, 64), (60, 70), (1, 200), (64, 128)] {
+            let mut set = BitSet::new();
+            set.insert_range(start..end);
+            assert_eq!(
+                set.iter_ones().collect::<Vec<_>>(),
+                (start..end).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: BitSet = [1, 5, 64, 65, 300].into_iter().collect();
+        let b: BitSet = [5, 65, 66].into_iter().collect();
+        assert_eq!(
+            (&a | &b).iter_ones().collect::<Vec<_>>(),
+            vec![1, 5, 64, 65, 66, 300]
+        );
+        assert_eq!((&a & &b).iter_ones().collect::<Vec<_>>(), vec![5, 65]);
+        assert_eq!((&a - &b).iter_ones().collect::<Vec<_>>(), vec![1, 64, 300]);
+        assert_eq!(
+            (&a ^ &b).iter_ones().collect::<Vec<_>>(),
+            vec![1, 64, 66, 300]
+        );
+        assert_eq!(a.intersection_len(&b), 2);
+        assert!((&a & &b).is_subset(&a));
+        assert!(!b.is_subset(&a));
+        assert!((&a - &b).is_disjoint(&b));
+        // 末尾全零字不同的两个集合相等
+        assert_eq!(&b & &a, [5, 65].into_iter().collect());
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let set: BitSet = [0, 2, 63, 64, 130].into_iter().collect();
+        let ranks: Vec<usize> = [0, 1, 3, 63, 64, 65, 130, 131, 1000]
+            .iter()
+            .map(|&x| set.rank(x))
+            .collect();
+        assert_eq!(ranks, vec![0, 1, 2, 2, 3, 4, 4, 5, 5]);
+        for (k, x) in set.iter_ones().enumerate() {
+            assert_eq!(set.select(k), Some(x));
+            assert_eq!(set.rank(x), k);
+        }
+        assert_eq!(set.select(5), None);
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut set: BitSet = [1, 100, 1000].into_iter().collect();
+        set.remove(1000);
+        let bytes = set.to_bytes();
+        assert_eq!(bytes.len(), 8 + 2 * 8);
+        assert_eq!(BitSet::from_bytes(&bytes), Ok(set));
+        assert_eq!(BitSet::from_bytes(&bytes[..10]), Err(MalformedBitmap));
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(BitSet::from_bytes(&longer), Err(MalformedBitmap));
+        assert_eq!(
+            BitSet::from_bytes(&u64::MAX.to_le_bytes()),
+            Err(MalformedBitmap)
+        );
+    }
+
+    #[test]
+    fn test_against_btree_set() {
+        let mut rng = StdRng::seed_from_u64(46);
+        let random_set = |rng: &mut StdRng| -> (BitSet, BTreeSet<usize>) {
+            let values: Vec<usize> = (0..rng.gen_range(0..300))
+                .map(|_| rng.gen_range(0..1000))
+                .collect();
+            (
+                values.iter().copied().collect(),
+                values.into_iter().collect(),
+            )
+        };
+        for _ in 0..50 {
+            let (a, sa) = random_set(&mut rng);
+            let (b, sb) = random_set(&mut rng);
+            assert_eq!(a.len(), sa.len());
+            assert!(a.iter_ones().eq(sa.iter().copied()));
+            assert!((&a | &b).iter_ones().eq(sa.union(&sb).copied()));
+            assert!((&a & &b).iter_ones().eq(sa.intersection(&sb).copied()));
+            assert!((&a - &b).iter_ones().eq(sa.difference(&sb).copied()));
+            assert!((&a ^ &b)
+                .iter_ones()
+                .eq(sa.symmetric_difference(&sb).copied()));
+            assert_eq!(a.is_subset(&b), sa.is_subset(&sb));
+            let x = rng.gen_range(0..1100);
+            assert_eq!(a.rank(x), sa.range(..x).count());
+        }
+    }
+}
@@ -0,0 +1,415 @@
+/*
+    树状数组（Fenwick tree / Binary Indexed Tree）
+
+    内部使用从 1 开始的下标，tree[i] 保存区间 (i - lowbit(i), i] 的和，
+    单点修改和前缀查询都只需要沿着 lowbit 跳 O(log n) 次。对外的下标从 0 开始。
+
+    - FenwickTree：单点加、前缀和/区间和，以及按前缀和二分的 lower_bound。
+    - RangeFenwickTree：区间加、区间和，用树状数组维护差分数组。
+    - FenwickTree2D：二维网格上的单点加、矩形区域和。
+    - count_inversions：借助树状数组在 O(n log n) 内统计逆序对个数。
+*/
+use crate::segment_tree::to_half_open;
+use std::ops::{AddAssign, Mul, RangeBounds, Sub};
+
+pub(crate) fn lowbit(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+pub struct FenwickTree<T> {
+    tree: Vec<T>, // tree[0] 不使用
+}
+
+impl<T> FenwickTree<T>
+where
+    T: Copy + Default + AddAssign + Sub<Output = T>,
+{
+    /// 创建长度为 n、所有元素都为 0 的树状数组
+    pub fn new(n: usize) -> Self {
+        FenwickTree {
+            tree: vec![T::default(); n + 1],
+        }
+    }
+
+    /// 从已有的数组 O(n) 建树
+    pub fn from_slice(values: &[T]) -> Self {
+        let n = values.len();
+        let mut tree = vec![T::default(); n + 1];
+        tree[1..].copy_from_slice(values);
+        for i in 1..=n {
+            let parent = i + lowbit(i);
+            if parent <= n {
+                let child = tree[i];
+                tree[parent] += child;
+            }
+        }
+        FenwickTree { tree }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 第 index 个元素加上 delta
+    pub fn add(&mut self, index: usize, delta: T) {
+        assert!(index < self.len(), "index {} out of bounds", index);
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += lowbit(i);
+        }
+    }
+
+    /// 前 count 个元素的和
+    pub fn prefix_sum(&self, count: usize) -> T {
+        assert!(count <= self.len(), "count {} out of bounds", count);
+        let mut sum = T::default();
+        let mut i = count;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= lowbit(i);
+        }
+        sum
+    }
+
+    pub fn range_sum<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = to_half_open(range, self.len());
+        self.prefix_sum(r) - self.prefix_sum(l)
+    }
+
+    /// 第 index 个元素的值
+    pub fn get(&self, index: usize) -> T {
+        self.range_sum(index..=index)
+    }
+}
+
+impl<T> FenwickTree<T>
+where
+    T: Copy + Default + AddAssign + Sub<Output = T> + PartialOrd,
+{
+    /// 返回最小的下标 i，使得前 i + 1 个元素的和不小于 target；
+    /// 总和都小于 target 时返回 len()。要求所有元素非负（前缀和单调不减）
+    pub fn lower_bound(&self, target: T) -> usize {
+        let n = self.len();
+        // 从高位到低位确定 pos：pos 是满足 prefix_sum(pos) < target 的最大值
+        let mut pos = 0;
+        let mut sum = T::default();
+        let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+        while step > 0 {
+            let next = pos + step;
+            if next <= n {
+                let mut candidate = sum;
+                candidate += self.tree[next];
+                if candidate < target {
+                    pos = next;
+                    sum = candidate;
+                }
+            }
+            step >>= 1;
+        }
+        pos
+    }
+}
+
+/// 支持区间加、区间和的树状数组
+///
+/// 设差分数组 d[i] = a[i] - a[i - 1]，则 a 的前 k 项和为
+/// k * Σd[i] - Σ(i * d[i])（i 从 0 开始）。区间 [l, r) 加 delta 相当于
+/// d[l] += delta、d[r] -= delta，d 本身可能为负，所以把两种修改分开保存：
+/// 区间起点的 +delta 和区间终点的 -delta 各用两棵树状数组维护，计算时
+/// 先把正项加在一起再减去负项，结果和中间值都不会小于 0，无符号类型也能使用。
+/// 中间值可以达到前缀长度乘以元素值，元素类型需要留出足够的范围。
+pub struct RangeFenwickTree<T> {
+    start: FenwickTree<T>,
+    start_id: FenwickTree<T>, // 维护 i * delta
+    end: FenwickTree<T>,
+    end_id: FenwickTree<T>,
+}
+
+impl<T> RangeFenwickTree<T>
+where
+    T: Copy + Default + AddAssign + Sub<Output = T> + Mul<Output = T> + TryFrom<usize>,
+{
+    pub fn new(n: usize) -> Self {
+        RangeFenwickTree {
+            start: FenwickTree::new(n),
+            start_id: FenwickTree::new(n),
+            end: FenwickTree::new(n),
+            end_id: FenwickTree::new(n),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.start.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start.is_empty()
+    }
+
+    /// 区间内的每个元素都加上 delta
+    pub fn add<R: RangeBounds<usize>>(&mut self, range: R, delta: T) {
+        let (l, r) = to_half_open(range, self.len());
+        if l == r {
+            return;
+        }
+        self.start.add(l, delta);
+        self.start_id.add(l, Self::index_value(l) * delta);
+        if r < self.len() {
+            self.end.add(r, delta);
+            self.end_id.add(r, Self::index_value(r) * delta);
+        }
+    }
+
+    pub fn prefix_sum(&self, count: usize) -> T {
+        // k * Σd - Σ(i * d) 中 d 的正负两部分分别展开
+        let k = Self::index_value(count);
+        let mut positive = k * self.start.prefix_sum(count);
+        positive += self.end_id.prefix_sum(count);
+        let mut negative = k * self.end.prefix_sum(count);
+        negative += self.start_id.prefix_sum(count);
+        positive - negative
+    }
+
+    pub fn range_sum<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = to_half_open(range, self.len());
+        self.prefix_sum(r) - self.prefix_sum(l)
+    }
+
+    fn index_value(index: usize) -> T {
+        T::try_from(index)
+            .ok()
+            .expect("index does not fit in the element type")
+    }
+}
+
+/// 二维树状数组，下标为 (行, 列)
+pub struct FenwickTree2D<T> {
+    rows: usize,
+    cols: usize,
+    tree: Vec<Vec<T>>, // 两个维度都从 1 开始
+}
+
+impl<T> FenwickTree2D<T>
+where
+    T: Copy + Default + AddAssign + Sub<Output = T>,
+{
+    pub fn new(rows: usize, cols: usize) -> Self {
+        FenwickTree2D {
+            rows,
+            cols,
+            tree: vec![vec![T::default(); cols + 1]; rows + 1],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn add(&mut self, row: usize, col: usize, delta: T) {
+        assert!(
+            row < self.rows && col < self.cols,
+            "cell ({}, {}) out of bounds",
+            row,
+            col
+        );
+        let mut i = row + 1;
+        while i <= self.rows {
+            let mut j = col + 1;
+            while j <= self.cols {
+                self.tree[i][j] += delta;
+                j += lowbit(j);
+            }
+            i += lowbit(i);
+        }
+    }
+
+    /// 左上角 rows x cols 区域的和
+    pub fn prefix_sum(&self, rows: usize, cols: usize) -> T {
+        assert!(rows <= self.rows && cols <= self.cols);
+        let mut sum = T::default();
+        let mut i = rows;
+        while i > 0 {
+            let mut j = cols;
+            while j > 0 {
+                sum += self.tree[i][j];
+                j -= lowbit(j);
+            }
+            i -= lowbit(i);
+        }
+        sum
+    }
+
+    /// 矩形区域的和，行和列分别由两个区间给出
+    pub fn rect_sum<R, C>(&self, rows: R, cols: C) -> T
+    where
+        R: RangeBounds<usize>,
+        C: RangeBounds<usize>,
+    {
+        let (r1, r2) = to_half_open(rows, self.rows);
+        let (c1, c2) = to_half_open(cols, self.cols);
+        let mut sum = self.prefix_sum(r2, c2) - self.prefix_sum(r1, c2);
+        sum += self.prefix_sum(r1, c1);
+        sum - self.prefix_sum(r2, c1)
+    }
+}
+
+/// 统计逆序对 (i < j 且 values[i] > values[j]) 的个数，可以用来衡量数组的有序程度
+pub fn count_inversions<T: Ord>(values: &[T]) -> u64 {
+    // 离散化：把每个值换成它在去重排序后的数组中的排名
+    let mut sorted: Vec<&T> = values.iter().collect();
+    sorted.sort();
+    sorted.dedup();
+
+    let mut seen: FenwickTree<u64> = FenwickTree::new(sorted.len());
+    let mut inversions = 0;
+    for (i, value) in values.iter().enumerate() {
+        let rank = sorted.binary_search(&value).unwrap();
+        // 前面已经出现的 i 个元素中，不大于 value 的有 prefix_sum(rank + 1) 个
+        inversions += i as u64 - seen.prefix_sum(rank + 1);
+        seen.add(rank, 1);
+    }
+    inversions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_fenwick_tree() {
+        let mut tree = FenwickTree::from_slice(&[3, 0, 2, 5, 1]);
+        assert_eq!(tree.prefix_sum(0), 0);
+        assert_eq!(tree.prefix_sum(5), 11);
+        assert_eq!(tree.range_sum(1..4), 7);
+        tree.add(1, 4);
+        assert_eq!(tree.get(1), 4);
+        assert_eq!(tree.range_sum(..), 15);
+
+        // 前缀和依次为 3, 7, 9, 14, 15
+        assert_eq!(tree.lower_bound(0), 0);
+        assert_eq!(tree.lower_bound(3), 0);
+        assert_eq!(tree.lower_bound(4), 1);
+        assert_eq!(tree.lower_bound(9), 2);
+        assert_eq!(tree.lower_bound(10), 3);
+        assert_eq!(tree.lower_bound(15), 4);
+        assert_eq!(tree.lower_bound(16), 5);
+
+        let empty: FenwickTree<i32> = FenwickTree::new(0);
+        assert_eq!(empty.lower_bound(1), 0);
+    }
+
+    #[test]
+    fn test_fenwick_tree_against_naive() {
+        let mut rng = StdRng::seed_from_u64(39);
+        for n in [1, 2, 3, 8, 13, 64, 100] {
+            // values 只做单点加非负数，保证 lower_bound 的前提成立；
+            // range_values 对应 RangeFenwickTree，做任意的区间加
+            let mut values: Vec<i64> = (0..n).map(|_| rng.gen_range(0..20)).collect();
+            let mut range_values = values.clone();
+            let mut tree = FenwickTree::from_slice(&values);
+            let mut range_tree = RangeFenwickTree::new(n);
+            for (i, &v) in values.iter().enumerate() {
+                range_tree.add(i..=i, v);
+            }
+            for _ in 0..300 {
+                let l = rng.gen_range(0..=n);
+                let r = rng.gen_range(l..=n);
+                assert_eq!(tree.range_sum(l..r), values[l..r].iter().sum::<i64>());
+                assert_eq!(
+                    range_tree.range_sum(l..r),
+                    range_values[l..r].iter().sum::<i64>()
+                );
+
+                let target = rng.gen_range(0..20 * n as i64);
+                let mut prefix = 0;
+                let expected = (0..n)
+                    .find(|&i| {
+                        prefix += values[i];
+                        prefix >= target
+                    })
+                    .unwrap_or(n);
+                assert_eq!(tree.lower_bound(target), expected);
+
+                let p = rng.gen_range(0..n);
+                let delta = rng.gen_range(0..10);
+                values[p] += delta;
+                tree.add(p, delta);
+
+                let delta = rng.gen_range(-10..10);
+                range_values[l..r].iter_mut().for_each(|v| *v += delta);
+                range_tree.add(l..r, delta);
+            }
+        }
+    }
+
+    #[test]
+    fn test_range_fenwick_tree_unsigned() {
+        let mut tree = RangeFenwickTree::<u64>::new(5);
+        tree.add(1..3, 2);
+        tree.add(2.., 5);
+        tree.add(..1, 1);
+        // a = [1, 2, 7, 5, 5]
+        assert_eq!(tree.prefix_sum(0), 0);
+        assert_eq!(tree.prefix_sum(3), 10);
+        assert_eq!(tree.range_sum(..), 20);
+        assert_eq!(tree.range_sum(3..), 10);
+        assert_eq!(tree.range_sum(2..3), 7);
+    }
+
+    #[test]
+    fn test_fenwick_tree_2d_against_naive() {
+        let mut rng = StdRng::seed_from_u64(390);
+        let (rows, cols) = (9, 14);
+        let mut grid = vec![vec![0i32; cols]; rows];
+        let mut tree = FenwickTree2D::new(rows, cols);
+        for _ in 0..1000 {
+            let (r, c) = (rng.gen_range(0..rows), rng.gen_range(0..cols));
+            let delta = rng.gen_range(-5..=5);
+            grid[r][c] += delta;
+            tree.add(r, c, delta);
+
+            let r1 = rng.gen_range(0..=rows);
+            let r2 = rng.gen_range(r1..=rows);
+            let c1 = rng.gen_range(0..=cols);
+            let c2 = rng.gen_range(c1..=cols);
+            let naive: i32 = grid[r1..r2]
+                .iter()
+                .map(|row| row[c1..c2].iter().sum::<i32>())
+                .sum();
+            assert_eq!(tree.rect_sum(r1..r2, c1..c2), naive);
+        }
+        assert_eq!(tree.rect_sum(.., ..), grid.iter().flatten().sum::<i32>());
+    }
+
+    #[test]
+    fn test_count_inversions() {
+        assert_eq!(count_inversions::<i32>(&[]), 0);
+        assert_eq!(count_inversions(&[1, 2, 3, 4]), 0);
+        assert_eq!(count_inversions(&[4, 3, 2, 1]), 6);
+        assert_eq!(count_inversions(&[2, 2, 1, 2]), 2);
+        assert_eq!(count_inversions(&["b", "a", "c"]), 1);
+
+        let mut rng = StdRng::seed_from_u64(3900);
+        let values: Vec<i32> = (0..300).map(|_| rng.gen_range(0..50)).collect();
+        let mut naive = 0;
+        for i in 0..values.len() {
+            for j in i + 1..values.len() {
+                if values[i] > values[j] {
+                    naive += 1;
+                }
+            }
+        }
+        assert_eq!(count_inversions(&values), naive);
+    }
+}
@@ -0,0 +1,760 @@
+/*
+    开放寻址哈希表（Robin Hood 线性探测）
+
+    所有键值对直接存放在一个长度为 2 的幂的数组中。每个元素记录自己的哈希值，
+    “探测距离”是它当前的位置与理想位置（哈希值 & mask）之间的距离。
+
+    - 插入：从理想位置开始线性探测，遇到探测距离比自己小的元素（“富人”）就与它交换，
+      然后继续为被换出来的元素找位置。这样所有元素的探测距离都比较平均。
+    - 查找：探测到空位，或者遇到探测距离比当前已探测的距离还小的元素时，
+      就可以确定键不存在，不需要扫描到空位为止。
+    - 删除：不使用墓碑，而是把后面探测距离大于 0 的元素依次向前移动一格（backward shift），
+      直到遇到空位或者恰好在理想位置上的元素。
+
+    装载率超过 7/8 时容量翻倍。哈希函数通过 BuildHasher 指定，默认与 std 相同，
+    对整数键可以换成更快的 FxBuildHasher。
+*/
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use std::mem;
+use std::ops::Index;
+
+/// rustc 使用的 FxHash：每次写入一个字就做一次乘法，对整数键非常快，但不抗碰撞攻击
+#[derive(Default, Clone, Copy)]
+pub struct FxHasher {
+    hash: u64,
+}
+
+const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+impl FxHasher {
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(FX_SEED);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add_to_hash(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        for &byte in chunks.remainder() {
+            self.add_to_hash(byte as u64);
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.add_to_hash(i);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        // 低位决定桶的位置，而乘法结果的高位更均匀，这里把高位折叠下来
+        self.hash ^ (self.hash >> 32)
+    }
+}
+
+pub type FxBuildHasher = BuildHasherDefault<FxHasher>;
+
+struct Slot<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+pub struct FlatHashMap<K, V, S = RandomState> {
+    slots: Vec<Option<Slot<K, V>>>,
+    len: usize,
+    hash_builder: S,
+}
+
+// 容纳 n 个元素所需的槽位数
+fn slots_for(n: usize) -> usize {
+    if n == 0 {
+        return 0;
+    }
+    (n.checked_mul(8).expect("capacity overflow") / 7 + 1)
+        .next_power_of_two()
+        .max(8)
+}
+
+impl<K: Hash + Eq, V> FlatHashMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S: Default> Default for FlatHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> FlatHashMap<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
+        FlatHashMap {
+            slots: Vec::new(),
+            len: 0,
+            hash_builder,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 不扩容的情况下最多能容纳的元素个数
+    pub fn capacity(&self) -> usize {
+        self.slots.len() / 8 * 7
+    }
+
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.slots.iter(),
+            remaining: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            inner: self.slots.iter_mut(),
+            remaining: self.len,
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
+        self.iter_mut().map(|(_, v)| v)
+    }
+
+    fn mask(&self) -> usize {
+        self.slots.len() - 1
+    }
+
+    fn ideal(&self, hash: u64) -> usize {
+        hash as usize & self.mask()
+    }
+
+    fn probe_distance(&self, hash: u64, index: usize) -> usize {
+        index.wrapping_sub(self.ideal(hash)) & self.mask()
+    }
+
+    // 插入一个确定不存在的键，调用前必须保证还有空位。返回新元素最终所在的位置
+    fn insert_new(&mut self, hash: u64, key: K, value: V) -> usize {
+        let mask = self.mask();
+        let mut carry = Slot { hash, key, value };
+        let mut index = self.ideal(hash);
+        let mut distance = 0;
+        let mut placed_at = None;
+        loop {
+            match &mut self.slots[index] {
+                None => {
+                    self.slots[index] = Some(carry);
+                    self.len += 1;
+                    return placed_at.unwrap_or(index);
+                }
+                Some(existing) => {
+                    let existing_distance = index.wrapping_sub(existing.hash as usize) & mask;
+                    if existing_distance < distance {
+                        // 劫富济贫：占据这个位置，继续为被换出的元素寻找位置
+                        mem::swap(existing, &mut carry);
+                        placed_at.get_or_insert(index);
+                        distance = existing_distance;
+                    }
+                }
+            }
+            index = (index + 1) & mask;
+            distance += 1;
+        }
+    }
+
+    // 删除 index 处的元素，并把后面的元素向前移动
+    fn remove_at(&mut self, index: usize) -> Slot<K, V> {
+        let removed = self.slots[index].take().unwrap();
+        self.len -= 1;
+        let mut hole = index;
+        loop {
+            let next = (hole + 1) & self.mask();
+            match &self.slots[next] {
+                Some(slot) if self.probe_distance(slot.hash, next) > 0 => {
+                    self.slots[hole] = self.slots[next].take();
+                    hole = next;
+                }
+                _ => break,
+            }
+        }
+        removed
+    }
+
+    // 重新分配 new_slots 个槽位，并把所有元素重新插入
+    fn resize(&mut self, new_slots: usize) {
+        let old = mem::take(&mut self.slots);
+        self.slots.resize_with(new_slots, || None);
+        self.len = 0;
+        for slot in old.into_iter().flatten() {
+            self.insert_new(slot.hash, slot.key, slot.value);
+        }
+    }
+}
+
+impl<K, V, S> FlatHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let mut map = Self::with_hasher(hash_builder);
+        map.reserve(capacity);
+        map
+    }
+
+    fn hash_key<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash_builder.hash_one(key)
+    }
+
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mut index = self.ideal(hash);
+        let mut distance = 0;
+        while let Some(slot) = &self.slots[index] {
+            // 如果键存在，它应该在这个比它“富”的元素之前出现
+            if self.probe_distance(slot.hash, index) < distance {
+                return None;
+            }
+            if slot.hash == hash && slot.key.borrow() == key {
+                return Some(index);
+            }
+            index = (index + 1) & self.mask();
+            distance += 1;
+        }
+        None
+    }
+
+    /// 保证至少还能再插入 additional 个元素而不扩容
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("capacity overflow");
+        if needed > self.capacity() {
+            self.resize(slots_for(needed));
+        }
+    }
+
+    /// 把容量缩小到刚好容纳当前的元素
+    pub fn shrink_to_fit(&mut self) {
+        let slots = slots_for(self.len);
+        if slots < self.slots.len() {
+            self.resize(slots);
+        }
+    }
+
+    /// 插入键值对，键已经存在时替换并返回旧的值
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let hash = self.hash_key(&key);
+        if let Some(index) = self.find(hash, &key) {
+            let slot = self.slots[index].as_mut().unwrap();
+            return Some(mem::replace(&mut slot.value, value));
+        }
+        self.reserve(1);
+        self.insert_new(hash, key, value);
+        None
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash_key(key), key)?;
+        self.slots[index].as_ref().map(|slot| &slot.value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash_key(key), key)?;
+        self.slots[index].as_mut().map(|slot| &mut slot.value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(self.hash_key(key), key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash_key(key), key)?;
+        let slot = self.remove_at(index);
+        Some((slot.key, slot.value))
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hash = self.hash_key(&key);
+        match self.find(hash, &key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry {
+                map: self,
+                hash,
+                key,
+            }),
+        }
+    }
+}
+
+pub enum Entry<'a, K, V, S> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+pub struct OccupiedEntry<'a, K, V, S> {
+    map: &'a mut FlatHashMap<K, V, S>,
+    index: usize,
+}
+
+pub struct VacantEntry<'a, K, V, S> {
+    map: &'a mut FlatHashMap<K, V, S>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K, V, S> Entry<'a, K, V, S> {
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+}
+
+impl<'a, K, V, S> OccupiedEntry<'a, K, V, S> {
+    fn slot(&self) -> &Slot<K, V> {
+        self.map.slots[self.index].as_ref().unwrap()
+    }
+
+    fn slot_mut(&mut self) -> &mut Slot<K, V> {
+        self.map.slots[self.index].as_mut().unwrap()
+    }
+
+    pub fn key(&self) -> &K {
+        &self.slot().key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.slot().value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.slot_mut().value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.slots[self.index].as_mut().unwrap().value
+    }
+
+    /// 替换值并返回旧的值
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        let slot = self.map.remove_at(self.index);
+        (slot.key, slot.value)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+impl<'a, K, V, S> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        let map = self.map;
+        if map.len + 1 > map.capacity() {
+            map.resize(slots_for(map.len + 1));
+        }
+        let index = map.insert_new(self.hash, self.key, value);
+        &mut map.slots[index].as_mut().unwrap().value
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    inner: std::slice::Iter<'a, Option<Slot<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slot = self.inner.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((&slot.key, &slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+pub struct IterMut<'a, K, V> {
+    inner: std::slice::IterMut<'a, Option<Slot<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slot = self.inner.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((&slot.key, &mut slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+pub struct IntoIter<K, V> {
+    inner: std::vec::IntoIter<Option<Slot<K, V>>>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slot = self.inner.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((slot.key, slot.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V, S> IntoIterator for FlatHashMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter {
+            remaining: self.len,
+            inner: self.slots.into_iter(),
+        }
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a FlatHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut FlatHashMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> IterMut<'a, K, V> {
+        self.iter_mut()
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for FlatHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for FlatHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, Q, V, S> Index<&Q> for FlatHashMap<K, V, S>
+where
+    K: Hash + Eq + Borrow<Q>,
+    Q: Hash + Eq + ?Sized,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("key not found in FlatHashMap")
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for FlatHashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_basic_operations() {
+        let mut map = FlatHashMap::new();
+        assert!(map.is_empty());
+        assert_eq!(map.insert("a".to_string(), 1), None);
+        assert_eq!(map.insert("b".to_string(), 2), None);
+        assert_eq!(map.insert("a".to_string(), 3), Some(1));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("a"), Some(&3));
+        assert_eq!(map["b"], 2);
+        assert!(map.contains_key("b"));
+        *map.get_mut("b").unwrap() += 10;
+        assert_eq!(map.remove("b"), Some(12));
+        assert_eq!(map.remove("b"), None);
+        assert_eq!(map.len(), 1);
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.get("a"), None);
+    }
+
+    #[test]
+    fn test_entry_api() {
+        let mut counts: FlatHashMap<char, usize, FxBuildHasher> = FlatHashMap::default();
+        for c in "hello world".chars() {
+            *counts.entry(c).or_insert(0) += 1;
+        }
+        assert_eq!(counts[&'l'], 3);
+        assert_eq!(counts[&'o'], 2);
+
+        counts.entry('h').and_modify(|n| *n += 100).or_default();
+        counts.entry('z').and_modify(|n| *n += 100).or_default();
+        assert_eq!(counts[&'h'], 101);
+        assert_eq!(counts[&'z'], 0);
+
+        match counts.entry('l') {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(entry.insert(7), 3);
+                assert_eq!(entry.remove(), 7);
+            }
+            Entry::Vacant(_) => unreachable!(),
+        }
+        assert!(!counts.contains_key(&'l'));
+        match counts.entry('q') {
+            Entry::Vacant(entry) => assert_eq!(*entry.insert(5), 5),
+            Entry::Occupied(_) => unreachable!(),
+        }
+        assert_eq!(counts.get(&'q'), Some(&5));
+    }
+
+    #[test]
+    fn test_reserve_shrink_and_iteration() {
+        let mut map: FlatHashMap<u64, u64, FxBuildHasher> = FlatHashMap::default();
+        map.reserve(1000);
+        let capacity = map.capacity();
+        assert!(capacity >= 1000);
+        map.extend((0..1000).map(|i| (i, i * i)));
+        assert_eq!(map.capacity(), capacity);
+
+        for i in 0..900 {
+            map.remove(&i);
+        }
+        map.shrink_to_fit();
+        assert!(map.capacity() < capacity && map.capacity() >= 100);
+        assert_eq!(map.len(), 100);
+
+        for (_, v) in map.iter_mut() {
+            *v += 1;
+        }
+        let mut items: Vec<(u64, u64)> = map.iter().map(|(&k, &v)| (k, v)).collect();
+        items.sort();
+        let expected: Vec<(u64, u64)> = (900..1000).map(|i| (i, i * i + 1)).collect();
+        assert_eq!(items, expected);
+        assert_eq!(map.iter().len(), 100);
+        assert_eq!(map.keys().count(), 100);
+        assert_eq!(
+            map.values().sum::<u64>(),
+            expected.iter().map(|&(_, v)| v).sum()
+        );
+
+        let mut owned: Vec<(u64, u64)> = map.into_iter().collect();
+        owned.sort();
+        assert_eq!(owned, expected);
+
+        let mut empty: FlatHashMap<u64, u64> = FlatHashMap::new();
+        empty.shrink_to_fit();
+        assert_eq!(empty.capacity(), 0);
+        assert_eq!(empty.get(&1), None);
+    }
+
+    // 所有键都落在同一个理想位置上，专门检验探测和向前移动的逻辑
+    #[derive(Default, Clone)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn write(&mut self, _: &[u8]) {}
+        fn finish(&self) -> u64 {
+            3
+        }
+    }
+
+    #[test]
+    fn test_collisions_and_backward_shift() {
+        let mut map: FlatHashMap<u32, u32, BuildHasherDefault<ConstantHasher>> =
+            FlatHashMap::default();
+        for i in 0..50 {
+            map.insert(i, i);
+        }
+        for i in (0..50).step_by(3) {
+            assert_eq!(map.remove(&i), Some(i));
+        }
+        for i in 0..50 {
+            assert_eq!(map.get(&i), if i % 3 == 0 { None } else { Some(&i) });
+        }
+    }
+
+    #[test]
+    fn test_against_std_hash_map() {
+        let mut rng = StdRng::seed_from_u64(43);
+        let mut map: FlatHashMap<u16, u32, FxBuildHasher> = FlatHashMap::default();
+        let mut std_map: HashMap<u16, u32> = HashMap::new();
+        for _ in 0..50_000 {
+            let key = rng.gen_range(0..2_000);
+            match rng.gen_range(0..4) {
+                0 | 1 => {
+                    let value = rng.gen();
+                    assert_eq!(map.insert(key, value), std_map.insert(key, value));
+                }
+                2 => assert_eq!(map.remove(&key), std_map.remove(&key)),
+                _ => {
+                    *map.entry(key).or_default() += 1;
+                    *std_map.entry(key).or_default() += 1;
+                }
+            }
+            assert_eq!(map.len(), std_map.len());
+        }
+        for (key, value) in &std_map {
+            assert_eq!(map.get(key), Some(value));
+        }
+        assert_eq!(map.iter().count(), std_map.len());
+    }
+}
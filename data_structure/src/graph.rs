@@ -1,5 +1,8 @@
 use core::fmt;
+use std::borrow::Borrow;
 use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::ops::Add;
 
 #[derive(Debug, Clone)]
 pub struct NodeNotInGraph;
@@ -10,59 +13,287 @@ impl fmt::Display for NodeNotInGraph {
     }
 }
 
-pub struct DirectedGraph {
-    adjacency_table: HashMap<String, Vec<(String, i32)>>,
+/// 在两个节点之间重复添加边时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParallelEdges {
+    /// 保留所有的边（多重图）
+    #[default]
+    Allow,
+    /// 用新边的权重替换已有的边
+    Replace,
+    /// 把新边的权重累加到已有的边上
+    Sum,
 }
 
-pub trait Graph {
-    fn new() -> Self;
-    fn adjacency_table_mutable(&mut self) -> &mut HashMap<String, Vec<(String, i32)>>;
-    fn adjacency_table(&self) -> &HashMap<String, Vec<(String, i32)>>;
+pub struct DirectedGraph<N, W> {
+    adjacency_table: HashMap<N, Vec<(N, W)>>,
+    parallel_edges: ParallelEdges,
+}
 
-    fn add_node(&mut self, node: &str) -> bool {
-        match self.adjacency_table().get(node) {
+impl<N, W> Graph<N, W> for DirectedGraph<N, W>
+where
+    N: Hash + Eq + Clone,
+    W: Clone + Add<Output = W>,
+{
+    fn with_parallel_edges(parallel_edges: ParallelEdges) -> Self {
+        DirectedGraph {
+            adjacency_table: HashMap::new(),
+            parallel_edges,
+        }
+    }
+    fn is_directed(&self) -> bool {
+        true
+    }
+    fn parallel_edges(&self) -> ParallelEdges {
+        self.parallel_edges
+    }
+    fn adjacency_table_mutable(&mut self) -> &mut HashMap<N, Vec<(N, W)>> {
+        &mut self.adjacency_table
+    }
+    fn adjacency_table(&self) -> &HashMap<N, Vec<(N, W)>> {
+        &self.adjacency_table
+    }
+}
+
+/// 以邻接表存储的图，节点类型为 N，边权类型为 W
+///
+/// 查询类的方法都接受 `&Q`（`N: Borrow<Q>`），例如 `N = String` 时可以直接传入 `&str`，
+/// 不需要为了查询而分配新的 String。
+pub trait Graph<N, W>
+where
+    N: Hash + Eq + Clone,
+    W: Clone + Add<Output = W>,
+{
+    fn with_parallel_edges(parallel_edges: ParallelEdges) -> Self;
+    fn is_directed(&self) -> bool;
+    fn parallel_edges(&self) -> ParallelEdges;
+    fn adjacency_table_mutable(&mut self) -> &mut HashMap<N, Vec<(N, W)>>;
+    fn adjacency_table(&self) -> &HashMap<N, Vec<(N, W)>>;
+
+    fn new() -> Self
+    where
+        Self: Sized,
+    {
+        Self::with_parallel_edges(ParallelEdges::default())
+    }
+
+    fn add_node(&mut self, node: N) -> bool {
+        match self.adjacency_table().get(&node) {
             None => {
-                self.adjacency_table_mutable()
-                    .insert((*node).to_string(), Vec::new());
+                self.adjacency_table_mutable().insert(node, Vec::new());
                 true
             }
             _ => false,
         }
     }
 
-    fn add_edge(&mut self, edge: (&str, &str, i32)) {
-        self.add_node(edge.0);
-        self.add_node(edge.1);
+    /// 添加一条边，无向图会同时添加两个方向（自环只添加一次）
+    fn add_edge(&mut self, edge: (N, N, W)) {
+        let (from, to, weight) = edge;
+        self.add_node(from.clone());
+        self.add_node(to.clone());
+
+        if !self.is_directed() && from != to {
+            self.add_arc(to.clone(), from.clone(), weight.clone());
+        }
+        self.add_arc(from, to, weight);
+    }
+
+    /// 在邻接表中添加一条 from -> to 的有向弧，按照 parallel_edges 处理重复的弧
+    fn add_arc(&mut self, from: N, to: N, weight: W) {
+        let policy = self.parallel_edges();
+        let neighbours = self.adjacency_table_mutable().entry(from).or_default();
+        let existing = neighbours.iter_mut().find(|(n, _)| *n == to);
+        match (policy, existing) {
+            (ParallelEdges::Replace, Some((_, w))) => *w = weight,
+            (ParallelEdges::Sum, Some((_, w))) => *w = w.clone() + weight,
+            _ => neighbours.push((to, weight)),
+        }
+    }
 
-        self.adjacency_table_mutable()
-            .entry(edge.0.to_string())
-            .and_modify(|e| {
-                e.push((edge.1.to_string(), edge.2));
-            });
+    /// 删除一个节点以及所有与它相连的边，节点不存在时返回 false
+    fn remove_node<Q>(&mut self, node: &Q) -> bool
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.adjacency_table_mutable().remove(node).is_none() {
+            return false;
+        }
+        for neighbours in self.adjacency_table_mutable().values_mut() {
+            neighbours.retain(|(n, _)| n.borrow() != node);
+        }
+        true
     }
 
-    fn neighbours(&self, node: &str) -> Result<&Vec<(String, i32)>, NodeNotInGraph> {
+    /// 删除 from 和 to 之间的所有边（包括平行边），返回是否删除了至少一条边
+    fn remove_edge<Q>(&mut self, from: &Q, to: &Q) -> bool
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let directed = self.is_directed();
+        let table = self.adjacency_table_mutable();
+        let removed = match table.get_mut(from) {
+            Some(neighbours) => {
+                let before = neighbours.len();
+                neighbours.retain(|(n, _)| n.borrow() != to);
+                neighbours.len() != before
+            }
+            None => false,
+        };
+        if removed && !directed {
+            if let Some(neighbours) = table.get_mut(to) {
+                neighbours.retain(|(n, _)| n.borrow() != from);
+            }
+        }
+        removed
+    }
+
+    fn has_edge<Q>(&self, from: &Q, to: &Q) -> bool
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.adjacency_table()
+            .get(from)
+            .is_some_and(|neighbours| neighbours.iter().any(|(n, _)| n.borrow() == to))
+    }
+
+    fn neighbours<Q>(&self, node: &Q) -> Result<&Vec<(N, W)>, NodeNotInGraph>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
         match self.adjacency_table().get(node) {
             None => Err(NodeNotInGraph),
             Some(i) => Ok(i),
         }
     }
 
-    fn contains(&self, node: &str) -> bool {
-        self.adjacency_table().get(node).is_some()
+    /// 出度；无向图中即与节点相连的边数（自环计一次）
+    fn degree<Q>(&self, node: &Q) -> Result<usize, NodeNotInGraph>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.neighbours(node).map(|neighbours| neighbours.len())
+    }
+
+    /// 入度，需要扫描整个邻接表；无向图中与 degree 相同
+    fn in_degree<Q>(&self, node: &Q) -> Result<usize, NodeNotInGraph>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if !self.is_directed() {
+            return self.degree(node);
+        }
+        if !self.contains(node) {
+            return Err(NodeNotInGraph);
+        }
+        Ok(self
+            .adjacency_table()
+            .values()
+            .flatten()
+            .filter(|(n, _)| n.borrow() == node)
+            .count())
+    }
+
+    fn contains<Q>(&self, node: &Q) -> bool
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.adjacency_table().contains_key(node)
     }
 
-    fn nodes(&self) -> HashSet<&String> {
+    fn nodes<'a>(&'a self) -> HashSet<&'a N>
+    where
+        W: 'a,
+    {
         self.adjacency_table().keys().collect()
     }
 
-    fn edges(&self) -> Vec<(&String, &String, i32)> {
+    fn edges<'a>(&'a self) -> Vec<(&'a N, &'a N, W)>
+    where
+        W: 'a,
+    {
         let mut edges = Vec::new();
         for (from_node, from_node_neighbours) in self.adjacency_table() {
             for (to_node, weight) in from_node_neighbours {
-                edges.push((from_node, to_node, *weight));
+                edges.push((from_node, to_node, weight.clone()));
             }
         }
         edges
     }
 }
+
+#[cfg(test)]
+mod test_directed_graph {
+    use super::{DirectedGraph, Graph, ParallelEdges};
+
+    #[test]
+    fn test_add_edge() {
+        let mut graph = DirectedGraph::new();
+        graph.add_edge(("a", "b", 5));
+        graph.add_edge(("b", "c", 10));
+        graph.add_edge(("c", "a", 7));
+        assert!(graph.has_edge("a", "b"));
+        assert!(!graph.has_edge("b", "a"));
+        assert_eq!(graph.edges().len(), 3);
+        assert_eq!(graph.neighbours("b").unwrap(), &vec![("c", 10)]);
+        assert!(graph.neighbours("d").is_err());
+    }
+
+    #[test]
+    fn test_integer_nodes_and_float_weights() {
+        let mut graph: DirectedGraph<u32, f64> = DirectedGraph::new();
+        graph.add_edge((1, 2, 0.5));
+        graph.add_edge((1, 3, 1.5));
+        graph.add_edge((3, 2, 2.0));
+        assert_eq!(graph.degree(&1).unwrap(), 2);
+        assert_eq!(graph.in_degree(&2).unwrap(), 2);
+        assert_eq!(graph.in_degree(&1).unwrap(), 0);
+        assert!(graph.degree(&4).is_err());
+    }
+
+    #[test]
+    fn test_string_nodes_borrowed_lookup() {
+        let mut graph: DirectedGraph<String, i32> = DirectedGraph::new();
+        graph.add_edge(("a".to_string(), "b".to_string(), 1));
+        assert!(graph.contains("a"));
+        assert!(graph.has_edge("a", "b"));
+        assert!(graph.remove_edge("a", "b"));
+        assert!(!graph.remove_edge("a", "b"));
+    }
+
+    #[test]
+    fn test_remove_node() {
+        let mut graph = DirectedGraph::new();
+        graph.add_edge(("a", "b", 1));
+        graph.add_edge(("b", "c", 2));
+        graph.add_edge(("c", "b", 3));
+        assert!(graph.remove_node("b"));
+        assert!(!graph.remove_node("b"));
+        assert!(!graph.contains("b"));
+        assert!(graph.edges().is_empty());
+        assert_eq!(graph.nodes().len(), 2);
+    }
+
+    #[test]
+    fn test_parallel_edges() {
+        let mut allow = DirectedGraph::new();
+        let mut replace = DirectedGraph::with_parallel_edges(ParallelEdges::Replace);
+        let mut sum = DirectedGraph::with_parallel_edges(ParallelEdges::Sum);
+        for graph in [&mut allow, &mut replace, &mut sum] {
+            graph.add_edge(("a", "b", 2));
+            graph.add_edge(("a", "b", 3));
+        }
+        assert_eq!(allow.neighbours("a").unwrap(), &vec![("b", 2), ("b", 3)]);
+        assert_eq!(replace.neighbours("a").unwrap(), &vec![("b", 3)]);
+        assert_eq!(sum.neighbours("a").unwrap(), &vec![("b", 5)]);
+        assert!(allow.remove_edge("a", "b"));
+        assert_eq!(allow.degree("a").unwrap(), 0);
+    }
+}
@@ -3,80 +3,44 @@
     This problem requires you to implement a basic graph functio
 */
 
-use std::collections::{HashMap, HashSet};
-use std::fmt;
-#[derive(Debug, Clone)]
-pub struct NodeNotInGraph;
-impl fmt::Display for NodeNotInGraph {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "accessing a node that is not in the graph")
-    }
-}
-pub struct UndirectedGraph {
-    adjacency_table: HashMap<String, Vec<(String, i32)>>,
+use crate::graph::{Graph, ParallelEdges};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Add;
+
+pub struct UndirectedGraph<N, W> {
+    adjacency_table: HashMap<N, Vec<(N, W)>>,
+    parallel_edges: ParallelEdges,
 }
-impl Graph for UndirectedGraph {
-    fn new() -> UndirectedGraph {
+impl<N, W> Graph<N, W> for UndirectedGraph<N, W>
+where
+    N: Hash + Eq + Clone,
+    W: Clone + Add<Output = W>,
+{
+    fn with_parallel_edges(parallel_edges: ParallelEdges) -> UndirectedGraph<N, W> {
         UndirectedGraph {
             adjacency_table: HashMap::new(),
+            parallel_edges,
         }
     }
-    fn adjacency_table_mutable(&mut self) -> &mut HashMap<String, Vec<(String, i32)>> {
-        &mut self.adjacency_table
-    }
-    fn adjacency_table(&self) -> &HashMap<String, Vec<(String, i32)>> {
-        &self.adjacency_table
-    }
-    fn add_edge(&mut self, edge: (&str, &str, i32)) {
-        let (from, to, weight) = edge;
-        self.add_node(from);
-        self.add_node(to);
-
-        // Add edge for `from -> to`
-        if let Some(neighbors) = self.adjacency_table_mutable().get_mut(from) {
-            neighbors.push((to.to_string(), weight));
-        }
-
-        // Add edge for `to -> from` since it's an undirected graph
-        if let Some(neighbors) = self.adjacency_table_mutable().get_mut(to) {
-            neighbors.push((from.to_string(), weight));
-        }
-    }
-}
-pub trait Graph {
-    fn new() -> Self;
-    fn adjacency_table_mutable(&mut self) -> &mut HashMap<String, Vec<(String, i32)>>;
-    fn adjacency_table(&self) -> &HashMap<String, Vec<(String, i32)>>;
-    fn add_node(&mut self, node: &str) -> bool {
-        if !self.adjacency_table().contains_key(node) {
-            self.adjacency_table_mutable()
-                .insert(node.to_string(), Vec::new());
-            true
-        } else {
-            false
-        }
+    fn is_directed(&self) -> bool {
+        false
     }
-    fn add_edge(&mut self, edge: (&str, &str, i32));
-    fn contains(&self, node: &str) -> bool {
-        self.adjacency_table().get(node).is_some()
+    fn parallel_edges(&self) -> ParallelEdges {
+        self.parallel_edges
     }
-    fn nodes(&self) -> HashSet<&String> {
-        self.adjacency_table().keys().collect()
+    fn adjacency_table_mutable(&mut self) -> &mut HashMap<N, Vec<(N, W)>> {
+        &mut self.adjacency_table
     }
-    fn edges(&self) -> Vec<(&String, &String, i32)> {
-        let mut edges = Vec::new();
-        for (from_node, from_node_neighbours) in self.adjacency_table() {
-            for (to_node, weight) in from_node_neighbours {
-                edges.push((from_node, to_node, *weight));
-            }
-        }
-        edges
+    fn adjacency_table(&self) -> &HashMap<N, Vec<(N, W)>> {
+        &self.adjacency_table
     }
 }
 #[cfg(test)]
 mod test_undirected_graph {
     use super::Graph;
     use super::UndirectedGraph;
+    use crate::graph::ParallelEdges;
     #[test]
     fn test_add_edge() {
         let mut graph = UndirectedGraph::new();
@@ -84,15 +48,37 @@ mod test_undirected_graph {
         graph.add_edge(("b", "c", 10));
         graph.add_edge(("c", "a", 7));
         let expected_edges = [
-            (&String::from("a"), &String::from("b"), 5),
-            (&String::from("b"), &String::from("a"), 5),
-            (&String::from("c"), &String::from("a"), 7),
-            (&String::from("a"), &String::from("c"), 7),
-            (&String::from("b"), &String::from("c"), 10),
-            (&String::from("c"), &String::from("b"), 10),
+            (&"a", &"b", 5),
+            (&"b", &"a", 5),
+            (&"c", &"a", 7),
+            (&"a", &"c", 7),
+            (&"b", &"c", 10),
+            (&"c", &"b", 10),
         ];
         for edge in expected_edges.iter() {
-            assert_eq!(graph.edges().contains(edge), true);
+            assert!(graph.edges().contains(edge));
         }
     }
+    #[test]
+    fn test_remove_edge_removes_both_directions() {
+        let mut graph = UndirectedGraph::new();
+        graph.add_edge((1, 2, 1.0));
+        graph.add_edge((2, 3, 1.0));
+        assert!(graph.has_edge(&2, &1));
+        assert!(graph.remove_edge(&2, &1));
+        assert!(!graph.has_edge(&1, &2));
+        assert_eq!(graph.degree(&2).unwrap(), 1);
+        assert_eq!(graph.in_degree(&3).unwrap(), 1);
+    }
+    #[test]
+    fn test_self_loop_and_sum_policy() {
+        let mut graph = UndirectedGraph::with_parallel_edges(ParallelEdges::Sum);
+        graph.add_edge(("a", "a", 1));
+        graph.add_edge(("a", "b", 2));
+        graph.add_edge(("b", "a", 3));
+        assert_eq!(graph.neighbours("a").unwrap(), &vec![("a", 1), ("b", 5)]);
+        assert_eq!(graph.neighbours("b").unwrap(), &vec![("a", 5)]);
+        assert!(graph.remove_node("a"));
+        assert_eq!(graph.degree("b").unwrap(), 0);
+    }
 }
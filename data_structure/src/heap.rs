@@ -18,8 +18,11 @@
     - 优先队列：堆常用于实现优先队列，其中根据优先级选择最优元素（在最小堆中选择最小值，在最大堆中选择最大值）。
     - 排序：堆可以用来实现堆排序（Heap Sort），堆排序的时间复杂度为 O(n log n)。
 */
+use crate::invariant::InvariantViolation;
+use crate::tree_render::{self, Shape};
 use std::cmp::Ord;
 use std::default::Default;
+use std::fmt::{Display, Write};
 
 pub struct Heap<T>
 where
@@ -92,6 +95,12 @@ where
         self.len() == 0
     }
 
+    /// 查看堆顶元素
+    pub fn peek(&self) -> Option<&T> {
+        // items[0] 是占位元素，堆中的元素从下标 1 开始
+        self.items.get(1)
+    }
+
     /// 向堆中添加一个元素
     pub fn add(&mut self, value: T) {
         self.count += 1;
@@ -112,6 +121,87 @@ where
         }
         min
     }
+
+    /// 检查堆的不变量：items 比 count 多一个占位元素，并且没有子节点比父节点更应该在堆顶
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        if self.items.len() != self.count + 1 {
+            return Err(InvariantViolation::new(
+                "Heap",
+                format!(
+                    "count is {} but items holds {} elements after the placeholder",
+                    self.count,
+                    self.items.len().saturating_sub(1)
+                ),
+            ));
+        }
+        for idx in 2..=self.count {
+            let parent = self.parent_idx(idx);
+            if (self.comparator)(&self.items[idx], &self.items[parent]) {
+                return Err(InvariantViolation::new(
+                    "Heap",
+                    format!("item {} should be above its parent {}", idx, parent),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// 调试输出
+impl<T> Heap<T>
+where
+    T: Default + Display,
+{
+    /// 用制表符画出堆对应的完全二叉树，左孩子在前
+    pub fn render_ascii(&self) -> String {
+        if self.count == 0 {
+            return "(empty)\n".to_string();
+        }
+        tree_render::render_ascii(&self.shape(1))
+    }
+
+    /// 生成 Graphviz 有向图，节点标签是元素和它在数组中的下标，
+    /// 同一层的节点用 rank=same 排在同一行
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph Heap {\n    node [shape=circle];\n");
+        for idx in 1..=self.count {
+            let label = format!("{}\nindex {}", self.items[idx], idx);
+            writeln!(
+                out,
+                "    n{} [label=\"{}\"];",
+                idx,
+                tree_render::escape_dot(&label)
+            )
+            .unwrap();
+        }
+        for idx in 2..=self.count {
+            writeln!(out, "    n{} -> n{};", self.parent_idx(idx), idx).unwrap();
+        }
+        // 第 d 层是下标 [2^d, 2^(d+1))
+        let mut level_start = 1;
+        while level_start <= self.count {
+            let level_end = (level_start * 2 - 1).min(self.count);
+            let nodes: Vec<String> = (level_start..=level_end)
+                .map(|idx| format!("n{};", idx))
+                .collect();
+            writeln!(out, "    {{ rank=same; {} }}", nodes.join(" ")).unwrap();
+            level_start *= 2;
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn shape(&self, idx: usize) -> Shape {
+        let children = [self.left_child_idx(idx), self.right_child_idx(idx)]
+            .into_iter()
+            .filter(|&child| child <= self.count)
+            .map(|child| Some(self.shape(child)))
+            .collect();
+        Shape {
+            label: self.items[idx].to_string(),
+            children,
+        }
+    }
 }
 
 // private methods
@@ -195,9 +285,12 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::invariant::run_random_ops;
+    use rand::Rng;
     #[test]
     fn test_empty_heap() {
         let mut heap = MaxHeap::new::<i32>();
+        assert_eq!(heap.peek(), None);
         assert_eq!(heap.next(), None);
     }
 
@@ -209,6 +302,7 @@ mod tests {
         heap.add(9);
         heap.add(11);
         assert_eq!(heap.len(), 4);
+        assert_eq!(heap.peek(), Some(&2));
         assert_eq!(heap.next(), Some(2));
         assert_eq!(heap.next(), Some(4));
         assert_eq!(heap.next(), Some(9));
@@ -230,4 +324,96 @@ mod tests {
         heap.add(1);
         assert_eq!(heap.next(), Some(2));
     }
+
+    #[test]
+    fn test_validate_random_ops() {
+        for (seed, mut heap) in [(48, MinHeap::new()), (480, MaxHeap::new())] {
+            let mut sorted: Vec<i32> = Vec::new();
+            let is_min = seed == 48;
+            run_random_ops(
+                seed,
+                2000,
+                &mut heap,
+                |rng, heap| {
+                    if rng.gen_bool(0.6) {
+                        let value = rng.gen_range(-50..50);
+                        heap.add(value);
+                        sorted.push(value);
+                        sorted.sort_unstable();
+                        format!("add({})", value)
+                    } else {
+                        let expected = if is_min && !sorted.is_empty() {
+                            Some(sorted.remove(0))
+                        } else {
+                            sorted.pop()
+                        };
+                        assert_eq!(heap.pop(), expected);
+                        "pop()".to_string()
+                    }
+                },
+                Heap::validate,
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let mut heap = MinHeap::new();
+        assert_eq!(heap.render_ascii(), "(empty)\n");
+        for value in [4, 2, 9, 11, 1, 7] {
+            heap.add(value);
+        }
+        assert_eq!(
+            heap.render_ascii(),
+            "1\n\
+             ├── 2\n\
+             │   ├── 11\n\
+             │   └── 4\n\
+             └── 7\n\
+             \x20   └── 9\n"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut heap = MaxHeap::new();
+        for value in [4, 2, 9, 11] {
+            heap.add(value);
+        }
+        assert_eq!(
+            heap.to_dot(),
+            r#"digraph Heap {
+    node [shape=circle];
+    n1 [label="11\nindex 1"];
+    n2 [label="9\nindex 2"];
+    n3 [label="4\nindex 3"];
+    n4 [label="2\nindex 4"];
+    n1 -> n2;
+    n1 -> n3;
+    n2 -> n4;
+    { rank=same; n1; }
+    { rank=same; n2; n3; }
+    { rank=same; n4; }
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_validate_detects_corruption() {
+        let mut heap = MinHeap::new();
+        for value in [5, 3, 8, 1] {
+            heap.add(value);
+        }
+        assert_eq!(heap.validate(), Ok(()));
+
+        // 把堆顶换成最大的元素，破坏堆序
+        heap.items.swap(1, 4);
+        let violation = heap.validate().unwrap_err();
+        assert_eq!(violation.structure, "Heap");
+
+        heap.items.swap(1, 4);
+        heap.count += 1;
+        assert!(heap.validate().is_err());
+    }
 }
@@ -0,0 +1,53 @@
+/*
+    不变量检查
+
+    各个数据结构的 validate() 会把内部表示从头到尾检查一遍，发现不变量被破坏时返回
+    InvariantViolation，说明是哪个结构、哪条规则出了问题。validate() 的代价通常是 O(n)，
+    只用于调试和测试：在每次操作之后调用它，就能知道结构是在哪一步被破坏的，
+    而不是等到很久以后才发现输出不对。
+*/
+use std::fmt;
+
+/// 数据结构的内部不变量被破坏
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvariantViolation {
+    /// 出问题的数据结构
+    pub structure: &'static str,
+    /// 被破坏的具体规则
+    pub message: String,
+}
+
+impl InvariantViolation {
+    pub fn new(structure: &'static str, message: impl Into<String>) -> Self {
+        InvariantViolation {
+            structure,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} invariant violated: {}", self.structure, self.message)
+    }
+}
+
+/// 随机操作序列测试：每一步用 step 对结构做一次随机操作，之后立即调用 validate，
+/// 一旦出错就报告是第几步、做了什么操作。其他 crate 的测试也复用这个驱动
+pub fn run_random_ops<S>(
+    seed: u64,
+    steps: usize,
+    state: &mut S,
+    mut step: impl FnMut(&mut rand::rngs::StdRng, &mut S) -> String,
+    validate: impl Fn(&S) -> Result<(), InvariantViolation>,
+) {
+    use rand::SeedableRng;
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    for i in 0..steps {
+        let op = step(&mut rng, state);
+        if let Err(violation) = validate(state) {
+            panic!("step {} ({}) broke an invariant: {}", i, op, violation);
+        }
+    }
+}
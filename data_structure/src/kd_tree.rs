@@ -0,0 +1,391 @@
+/*
+    KD 树（k-dimensional tree）
+
+    对 D 维空间中的点建立的二叉划分树。深度为 depth 的节点按第 depth % D 维划分：
+    左子树中的点在这一维上不大于节点，右子树中的点不小于节点。
+
+    树用隐式的方式保存在数组中：区间 [lo, hi) 对应的子树根在中点 mid = (lo + hi) / 2，
+    左右子树分别是 [lo, mid) 和 (mid, hi)。建树时用 quick_select_by 把当前维度的中位数
+    放到 mid，整体 O(n log n)，树的深度为 O(log n)。
+
+    查询时先进入查询点所在的一侧，只有当划分平面到查询点的距离（下界）
+    还可能优于当前结果时才进入另一侧。
+    - nearest / k_nearest：k 近邻用一个大小为 k 的大根堆保存当前最好的 k 个点
+    - within_radius：与查询点的距离不超过 radius 的所有点
+    - in_box：落在轴对齐的长方体 [min, max]（闭区间）中的所有点
+*/
+use crate::heap::Heap;
+use search::quick_select::quick_select_by;
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Sub};
+
+/// 坐标的类型，整数和浮点数都可以（坐标不能是 NaN）
+pub trait Scalar:
+    Copy + PartialOrd + Default + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+}
+
+impl<T> Scalar for T where
+    T: Copy + PartialOrd + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T>
+{
+}
+
+fn abs_diff<T: Scalar>(a: T, b: T) -> T {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn compare<T: Scalar>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// 欧几里得距离的平方，避免开方，整数坐标也能精确计算
+    SquaredEuclidean,
+    /// 曼哈顿距离：各维坐标差的绝对值之和
+    Manhattan,
+}
+
+impl Metric {
+    pub fn distance<T: Scalar, const D: usize>(self, a: &[T; D], b: &[T; D]) -> T {
+        a.iter().zip(b).fold(T::default(), |sum, (&x, &y)| {
+            sum + self.axis_distance(abs_diff(x, y))
+        })
+    }
+
+    // 只在一维上相差 diff 时的距离，也是到划分平面另一侧所有点距离的下界
+    fn axis_distance<T: Scalar>(self, diff: T) -> T {
+        match self {
+            Metric::SquaredEuclidean => diff * diff,
+            Metric::Manhattan => diff,
+        }
+    }
+}
+
+/// 查询结果：点在建树时输入数组中的下标，以及到查询点的距离
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor<T> {
+    pub index: usize,
+    pub distance: T,
+}
+
+pub struct KdTree<const D: usize, T> {
+    // (坐标, 在输入中的下标)，按隐式树的顺序排列
+    nodes: Vec<([T; D], usize)>,
+    metric: Metric,
+}
+
+impl<const D: usize, T: Scalar> KdTree<D, T> {
+    /// 使用欧几里得距离的平方建树
+    pub fn new(points: &[[T; D]]) -> Self {
+        Self::with_metric(points, Metric::SquaredEuclidean)
+    }
+
+    pub fn with_metric(points: &[[T; D]], metric: Metric) -> Self {
+        assert!(D > 0, "KdTree needs at least one dimension");
+        let mut nodes: Vec<([T; D], usize)> =
+            points.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+        Self::build(&mut nodes, 0);
+        KdTree { nodes, metric }
+    }
+
+    fn build(nodes: &mut [([T; D], usize)], depth: usize) {
+        if nodes.len() <= 1 {
+            return;
+        }
+        let axis = depth % D;
+        let mid = nodes.len() / 2;
+        quick_select_by(nodes, mid, |a, b| compare(&a.0[axis], &b.0[axis]));
+        let (left, right) = nodes.split_at_mut(mid);
+        Self::build(left, depth + 1);
+        Self::build(&mut right[1..], depth + 1);
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    /// 距离查询点最近的点
+    pub fn nearest(&self, point: &[T; D]) -> Option<Neighbor<T>> {
+        self.k_nearest(point, 1).pop()
+    }
+
+    /// 距离查询点最近的 k 个点，按距离从小到大排列
+    pub fn k_nearest(&self, point: &[T; D], k: usize) -> Vec<Neighbor<T>> {
+        if k == 0 {
+            return Vec::new();
+        }
+        // 大根堆，堆顶是当前 k 个结果中最远的
+        let mut best: Heap<(T, usize)> = Heap::new(|a, b| compare(&a.0, &b.0).is_gt());
+        self.search_nearest(point, k, 0, self.nodes.len(), 0, &mut best);
+        let mut result: Vec<Neighbor<T>> = best
+            .map(|(distance, i)| Neighbor {
+                index: self.nodes[i].1,
+                distance,
+            })
+            .collect();
+        result.reverse();
+        result
+    }
+
+    fn search_nearest(
+        &self,
+        point: &[T; D],
+        k: usize,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        best: &mut Heap<(T, usize)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let node = &self.nodes[mid].0;
+        let distance = self.metric.distance(node, point);
+        if best.len() < k {
+            best.add((distance, mid));
+        } else if distance < best.peek().unwrap().0 {
+            best.pop();
+            best.add((distance, mid));
+        }
+
+        let axis = depth % D;
+        let (near, far) = if point[axis] < node[axis] {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.search_nearest(point, k, near.0, near.1, depth + 1, best);
+        let bound = self.metric.axis_distance(abs_diff(point[axis], node[axis]));
+        if best.len() < k || bound < best.peek().unwrap().0 {
+            self.search_nearest(point, k, far.0, far.1, depth + 1, best);
+        }
+    }
+
+    /// 与查询点的距离不超过 radius 的所有点，按距离从小到大排列。
+    /// radius 与度量使用同样的单位，例如欧几里得距离的平方
+    pub fn within_radius(&self, point: &[T; D], radius: T) -> Vec<Neighbor<T>> {
+        let mut result = Vec::new();
+        self.search_radius(point, radius, 0, self.nodes.len(), 0, &mut result);
+        result.sort_by(|a, b| compare(&a.distance, &b.distance).then(a.index.cmp(&b.index)));
+        result
+    }
+
+    fn search_radius(
+        &self,
+        point: &[T; D],
+        radius: T,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        result: &mut Vec<Neighbor<T>>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (node, index) = &self.nodes[mid];
+        let distance = self.metric.distance(node, point);
+        if distance <= radius {
+            result.push(Neighbor {
+                index: *index,
+                distance,
+            });
+        }
+        let axis = depth % D;
+        let bound = self.metric.axis_distance(abs_diff(point[axis], node[axis]));
+        let reaches_other_side = bound <= radius;
+        if point[axis] < node[axis] || reaches_other_side {
+            self.search_radius(point, radius, lo, mid, depth + 1, result);
+        }
+        if point[axis] >= node[axis] || reaches_other_side {
+            self.search_radius(point, radius, mid + 1, hi, depth + 1, result);
+        }
+    }
+
+    /// 落在 [min, max]（各维都是闭区间）中的所有点的下标，从小到大排列
+    pub fn in_box(&self, min: &[T; D], max: &[T; D]) -> Vec<usize> {
+        let mut result = Vec::new();
+        self.search_box(min, max, 0, self.nodes.len(), 0, &mut result);
+        result.sort_unstable();
+        result
+    }
+
+    fn search_box(
+        &self,
+        min: &[T; D],
+        max: &[T; D],
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        result: &mut Vec<usize>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (node, index) = &self.nodes[mid];
+        if (0..D).all(|d| min[d] <= node[d] && node[d] <= max[d]) {
+            result.push(*index);
+        }
+        let axis = depth % D;
+        if min[axis] <= node[axis] {
+            self.search_box(min, max, lo, mid, depth + 1, result);
+        }
+        if node[axis] <= max[axis] {
+            self.search_box(min, max, mid + 1, hi, depth + 1, result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn brute_force<const D: usize, T: Scalar>(
+        points: &[[T; D]],
+        query: &[T; D],
+        metric: Metric,
+    ) -> Vec<Neighbor<T>> {
+        let mut all: Vec<Neighbor<T>> = points
+            .iter()
+            .enumerate()
+            .map(|(index, p)| Neighbor {
+                index,
+                distance: metric.distance(p, query),
+            })
+            .collect();
+        all.sort_by(|a, b| compare(&a.distance, &b.distance).then(a.index.cmp(&b.index)));
+        all
+    }
+
+    fn distances<T: Scalar>(neighbors: &[Neighbor<T>]) -> Vec<T> {
+        neighbors.iter().map(|n| n.distance).collect()
+    }
+
+    #[test]
+    fn test_small_example() {
+        let points = [[2, 3], [5, 4], [9, 6], [4, 7], [8, 1], [7, 2]];
+        let tree = KdTree::new(&points);
+        assert_eq!(tree.len(), 6);
+        assert_eq!(
+            tree.nearest(&[9, 2]),
+            Some(Neighbor {
+                index: 4,
+                distance: 2
+            })
+        );
+        let nearest: Vec<usize> = tree.k_nearest(&[5, 5], 2).iter().map(|n| n.index).collect();
+        assert_eq!(nearest, vec![1, 3]);
+        assert_eq!(tree.k_nearest(&[0, 0], 10).len(), 6);
+        assert_eq!(tree.in_box(&[4, 1], &[8, 4]), vec![1, 4, 5]);
+        let within: Vec<usize> = tree
+            .within_radius(&[6, 3], 5)
+            .iter()
+            .map(|n| n.index)
+            .collect();
+        assert_eq!(within, vec![1, 5]);
+
+        let manhattan = KdTree::with_metric(&points, Metric::Manhattan);
+        assert_eq!(manhattan.nearest(&[6, 6]).map(|n| n.distance), Some(3));
+
+        let empty: KdTree<2, i32> = KdTree::new(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.nearest(&[0, 0]), None);
+        assert!(empty.in_box(&[0, 0], &[1, 1]).is_empty());
+    }
+
+    #[test]
+    fn test_against_brute_force_f64() {
+        let mut rng = StdRng::seed_from_u64(47);
+        let points: Vec<[f64; 3]> = (0..500)
+            .map(|_| [rng.gen(), rng.gen(), rng.gen()])
+            .collect();
+        for metric in [Metric::SquaredEuclidean, Metric::Manhattan] {
+            let tree = KdTree::with_metric(&points, metric);
+            for _ in 0..50 {
+                let query = [rng.gen(), rng.gen(), rng.gen()];
+                let expected = brute_force(&points, &query, metric);
+                assert_eq!(tree.nearest(&query), Some(expected[0]));
+                let k = rng.gen_range(1..20);
+                assert_eq!(tree.k_nearest(&query, k), expected[..k]);
+
+                let radius = rng.gen_range(0.0..0.1);
+                let inside: Vec<Neighbor<f64>> = expected
+                    .iter()
+                    .copied()
+                    .take_while(|n| n.distance <= radius)
+                    .collect();
+                assert_eq!(tree.within_radius(&query, radius), inside);
+
+                let (a, b): ([f64; 3], [f64; 3]) = (
+                    [rng.gen(), rng.gen(), rng.gen()],
+                    [rng.gen(), rng.gen(), rng.gen()],
+                );
+                let min = [a[0].min(b[0]), a[1].min(b[1]), a[2].min(b[2])];
+                let max = [a[0].max(b[0]), a[1].max(b[1]), a[2].max(b[2])];
+                let in_box: Vec<usize> = (0..points.len())
+                    .filter(|&i| (0..3).all(|d| min[d] <= points[i][d] && points[i][d] <= max[d]))
+                    .collect();
+                assert_eq!(tree.in_box(&min, &max), in_box);
+            }
+        }
+    }
+
+    #[test]
+    fn test_against_brute_force_with_duplicates() {
+        // 整数坐标范围很小，有大量重复的坐标和相等的距离，只比较距离
+        let mut rng = StdRng::seed_from_u64(470);
+        let points: Vec<[i64; 2]> = (0..300)
+            .map(|_| [rng.gen_range(0..10), rng.gen_range(0..10)])
+            .collect();
+        for metric in [Metric::SquaredEuclidean, Metric::Manhattan] {
+            let tree = KdTree::with_metric(&points, metric);
+            for _ in 0..100 {
+                let query = [rng.gen_range(-2..12), rng.gen_range(-2..12)];
+                let expected = brute_force(&points, &query, metric);
+                let k = rng.gen_range(1..40);
+                assert_eq!(
+                    distances(&tree.k_nearest(&query, k)),
+                    distances(&expected[..k])
+                );
+                let radius = rng.gen_range(0..10);
+                let mut within: Vec<usize> = tree
+                    .within_radius(&query, radius)
+                    .iter()
+                    .map(|n| n.index)
+                    .collect();
+                within.sort_unstable();
+                let mut inside: Vec<usize> = expected
+                    .iter()
+                    .filter(|n| n.distance <= radius)
+                    .map(|n| n.index)
+                    .collect();
+                inside.sort_unstable();
+                assert_eq!(within, inside);
+
+                let min = [rng.gen_range(0..10), rng.gen_range(0..10)];
+                let max = [min[0] + rng.gen_range(0..4), min[1] + rng.gen_range(0..4)];
+                let in_box: Vec<usize> = (0..points.len())
+                    .filter(|&i| (0..2).all(|d| min[d] <= points[i][d] && points[i][d] <= max[d]))
+                    .collect();
+                assert_eq!(tree.in_box(&min, &max), in_box);
+            }
+        }
+    }
+}
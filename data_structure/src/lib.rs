@@ -1,12 +1,27 @@
-mod b_tree;
-mod binary_search_tree;
+pub mod b_tree;
+pub mod binary_search_tree;
 mod binary_search_tree1;
-mod graph;
-mod graph1;
-mod heap;
-mod linked_list;
-mod lru_cache;
-mod median_finder;
-mod min_stack;
-mod randomized_set;
-mod trie;
+pub mod bit_set;
+pub mod fenwick_tree;
+pub mod flat_hash_map;
+pub mod graph;
+pub mod graph1;
+pub mod heap;
+pub mod invariant;
+pub mod kd_tree;
+pub mod linked_list;
+pub mod lru_cache;
+pub mod median_finder;
+pub mod membership_filter;
+pub mod min_stack;
+pub mod quantile_sketch;
+pub mod randomized_set;
+pub mod ring_deque;
+pub mod roaring_bitmap;
+pub mod rope;
+pub mod segment_tree;
+pub mod sparse_table;
+pub mod stream_sketch;
+mod tree_render;
+pub mod trie;
+pub mod weighted_sampling;
@@ -1,4 +1,5 @@
-use std::fmt::{self, write, Display, Formatter};
+use crate::invariant::InvariantViolation;
+use std::fmt::{self, Display, Formatter};
 use std::ptr::NonNull;
 
 struct Node<T> {
@@ -66,6 +67,50 @@ impl<T> LinkedList<T> {
             },
         }
     }
+
+    /// Walk the list from start to end and check that every node's prev points
+    /// back at its predecessor, that the walk ends at `end`, and that it visits
+    /// exactly `length` nodes
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        let mut count: u32 = 0;
+        let mut prev: Option<NonNull<Node<T>>> = None;
+        let mut current = self.start;
+        while let Some(node_ptr) = current {
+            // A cycle would make the walk longer than the recorded length
+            if count == self.length {
+                return Err(InvariantViolation::new(
+                    "LinkedList",
+                    format!("more than {} nodes are reachable from start", self.length),
+                ));
+            }
+            let node = unsafe { node_ptr.as_ref() };
+            if node.prev != prev {
+                return Err(InvariantViolation::new(
+                    "LinkedList",
+                    format!("node {} does not point back at its predecessor", count),
+                ));
+            }
+            count += 1;
+            prev = current;
+            current = node.next;
+        }
+        if prev != self.end {
+            return Err(InvariantViolation::new(
+                "LinkedList",
+                "end is not the last node reachable from start",
+            ));
+        }
+        if count != self.length {
+            return Err(InvariantViolation::new(
+                "LinkedList",
+                format!(
+                    "length is {} but {} nodes are reachable",
+                    self.length, count
+                ),
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl<T> Display for Node<T>
@@ -95,6 +140,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::LinkedList;
+    use crate::invariant::run_random_ops;
+    use rand::Rng;
 
     #[test]
     fn create_numeric_list() {
@@ -137,4 +184,50 @@ mod tests {
         assert!(retrived_item.is_some());
         assert_eq!("B", *retrived_item.unwrap());
     }
+
+    #[test]
+    fn validate_after_random_ops() {
+        let mut list = LinkedList::<i32>::new();
+        let mut values = Vec::new();
+        assert_eq!(list.validate(), Ok(()));
+        run_random_ops(
+            48,
+            500,
+            &mut list,
+            |rng, list| {
+                if rng.gen_bool(0.5) {
+                    let value = rng.gen_range(0..1000);
+                    list.add(value);
+                    values.push(value);
+                    format!("add({})", value)
+                } else {
+                    let index = rng.gen_range(0..=values.len() as i32);
+                    assert_eq!(list.get(index), values.get(index as usize));
+                    format!("get({})", index)
+                }
+            },
+            LinkedList::validate,
+        );
+    }
+
+    #[test]
+    fn validate_detects_broken_links() {
+        let mut list = LinkedList::<i32>::new();
+        list.add(1);
+        list.add(2);
+        list.add(3);
+        assert_eq!(list.validate(), Ok(()));
+
+        // The last node forgets its predecessor
+        let end = list.end.unwrap();
+        unsafe { (*end.as_ptr()).prev = None };
+        assert!(list.validate().is_err());
+        unsafe { (*end.as_ptr()).prev = (*list.start.unwrap().as_ptr()).next };
+        assert_eq!(list.validate(), Ok(()));
+
+        list.length = 2;
+        assert!(list.validate().is_err());
+        list.length = 4;
+        assert!(list.validate().is_err());
+    }
 }
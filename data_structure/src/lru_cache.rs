@@ -1,6 +1,7 @@
-use std::collections::{HashMap, VecDeque};
+use crate::invariant::InvariantViolation;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-struct LRUCache {
+pub struct LRUCache {
     capacity: usize,
     map: HashMap<i32, (i32, usize)>, // 键 -> (值, 链表中的位置)
     list: VecDeque<i32>,             // 双端队列，用于维护 LRU 的顺序
@@ -8,7 +9,7 @@ struct LRUCache {
 
 impl LRUCache {
     // 初始化 LRU 缓存，设定容量
-    fn new(capacity: i32) -> Self {
+    pub fn new(capacity: i32) -> Self {
         LRUCache {
             capacity: capacity as usize,
             map: HashMap::new(),
@@ -17,7 +18,7 @@ impl LRUCache {
     }
 
     // 获取键的值，如果键不存在，返回 -1
-    fn get(&mut self, key: i32) -> i32 {
+    pub fn get(&mut self, key: i32) -> i32 {
         if let Some(&(value, _)) = self.map.get(&key) {
             // 如果键存在，将其提升为最近使用的
             self.make_recent(key);
@@ -28,7 +29,7 @@ impl LRUCache {
     }
 
     // 插入或更新键值
-    fn put(&mut self, key: i32, value: i32) {
+    pub fn put(&mut self, key: i32, value: i32) {
         if self.map.contains_key(&key) {
             // 如果键已经存在，更新值并将其提升为最近使用的
             self.make_recent(key);
@@ -57,4 +58,111 @@ impl LRUCache {
         }
         self.list.push_front(key); // 插入到队列头部
     }
+
+    // 检查哈希表和双端队列是否一致：两者保存的键完全相同，队列中没有重复的键，
+    // 并且缓存中的元素个数不超过容量
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        if self.map.len() != self.list.len() {
+            return Err(InvariantViolation::new(
+                "LRUCache",
+                format!(
+                    "map holds {} keys but list holds {}",
+                    self.map.len(),
+                    self.list.len()
+                ),
+            ));
+        }
+        let mut seen = HashSet::with_capacity(self.list.len());
+        for key in &self.list {
+            if !seen.insert(key) {
+                return Err(InvariantViolation::new(
+                    "LRUCache",
+                    format!("key {} appears twice in the list", key),
+                ));
+            }
+            if !self.map.contains_key(key) {
+                return Err(InvariantViolation::new(
+                    "LRUCache",
+                    format!("key {} is in the list but not in the map", key),
+                ));
+            }
+        }
+        if self.map.len() > self.capacity {
+            return Err(InvariantViolation::new(
+                "LRUCache",
+                format!(
+                    "holds {} keys, more than the capacity {}",
+                    self.map.len(),
+                    self.capacity
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::invariant::run_random_ops;
+    use rand::Rng;
+
+    #[test]
+    fn test_validate_random_ops() {
+        let capacity = 8;
+        let mut cache = LRUCache::new(capacity as i32);
+        // 参照模型：按最近使用顺序排列的 (键, 值)，最近使用的在最前面
+        let mut model: Vec<(i32, i32)> = Vec::new();
+        run_random_ops(
+            48,
+            3000,
+            &mut cache,
+            |rng, cache| {
+                let key = rng.gen_range(0..20);
+                let position = model.iter().position(|&(k, _)| k == key);
+                if rng.gen_bool(0.5) {
+                    let value = rng.gen_range(0..1000);
+                    cache.put(key, value);
+                    match position {
+                        Some(position) => {
+                            model.remove(position);
+                        }
+                        None => model.truncate(capacity - 1),
+                    }
+                    model.insert(0, (key, value));
+                    format!("put({}, {})", key, value)
+                } else {
+                    let expected = match position {
+                        Some(position) => {
+                            let entry = model.remove(position);
+                            model.insert(0, entry);
+                            entry.1
+                        }
+                        None => -1,
+                    };
+                    assert_eq!(cache.get(key), expected);
+                    format!("get({})", key)
+                }
+            },
+            LRUCache::validate,
+        );
+    }
+
+    #[test]
+    fn test_validate_detects_disagreement() {
+        let mut cache = LRUCache::new(2);
+        cache.put(1, 1);
+        cache.put(2, 2);
+        assert_eq!(cache.validate(), Ok(()));
+
+        // 队列里多出一个哈希表中没有的键
+        cache.list.push_back(3);
+        cache.map.remove(&1);
+        assert!(cache.validate().is_err());
+
+        let mut cache = LRUCache::new(2);
+        cache.put(1, 1);
+        cache.list.push_back(1);
+        assert!(cache.validate().is_err());
+    }
 }
@@ -1,43 +1,299 @@
 use std::cmp::Reverse;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
 
-struct MedianFinder {
-    small: BinaryHeap<i32>,          // 最大堆，存储较小的一半元素
-    large: BinaryHeap<Reverse<i32>>, // 最小堆，存储较大的一半元素
+/// 用两个堆维护数据流的分位数（默认是中位数）
+///
+/// 把数据排序后记为 x[0..n]，对分位数 p 令 h = p * (n - 1)、lo = floor(h)，
+/// 那么 small 中恰好保存 x[0..=lo]，large 中保存剩下的元素，
+/// 两个堆顶就是插值所需的 x[lo] 和 x[lo + 1]。
+///
+/// 删除采用延迟删除：被删除的元素先记在 delayed 中，等它出现在堆顶时再真正弹出，
+/// 因此两个堆的实际长度可能大于逻辑长度 small_size / large_size。
+pub struct MedianFinder<T> {
+    small: BinaryHeap<T>,          // 最大堆，存储较小的那部分元素
+    large: BinaryHeap<Reverse<T>>, // 最小堆，存储较大的那部分元素
+    small_size: usize,
+    large_size: usize,
+    delayed: HashMap<T, usize>, // 等待从堆中删除的元素及次数
+    counts: HashMap<T, usize>,  // 当前仍在集合中的元素及次数
+    p: f64,
 }
 
-impl MedianFinder {
-    fn new() -> Self {
+impl<T> Default for MedianFinder<T>
+where
+    T: Ord + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> MedianFinder<T>
+where
+    T: Ord + Hash + Clone,
+{
+    /// 创建一个维护中位数的 MedianFinder
+    pub fn new() -> Self {
+        Self::with_quantile(0.5)
+    }
+
+    /// 创建一个维护 p 分位数的 MedianFinder，p 必须在 [0, 1] 之间
+    pub fn with_quantile(p: f64) -> Self {
+        assert!((0.0..=1.0).contains(&p), "quantile must be in [0, 1]");
         MedianFinder {
             small: BinaryHeap::new(),
             large: BinaryHeap::new(),
+            small_size: 0,
+            large_size: 0,
+            delayed: HashMap::new(),
+            counts: HashMap::new(),
+            p,
+        }
+    }
+
+    /// 返回维护的分位数 p
+    pub fn quantile(&self) -> f64 {
+        self.p
+    }
+
+    /// 返回当前元素的个数
+    pub fn len(&self) -> usize {
+        self.small_size + self.large_size
+    }
+
+    /// 判断是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn add_num(&mut self, num: T) {
+        *self.counts.entry(num.clone()).or_insert(0) += 1;
+
+        // 不大于 small 堆顶的元素属于较小的那部分
+        if self.small.peek().is_none_or(|top| num <= *top) {
+            self.small.push(num);
+            self.small_size += 1;
+        } else {
+            self.large.push(Reverse(num));
+            self.large_size += 1;
+        }
+        self.rebalance();
+    }
+
+    /// 删除一个等于 num 的元素，如果不存在则返回 false
+    pub fn remove(&mut self, num: &T) -> bool {
+        match self.counts.get_mut(num) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => return false,
+        }
+        if self.counts[num] == 0 {
+            self.counts.remove(num);
+        }
+        *self.delayed.entry(num.clone()).or_insert(0) += 1;
+
+        // small 中的元素都不大于 small 的堆顶，以此判断 num 逻辑上在哪个堆里
+        if self.small.peek().is_some_and(|top| num <= top) {
+            self.small_size -= 1;
+            if self.small.peek() == Some(num) {
+                self.prune_small();
+            }
+        } else {
+            self.large_size -= 1;
+            if self.large.peek().map(|Reverse(top)| top) == Some(num) {
+                self.prune_large();
+            }
+        }
+        self.rebalance();
+        true
+    }
+
+    /// 返回 p 分位数对应的那个元素 x[floor(p * (n - 1))]，不做插值，为空时返回 None
+    pub fn quantile_element(&self) -> Option<&T> {
+        self.small.peek()
+    }
+
+    /// small 中应该保存的元素个数
+    fn target_small_size(&self) -> usize {
+        match self.len() {
+            0 => 0,
+            n => (self.p * (n - 1) as f64).floor() as usize + 1,
+        }
+    }
+
+    /// 在两个堆之间移动元素，使 small 的逻辑长度等于目标长度
+    fn rebalance(&mut self) {
+        let target = self.target_small_size();
+        while self.small_size > target {
+            let top = self.small.pop().unwrap();
+            self.large.push(Reverse(top));
+            self.small_size -= 1;
+            self.large_size += 1;
+            self.prune_small();
+        }
+        while self.small_size < target {
+            let Reverse(top) = self.large.pop().unwrap();
+            self.small.push(top);
+            self.small_size += 1;
+            self.large_size -= 1;
+            self.prune_large();
         }
     }
 
-    fn add_num(&mut self, num: i32) {
-        // 将元素加入到最大堆
-        self.small.push(num);
+    /// 弹出 small 堆顶所有已被删除的元素
+    fn prune_small(&mut self) {
+        while let Some(top) = self.small.peek() {
+            if !Self::take_delayed(&mut self.delayed, top) {
+                break;
+            }
+            self.small.pop();
+        }
+    }
 
-        // 确保最大堆中的所有元素都小于最小堆中的元素
-        if let Some(small_top) = self.small.pop() {
-            self.large.push(Reverse(small_top));
+    /// 弹出 large 堆顶所有已被删除的元素
+    fn prune_large(&mut self) {
+        while let Some(Reverse(top)) = self.large.peek() {
+            if !Self::take_delayed(&mut self.delayed, top) {
+                break;
+            }
+            self.large.pop();
         }
+    }
 
-        // 保证最大堆的元素数量 >= 最小堆的元素数量
-        if self.small.len() < self.large.len() {
-            if let Some(Reverse(large_top)) = self.large.pop() {
-                self.small.push(large_top);
+    /// 如果 val 在等待删除，消耗一次删除次数并返回 true
+    fn take_delayed(delayed: &mut HashMap<T, usize>, val: &T) -> bool {
+        match delayed.get_mut(val) {
+            Some(count) => {
+                *count -= 1;
+                if *count == 0 {
+                    delayed.remove(val);
+                }
+                true
             }
+            None => false,
         }
     }
+}
+
+impl<T> MedianFinder<T>
+where
+    T: Ord + Hash + Clone + Into<f64>,
+{
+    /// 按线性插值返回 p 分位数，为空时返回 None
+    pub fn find_quantile(&self) -> Option<f64> {
+        let n = self.len();
+        if n == 0 {
+            return None;
+        }
+        let h = self.p * (n - 1) as f64;
+        let frac = h - h.floor();
+        let lower: f64 = self.small.peek()?.clone().into();
+        if frac == 0.0 {
+            return Some(lower);
+        }
+        let upper: f64 = self.large.peek()?.0.clone().into();
+        Some(lower + (upper - lower) * frac)
+    }
+
+    /// 返回中位数；对于用 `with_quantile` 创建的对象，返回的是它维护的分位数
+    pub fn find_median(&self) -> Option<f64> {
+        self.find_quantile()
+    }
+}
 
-    fn find_median(&self) -> f64 {
-        if self.small.len() > self.large.len() {
-            *self.small.peek().unwrap() as f64
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// 对排好序的窗口按同样的插值规则计算分位数
+    fn brute_force_quantile(window: &[i32], p: f64) -> Option<f64> {
+        if window.is_empty() {
+            return None;
+        }
+        let mut sorted = window.to_vec();
+        sorted.sort();
+        let h = p * (sorted.len() - 1) as f64;
+        let lo = h.floor() as usize;
+        let frac = h - h.floor();
+        if frac == 0.0 {
+            Some(sorted[lo] as f64)
         } else {
-            let small_top = *self.small.peek().unwrap() as f64;
-            let large_top = self.large.peek().unwrap().0 as f64;
-            (small_top + large_top) / 2.0
+            Some(sorted[lo] as f64 + (sorted[lo + 1] - sorted[lo]) as f64 * frac)
+        }
+    }
+
+    #[test]
+    fn test_find_median() {
+        let mut finder = MedianFinder::new();
+        assert_eq!(finder.find_median(), None);
+        finder.add_num(1);
+        finder.add_num(2);
+        assert_eq!(finder.find_median(), Some(1.5));
+        finder.add_num(3);
+        assert_eq!(finder.find_median(), Some(2.0));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut finder = MedianFinder::new();
+        for num in [5, 1, 3, 3, 9] {
+            finder.add_num(num);
+        }
+        assert_eq!(finder.find_median(), Some(3.0));
+        assert!(finder.remove(&3));
+        assert_eq!(finder.find_median(), Some(4.0));
+        assert!(!finder.remove(&4));
+        assert!(finder.remove(&9));
+        assert!(finder.remove(&3));
+        assert_eq!(finder.find_median(), Some(3.0));
+        assert!(finder.remove(&1));
+        assert!(finder.remove(&5));
+        assert!(!finder.remove(&5));
+        assert_eq!(finder.find_median(), None);
+        assert!(finder.is_empty());
+    }
+
+    #[test]
+    fn test_quantile_element_for_non_numeric_types() {
+        let mut finder = MedianFinder::with_quantile(0.0);
+        for word in ["pear", "apple", "fig"] {
+            finder.add_num(word.to_string());
+        }
+        assert_eq!(finder.quantile_element().map(String::as_str), Some("apple"));
+        finder.remove(&"apple".to_string());
+        assert_eq!(finder.quantile_element().map(String::as_str), Some("fig"));
+    }
+
+    #[test]
+    fn test_sliding_window_against_brute_force() {
+        let mut rng = StdRng::seed_from_u64(29);
+        // 取值范围较小，保证窗口中有大量重复元素
+        let stream: Vec<i32> = (0..2000).map(|_| rng.gen_range(-20..20)).collect();
+
+        for &p in &[0.0, 0.1, 0.25, 0.5, 0.9, 0.99, 1.0] {
+            for &window in &[1, 2, 5, 16] {
+                let mut finder = MedianFinder::with_quantile(p);
+                for (i, &num) in stream.iter().enumerate() {
+                    finder.add_num(num);
+                    if i >= window {
+                        assert!(finder.remove(&stream[i - window]));
+                    }
+                    let start = (i + 1).saturating_sub(window);
+                    let expected = brute_force_quantile(&stream[start..=i], p);
+                    let actual = finder.find_quantile();
+                    assert!(
+                        (actual.unwrap() - expected.unwrap()).abs() < 1e-9,
+                        "p = {}, window = {}, i = {}: {:?} != {:?}",
+                        p,
+                        window,
+                        i,
+                        actual,
+                        expected
+                    );
+                }
+            }
         }
     }
 }
@@ -0,0 +1,741 @@
+/*
+    概率型成员查询
+
+    三种过滤器都只会产生假阳性（误报存在），不会产生假阴性：
+
+    - BloomFilter：m 个比特位、k 个哈希函数。插入时把 k 个位置 1，查询时检查 k 个位
+      是否全为 1。根据预期元素个数 n 和目标误判率 p 计算 m = -n ln p / (ln 2)^2，
+      k = (m / n) ln 2。不支持删除。
+    - CountingBloomFilter：把比特位换成 8 位计数器，从而支持删除；计数器饱和后不再变化。
+    - CuckooFilter：每个桶 4 个槽位，存放 16 位指纹。元素的两个候选桶为 i1 和
+      i1 ^ hash(指纹)，两个桶都满时随机踢出一个指纹并把它搬到它的另一个候选桶。
+      支持删除，在低误判率下比 Bloom filter 更省空间。
+
+    k 个哈希值由同一个 64 位哈希派生（Kirsch–Mitzenmacher 双重哈希：h1 + i * h2）。
+    哈希使用固定密钥的 DefaultHasher，同一个版本的程序序列化出来的字节可以在
+    不同进程之间互相读取。
+*/
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterError {
+    /// 两个过滤器的参数不同，无法合并
+    Incompatible,
+    /// 反序列化时字节格式不正确
+    Malformed,
+    /// CuckooFilter 已满，无法再放入指纹
+    Full,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::Incompatible => write!(f, "filters have different parameters"),
+            FilterError::Malformed => write!(f, "malformed filter bytes"),
+            FilterError::Full => write!(f, "filter is full"),
+        }
+    }
+}
+
+pub(crate) fn hash_of<T: Hash + ?Sized>(item: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+// splitmix64 的混合函数，用于从一个哈希值派生出另一个独立的哈希值
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+// 第 i 个哈希函数对应的位置
+fn nth_index(hash: u64, i: u32, len: usize) -> usize {
+    let h2 = mix(hash) | 1;
+    (hash.wrapping_add((i as u64).wrapping_mul(h2)) % len as u64) as usize
+}
+
+// 序列化时使用的简单读取器，所有整数都是小端序
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], FilterError> {
+        if self.bytes.len() < n {
+            return Err(FilterError::Malformed);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, FilterError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, FilterError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, FilterError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn usize(&mut self) -> Result<usize, FilterError> {
+        usize::try_from(self.u64()?).map_err(|_| FilterError::Malformed)
+    }
+
+    fn finish(self) -> Result<(), FilterError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(FilterError::Malformed)
+        }
+    }
+}
+
+const BLOOM_TAG: u8 = 0xb1;
+const COUNTING_BLOOM_TAG: u8 = 0xb2;
+const CUCKOO_TAG: u8 = 0xc1;
+
+// 根据预期元素个数和目标误判率计算 (比特数, 哈希函数个数)
+fn bloom_parameters(expected_items: usize, fpr: f64) -> (usize, u32) {
+    assert!(
+        fpr > 0.0 && fpr < 1.0,
+        "false positive rate must be in (0, 1)"
+    );
+    let n = expected_items.max(1) as f64;
+    let ln2 = std::f64::consts::LN_2;
+    let bits = (-n * fpr.ln() / (ln2 * ln2)).ceil().max(1.0) as usize;
+    let hashes = ((bits as f64 / n) * ln2).round().max(1.0) as u32;
+    (bits, hashes)
+}
+
+pub struct BloomFilter<T: ?Sized> {
+    bits: Vec<u64>,
+    num_bits: usize,
+    num_hashes: u32,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> BloomFilter<T> {
+    /// 预期放入 expected_items 个元素时，误判率不超过 fpr
+    pub fn new(expected_items: usize, fpr: f64) -> Self {
+        let (num_bits, num_hashes) = bloom_parameters(expected_items, fpr);
+        Self::with_params(num_bits, num_hashes)
+    }
+
+    pub fn with_params(num_bits: usize, num_hashes: u32) -> Self {
+        assert!(num_bits > 0 && num_hashes > 0);
+        BloomFilter {
+            bits: vec![0; num_bits.div_ceil(64)],
+            num_bits,
+            num_hashes,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    pub fn insert(&mut self, item: &T) {
+        let hash = hash_of(item);
+        for i in 0..self.num_hashes {
+            let index = nth_index(hash, i, self.num_bits);
+            self.bits[index / 64] |= 1 << (index % 64);
+        }
+    }
+
+    /// 返回 false 时元素一定不存在，返回 true 时元素可能存在
+    pub fn contains(&self, item: &T) -> bool {
+        let hash = hash_of(item);
+        (0..self.num_hashes).all(|i| {
+            let index = nth_index(hash, i, self.num_bits);
+            self.bits[index / 64] & (1 << (index % 64)) != 0
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// 合并另一个参数相同的过滤器，结果等价于把两者的元素都插入同一个过滤器
+    pub fn union(&mut self, other: &Self) -> Result<(), FilterError> {
+        if self.num_bits != other.num_bits || self.num_hashes != other.num_hashes {
+            return Err(FilterError::Incompatible);
+        }
+        for (word, other) in self.bits.iter_mut().zip(&other.bits) {
+            *word |= other;
+        }
+        Ok(())
+    }
+
+    /// 根据当前置 1 的比特比例估计误判率：(置 1 的比例)^k
+    pub fn estimated_fpr(&self) -> f64 {
+        let ones: usize = self
+            .bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        (ones as f64 / self.num_bits as f64).powi(self.num_hashes as i32)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(13 + 8 * self.bits.len());
+        bytes.push(BLOOM_TAG);
+        bytes.extend_from_slice(&(self.num_bits as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.num_hashes.to_le_bytes());
+        for word in &self.bits {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FilterError> {
+        let mut reader = ByteReader { bytes };
+        if reader.u8()? != BLOOM_TAG {
+            return Err(FilterError::Malformed);
+        }
+        let num_bits = reader.usize()?;
+        let num_hashes = u32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+        if num_bits == 0 || num_hashes == 0 {
+            return Err(FilterError::Malformed);
+        }
+        let words = num_bits.div_ceil(64);
+        if reader.bytes.len() != 8 * words {
+            return Err(FilterError::Malformed);
+        }
+        let bits = (0..words).map(|_| reader.u64()).collect::<Result<_, _>>()?;
+        reader.finish()?;
+        Ok(BloomFilter {
+            bits,
+            num_bits,
+            num_hashes,
+            marker: PhantomData,
+        })
+    }
+}
+
+pub struct CountingBloomFilter<T: ?Sized> {
+    counters: Vec<u8>,
+    num_hashes: u32,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> CountingBloomFilter<T> {
+    pub fn new(expected_items: usize, fpr: f64) -> Self {
+        let (num_counters, num_hashes) = bloom_parameters(expected_items, fpr);
+        Self::with_params(num_counters, num_hashes)
+    }
+
+    pub fn with_params(num_counters: usize, num_hashes: u32) -> Self {
+        assert!(num_counters > 0 && num_hashes > 0);
+        CountingBloomFilter {
+            counters: vec![0; num_counters],
+            num_hashes,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn num_counters(&self) -> usize {
+        self.counters.len()
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    fn indices(&self, item: &T) -> impl Iterator<Item = usize> {
+        let hash = hash_of(item);
+        let len = self.counters.len();
+        (0..self.num_hashes).map(move |i| nth_index(hash, i, len))
+    }
+
+    pub fn insert(&mut self, item: &T) {
+        for index in self.indices(item).collect::<Vec<_>>() {
+            self.counters[index] = self.counters[index].saturating_add(1);
+        }
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        self.indices(item).all(|index| self.counters[index] > 0)
+    }
+
+    /// 删除一个元素；元素一定不存在时返回 false。
+    /// 只能删除确实插入过的元素，否则可能把其它元素也“删掉”而产生假阴性
+    pub fn remove(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+        for index in self.indices(item).collect::<Vec<_>>() {
+            // 饱和的计数器已经不知道真实的值，不再减少
+            if self.counters[index] != u8::MAX {
+                self.counters[index] -= 1;
+            }
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.counters.iter_mut().for_each(|c| *c = 0);
+    }
+
+    /// 合并另一个参数相同的过滤器，对应计数器相加
+    pub fn union(&mut self, other: &Self) -> Result<(), FilterError> {
+        if self.counters.len() != other.counters.len() || self.num_hashes != other.num_hashes {
+            return Err(FilterError::Incompatible);
+        }
+        for (c, other) in self.counters.iter_mut().zip(&other.counters) {
+            *c = c.saturating_add(*other);
+        }
+        Ok(())
+    }
+
+    pub fn estimated_fpr(&self) -> f64 {
+        let nonzero = self.counters.iter().filter(|&&c| c > 0).count();
+        (nonzero as f64 / self.counters.len() as f64).powi(self.num_hashes as i32)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(13 + self.counters.len());
+        bytes.push(COUNTING_BLOOM_TAG);
+        bytes.extend_from_slice(&(self.counters.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.num_hashes.to_le_bytes());
+        bytes.extend_from_slice(&self.counters);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FilterError> {
+        let mut reader = ByteReader { bytes };
+        if reader.u8()? != COUNTING_BLOOM_TAG {
+            return Err(FilterError::Malformed);
+        }
+        let len = reader.usize()?;
+        let num_hashes = u32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+        if len == 0 || num_hashes == 0 {
+            return Err(FilterError::Malformed);
+        }
+        let counters = reader.take(len)?.to_vec();
+        reader.finish()?;
+        Ok(CountingBloomFilter {
+            counters,
+            num_hashes,
+            marker: PhantomData,
+        })
+    }
+}
+
+const BUCKET_SIZE: usize = 4;
+const MAX_KICKS: usize = 500;
+
+pub struct CuckooFilter<T: ?Sized> {
+    buckets: Vec<[u16; BUCKET_SIZE]>, // 0 表示空槽位
+    len: usize,
+    // 踢出次数用尽后无处安放的指纹，存在它时过滤器视为已满
+    victim: Option<(usize, u16)>,
+    rng_state: u64, // 选择踢出哪个槽位用的伪随机数
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> CuckooFilter<T> {
+    /// 可以放入大约 capacity 个元素的过滤器（桶数取 2 的幂，装载率约 95% 时开始失败）
+    pub fn new(capacity: usize) -> Self {
+        let buckets = (capacity.max(1) as f64 / (BUCKET_SIZE as f64 * 0.95)).ceil() as usize;
+        Self::with_buckets(buckets.next_power_of_two())
+    }
+
+    fn with_buckets(num_buckets: usize) -> Self {
+        CuckooFilter {
+            buckets: vec![[0; BUCKET_SIZE]; num_buckets],
+            len: 0,
+            victim: None,
+            rng_state: 0x9e3779b97f4a7c15,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 槽位总数
+    pub fn capacity(&self) -> usize {
+        self.buckets.len() * BUCKET_SIZE
+    }
+
+    // 元素的 (第一个候选桶, 指纹)，指纹不为 0
+    fn locate(&self, item: &T) -> (usize, u16) {
+        let hash = hash_of(item);
+        let index = (hash as usize) & (self.buckets.len() - 1);
+        let fingerprint = ((hash >> 48) as u16).max(1);
+        (index, fingerprint)
+    }
+
+    fn alternate(&self, index: usize, fingerprint: u16) -> usize {
+        (index ^ mix(fingerprint as u64) as usize) & (self.buckets.len() - 1)
+    }
+
+    fn try_put(&mut self, index: usize, fingerprint: u16) -> bool {
+        match self.buckets[index].iter_mut().find(|slot| **slot == 0) {
+            Some(slot) => {
+                *slot = fingerprint;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn insert_fingerprint(&mut self, index: usize, fingerprint: u16) -> Result<(), FilterError> {
+        if self.victim.is_some() {
+            return Err(FilterError::Full);
+        }
+        let alt = self.alternate(index, fingerprint);
+        if self.try_put(index, fingerprint) || self.try_put(alt, fingerprint) {
+            self.len += 1;
+            return Ok(());
+        }
+        // 两个候选桶都满了，随机踢出一个指纹，让它去它的另一个候选桶
+        let mut index = if self.next_random() & 1 == 0 {
+            index
+        } else {
+            alt
+        };
+        let mut fingerprint = fingerprint;
+        for _ in 0..MAX_KICKS {
+            let slot = (self.next_random() as usize) % BUCKET_SIZE;
+            std::mem::swap(&mut fingerprint, &mut self.buckets[index][slot]);
+            index = self.alternate(index, fingerprint);
+            if self.try_put(index, fingerprint) {
+                self.len += 1;
+                return Ok(());
+            }
+        }
+        // 新元素已经放进去了，只是某个被踢出的指纹无处安放，暂存起来以免产生假阴性
+        self.victim = Some((index, fingerprint));
+        self.len += 1;
+        Ok(())
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.rng_state = mix(self.rng_state);
+        self.rng_state
+    }
+
+    /// 插入一个元素，过滤器已满时返回错误。同一个元素可以插入多次
+    pub fn insert(&mut self, item: &T) -> Result<(), FilterError> {
+        let (index, fingerprint) = self.locate(item);
+        self.insert_fingerprint(index, fingerprint)
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        let (index, fingerprint) = self.locate(item);
+        let alt = self.alternate(index, fingerprint);
+        self.buckets[index].contains(&fingerprint)
+            || self.buckets[alt].contains(&fingerprint)
+            || self
+                .victim
+                .is_some_and(|(i, f)| f == fingerprint && (i == index || i == alt))
+    }
+
+    /// 删除一个元素，元素一定不存在时返回 false。同样只能删除确实插入过的元素
+    pub fn remove(&mut self, item: &T) -> bool {
+        let (index, fingerprint) = self.locate(item);
+        let alt = self.alternate(index, fingerprint);
+        if self
+            .victim
+            .is_some_and(|(i, f)| f == fingerprint && (i == index || i == alt))
+        {
+            self.victim = None;
+            self.len -= 1;
+            return true;
+        }
+        for bucket in [index, alt] {
+            if let Some(slot) = self.buckets[bucket].iter_mut().find(|s| **s == fingerprint) {
+                *slot = 0;
+                self.len -= 1;
+                // 腾出了位置，尝试把暂存的指纹放回去
+                if let Some((i, f)) = self.victim.take() {
+                    self.len -= 1;
+                    self.insert_fingerprint(i, f)
+                        .expect("a slot was just freed");
+                }
+                return true;
+            }
+        }
+        false
+    }
+
+    /// 把另一个桶数相同的过滤器中的所有指纹加入进来，空间不足时返回错误
+    pub fn union(&mut self, other: &Self) -> Result<(), FilterError> {
+        if self.buckets.len() != other.buckets.len() {
+            return Err(FilterError::Incompatible);
+        }
+        for (index, bucket) in other.buckets.iter().enumerate() {
+            for &fingerprint in bucket.iter().filter(|&&f| f != 0) {
+                self.insert_fingerprint(index, fingerprint)?;
+            }
+        }
+        if let Some((index, fingerprint)) = other.victim {
+            self.insert_fingerprint(index, fingerprint)?;
+        }
+        Ok(())
+    }
+
+    /// 查询一个不存在的元素时，它的两个候选桶中的每个指纹都有 1 / 2^16 的概率与之相同：
+    /// 1 - (1 - 2^-16)^(2 * 4 * 装载率)
+    pub fn estimated_fpr(&self) -> f64 {
+        let load = self.len as f64 / self.capacity() as f64;
+        1.0 - (1.0 - 1.0 / 65536.0f64).powf(2.0 * BUCKET_SIZE as f64 * load)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(28 + 2 * self.capacity());
+        bytes.push(CUCKOO_TAG);
+        bytes.extend_from_slice(&(self.buckets.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.len as u64).to_le_bytes());
+        match self.victim {
+            Some((index, fingerprint)) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&(index as u64).to_le_bytes());
+                bytes.extend_from_slice(&fingerprint.to_le_bytes());
+            }
+            None => bytes.push(0),
+        }
+        for fingerprint in self.buckets.iter().flatten() {
+            bytes.extend_from_slice(&fingerprint.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FilterError> {
+        let mut reader = ByteReader { bytes };
+        if reader.u8()? != CUCKOO_TAG {
+            return Err(FilterError::Malformed);
+        }
+        let num_buckets = reader.usize()?;
+        if !num_buckets.is_power_of_two() {
+            return Err(FilterError::Malformed);
+        }
+        let len = reader.usize()?;
+        let victim = match reader.u8()? {
+            0 => None,
+            1 => {
+                let index = reader.usize()?;
+                if index >= num_buckets {
+                    return Err(FilterError::Malformed);
+                }
+                Some((index, reader.u16()?))
+            }
+            _ => return Err(FilterError::Malformed),
+        };
+        // 头部来自不可信的输入，先确认剩余字节数与桶数一致再分配内存
+        let slots = num_buckets
+            .checked_mul(BUCKET_SIZE)
+            .ok_or(FilterError::Malformed)?;
+        if slots.checked_mul(2) != Some(reader.bytes.len())
+            || len > slots + usize::from(victim.is_some())
+        {
+            return Err(FilterError::Malformed);
+        }
+        let mut filter = Self::with_buckets(num_buckets);
+        filter.len = len;
+        filter.victim = victim;
+        for bucket in filter.buckets.iter_mut() {
+            for slot in bucket.iter_mut() {
+                *slot = reader.u16()?;
+            }
+        }
+        reader.finish()?;
+        Ok(filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashSet;
+
+    // 生成互不相同的 inserted 个已插入的键和 probes 个未插入的键
+    fn seeded_keys(seed: u64, inserted: usize, probes: usize) -> (Vec<u64>, Vec<u64>) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut seen = HashSet::new();
+        let mut keys = Vec::with_capacity(inserted + probes);
+        while keys.len() < inserted + probes {
+            let key: u64 = rng.gen();
+            if seen.insert(key) {
+                keys.push(key);
+            }
+        }
+        let probes = keys.split_off(inserted);
+        (keys, probes)
+    }
+
+    fn measured_fpr(probes: &[u64], contains: impl Fn(&u64) -> bool) -> f64 {
+        probes.iter().filter(|key| contains(key)).count() as f64 / probes.len() as f64
+    }
+
+    #[test]
+    fn test_bloom_filter_fpr() {
+        let (keys, probes) = seeded_keys(41, 10_000, 100_000);
+        let mut filter = BloomFilter::new(keys.len(), 0.01);
+        for key in &keys {
+            filter.insert(key);
+        }
+        assert!(keys.iter().all(|key| filter.contains(key)));
+
+        let fpr = measured_fpr(&probes, |key| filter.contains(key));
+        assert!(fpr < 0.015, "measured false positive rate {}", fpr);
+        let estimated = filter.estimated_fpr();
+        assert!((estimated - fpr).abs() < 0.005, "{} vs {}", estimated, fpr);
+    }
+
+    #[test]
+    fn test_bloom_filter_union_and_bytes() {
+        let mut a: BloomFilter<str> = BloomFilter::new(100, 0.01);
+        let mut b: BloomFilter<str> = BloomFilter::new(100, 0.01);
+        a.insert("apple");
+        b.insert("banana");
+        a.union(&b).unwrap();
+        assert!(a.contains("apple") && a.contains("banana"));
+        assert!(!b.contains("apple"));
+
+        let restored: BloomFilter<str> = BloomFilter::from_bytes(&a.to_bytes()).unwrap();
+        assert!(restored.contains("apple") && restored.contains("banana"));
+        assert_eq!(restored.num_bits(), a.num_bits());
+
+        let mut other: BloomFilter<str> = BloomFilter::new(1000, 0.01);
+        assert_eq!(other.union(&a), Err(FilterError::Incompatible));
+        let bytes = a.to_bytes();
+        assert!(BloomFilter::<str>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(BloomFilter::<str>::from_bytes(&[]).is_err());
+    }
+
+    #[test]
+    fn test_counting_bloom_filter() {
+        let (keys, probes) = seeded_keys(410, 5_000, 50_000);
+        let mut filter = CountingBloomFilter::new(keys.len(), 0.02);
+        for key in &keys {
+            filter.insert(key);
+        }
+        let fpr = measured_fpr(&probes, |key| filter.contains(key));
+        assert!(fpr < 0.03, "measured false positive rate {}", fpr);
+
+        // 删除一半的键后，剩下的键仍然都能查到
+        for key in &keys[..2_500] {
+            assert!(filter.remove(key));
+        }
+        assert!(keys[2_500..].iter().all(|key| filter.contains(key)));
+        let removed_hits = keys[..2_500]
+            .iter()
+            .filter(|key| filter.contains(key))
+            .count();
+        assert!(removed_hits < 100);
+        assert!(filter.estimated_fpr() < 0.02);
+
+        let mut other = CountingBloomFilter::new(keys.len(), 0.02);
+        other.insert(&keys[0]);
+        filter.union(&other).unwrap();
+        assert!(filter.contains(&keys[0]));
+
+        let restored = CountingBloomFilter::<u64>::from_bytes(&filter.to_bytes()).unwrap();
+        assert!(keys[2_500..].iter().all(|key| restored.contains(key)));
+    }
+
+    #[test]
+    fn test_cuckoo_filter() {
+        let (keys, probes) = seeded_keys(4100, 20_000, 100_000);
+        let mut filter = CuckooFilter::new(keys.len());
+        for key in &keys {
+            filter.insert(key).unwrap();
+        }
+        assert_eq!(filter.len(), keys.len());
+        assert!(keys.iter().all(|key| filter.contains(key)));
+
+        let fpr = measured_fpr(&probes, |key| filter.contains(key));
+        let estimated = filter.estimated_fpr();
+        assert!(fpr < 3.0 * estimated + 1e-4, "{} vs {}", fpr, estimated);
+
+        for key in &keys[..10_000] {
+            assert!(filter.remove(key));
+        }
+        assert_eq!(filter.len(), 10_000);
+        assert!(keys[10_000..].iter().all(|key| filter.contains(key)));
+
+        let restored = CuckooFilter::<u64>::from_bytes(&filter.to_bytes()).unwrap();
+        assert_eq!(restored.len(), 10_000);
+        assert!(keys[10_000..].iter().all(|key| restored.contains(key)));
+    }
+
+    #[test]
+    fn test_cuckoo_filter_rejects_malformed_bytes() {
+        let header = |num_buckets: u64, len: u64| {
+            let mut bytes = vec![CUCKOO_TAG];
+            bytes.extend_from_slice(&num_buckets.to_le_bytes());
+            bytes.extend_from_slice(&len.to_le_bytes());
+            bytes.push(0);
+            bytes
+        };
+
+        // 桶数巨大但没有相应的数据，不能先分配内存
+        let mut bytes = header(1 << 62, 0);
+        bytes.extend_from_slice(&[0; 8]);
+        assert_eq!(bytes.len(), 26);
+        assert_eq!(
+            CuckooFilter::<u64>::from_bytes(&bytes).err(),
+            Some(FilterError::Malformed)
+        );
+        let bytes = header(1 << 63, 0);
+        assert!(CuckooFilter::<u64>::from_bytes(&bytes).is_err());
+
+        // 元素个数超过槽位数
+        let mut bytes = header(2, 9);
+        bytes.extend_from_slice(&[0; 16]);
+        assert!(CuckooFilter::<u64>::from_bytes(&bytes).is_err());
+        let mut bytes = header(2, 8);
+        bytes.extend_from_slice(&[0; 16]);
+        assert_eq!(CuckooFilter::<u64>::from_bytes(&bytes).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_cuckoo_filter_full_and_union() {
+        let mut filter: CuckooFilter<u32> = CuckooFilter::new(8);
+        let mut inserted = Vec::new();
+        for key in 0.. {
+            if filter.insert(&key).is_err() {
+                break;
+            }
+            inserted.push(key);
+        }
+        // 满了之后已经插入的元素仍然都能查到
+        assert!(inserted.iter().all(|key| filter.contains(key)));
+        assert!(filter.len() <= filter.capacity() + 1);
+        assert!(filter.remove(&inserted[0]));
+        assert!(filter.insert(&inserted[0]).is_ok());
+
+        let mut a: CuckooFilter<str> = CuckooFilter::new(100);
+        let mut b: CuckooFilter<str> = CuckooFilter::new(100);
+        a.insert("x").unwrap();
+        b.insert("y").unwrap();
+        a.union(&b).unwrap();
+        assert!(a.contains("x") && a.contains("y"));
+        assert_eq!(a.len(), 2);
+        let mut small: CuckooFilter<str> = CuckooFilter::new(4);
+        assert_eq!(small.union(&a), Err(FilterError::Incompatible));
+    }
+}
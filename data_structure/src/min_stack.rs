@@ -1,38 +1,272 @@
-struct MinStack {
-    stack: Vec<i32>,
-    min_stack: Vec<i32>,
+/// 可以在 O(1) 时间内取得最小值的栈
+///
+/// min_stack 里存的是 stack 中元素的下标而不是元素本身，因此不要求 `T: Clone`。
+pub struct MinStack<T> {
+    stack: Vec<T>,
+    min_stack: Vec<usize>,
 }
 
-impl MinStack {
-    fn new() -> Self {
+impl<T: Ord> Default for MinStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> MinStack<T> {
+    pub fn new() -> Self {
         Self {
             stack: Vec::new(),
             min_stack: Vec::new(),
         }
     }
 
-    fn push(&mut self, val: i32) {
-        self.stack.push(val);
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn push(&mut self, val: T) {
         // 如果最小栈为空，或者当前值小于等于最小栈的栈顶元素，推入最小栈
-        if self.min_stack.is_empty() || val <= *self.min_stack.last().unwrap() {
-            self.min_stack.push(val);
+        if self.get_min().is_none_or(|min| val <= *min) {
+            self.min_stack.push(self.stack.len());
+        }
+        self.stack.push(val);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        // 如果弹出的元素就是最小栈的栈顶元素，最小栈也需要弹出
+        if self.min_stack.last() == Some(&(self.stack.len().wrapping_sub(1))) {
+            self.min_stack.pop();
+        }
+        self.stack.pop()
+    }
+
+    pub fn top(&self) -> Option<&T> {
+        self.stack.last()
+    }
+
+    pub fn get_min(&self) -> Option<&T> {
+        self.min_stack.last().map(|&idx| &self.stack[idx])
+    }
+}
+
+/// 同时维护最小值和最大值的栈
+pub struct MinMaxStack<T> {
+    stack: Vec<T>,
+    min_stack: Vec<usize>,
+    max_stack: Vec<usize>,
+}
+
+impl<T: Ord> Default for MinMaxStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> MinMaxStack<T> {
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            min_stack: Vec::new(),
+            max_stack: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn push(&mut self, val: T) {
+        let idx = self.stack.len();
+        if self.min().is_none_or(|min| val <= *min) {
+            self.min_stack.push(idx);
+        }
+        if self.max().is_none_or(|max| val >= *max) {
+            self.max_stack.push(idx);
+        }
+        self.stack.push(val);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let top = self.stack.len().wrapping_sub(1);
+        if self.min_stack.last() == Some(&top) {
+            self.min_stack.pop();
+        }
+        if self.max_stack.last() == Some(&top) {
+            self.max_stack.pop();
+        }
+        self.stack.pop()
+    }
+
+    pub fn top(&self) -> Option<&T> {
+        self.stack.last()
+    }
+
+    pub fn min(&self) -> Option<&T> {
+        self.min_stack.last().map(|&idx| &self.stack[idx])
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        self.max_stack.last().map(|&idx| &self.stack[idx])
+    }
+}
+
+/// 用两个 MinMaxStack 拼成的队列，push_back/pop_front 均摊 O(1)，min/max 为 O(1)
+///
+/// 新元素压入 back 栈；pop_front 时如果 front 栈为空，就把 back 栈的元素全部倒进 front 栈，
+/// 这样 front 栈的栈顶就是队首。整个队列的最值就是两个栈最值中的较小/较大者。
+/// 用它维护滑动窗口：每进来一个元素 push_back，窗口超长时 pop_front，然后直接取 min/max。
+pub struct MonotonicQueue<T> {
+    front: MinMaxStack<T>,
+    back: MinMaxStack<T>,
+}
+
+impl<T: Ord> Default for MonotonicQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> MonotonicQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            front: MinMaxStack::new(),
+            back: MinMaxStack::new(),
         }
     }
 
-    fn pop(&mut self) {
-        // 如果弹出的元素等于最小栈的栈顶元素，最小栈也需要弹出
-        if let Some(val) = self.stack.pop() {
-            if val == *self.min_stack.last().unwrap() {
-                self.min_stack.pop();
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.front.is_empty() && self.back.is_empty()
+    }
+
+    pub fn push_back(&mut self, val: T) {
+        self.back.push(val);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.front.is_empty() {
+            while let Some(val) = self.back.pop() {
+                self.front.push(val);
             }
         }
+        self.front.pop()
     }
 
-    fn top(&self) -> i32 {
-        *self.stack.last().unwrap()
+    /// 返回队首元素
+    pub fn front(&self) -> Option<&T> {
+        self.front.top().or_else(|| self.back.stack.first())
     }
 
-    fn get_min(&self) -> i32 {
-        *self.min_stack.last().unwrap()
+    pub fn min(&self) -> Option<&T> {
+        match (self.front.min(), self.back.min()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    pub fn max(&self) -> Option<&T> {
+        match (self.front.max(), self.back.max()) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_min_stack() {
+        let mut stack = MinStack::new();
+        assert_eq!(stack.top(), None);
+        assert_eq!(stack.get_min(), None);
+        assert_eq!(stack.pop(), None);
+
+        stack.push(-2);
+        stack.push(0);
+        stack.push(-3);
+        assert_eq!(stack.get_min(), Some(&-3));
+        assert_eq!(stack.pop(), Some(-3));
+        assert_eq!(stack.top(), Some(&0));
+        assert_eq!(stack.get_min(), Some(&-2));
+    }
+
+    #[test]
+    fn test_min_stack_duplicate_minimum() {
+        let mut stack = MinStack::new();
+        stack.push("b".to_string());
+        stack.push("a".to_string());
+        stack.push("a".to_string());
+        stack.pop();
+        assert_eq!(stack.get_min().map(String::as_str), Some("a"));
+        stack.pop();
+        assert_eq!(stack.get_min().map(String::as_str), Some("b"));
+    }
+
+    #[test]
+    fn test_min_max_stack() {
+        let mut stack = MinMaxStack::new();
+        for val in [3, 1, 4, 1, 5] {
+            stack.push(val);
+        }
+        assert_eq!((stack.min(), stack.max()), (Some(&1), Some(&5)));
+        stack.pop();
+        assert_eq!((stack.min(), stack.max()), (Some(&1), Some(&4)));
+        stack.pop();
+        stack.pop();
+        assert_eq!((stack.min(), stack.max()), (Some(&1), Some(&3)));
+        stack.pop();
+        assert_eq!((stack.min(), stack.max()), (Some(&3), Some(&3)));
+        stack.pop();
+        assert_eq!((stack.min(), stack.max()), (None, None));
+    }
+
+    #[test]
+    fn test_queue_fifo_order() {
+        let mut queue = MonotonicQueue::new();
+        queue.push_back(1);
+        queue.push_back(2);
+        assert_eq!(queue.front(), Some(&1));
+        assert_eq!(queue.pop_front(), Some(1));
+        queue.push_back(3);
+        assert_eq!(queue.front(), Some(&2));
+        assert_eq!(queue.pop_front(), Some(2));
+        assert_eq!(queue.pop_front(), Some(3));
+        assert_eq!(queue.pop_front(), None);
+        assert_eq!(queue.min(), None);
+    }
+
+    #[test]
+    fn test_sliding_window_min_max() {
+        let mut rng = StdRng::seed_from_u64(28);
+        let stream: Vec<i32> = (0..500).map(|_| rng.gen_range(-50..50)).collect();
+        let window = 7;
+
+        let mut queue = MonotonicQueue::new();
+        for (i, &val) in stream.iter().enumerate() {
+            queue.push_back(val);
+            if queue.len() > window {
+                queue.pop_front();
+            }
+            let start = (i + 1).saturating_sub(window);
+            let expected = &stream[start..=i];
+            assert_eq!(queue.min(), expected.iter().min());
+            assert_eq!(queue.max(), expected.iter().max());
+            assert_eq!(queue.front(), expected.first());
+        }
     }
 }
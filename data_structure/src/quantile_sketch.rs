@@ -0,0 +1,589 @@
+/*
+    流式分位数草图
+
+    MedianFinder 需要保存所有元素，数据量很大时内存无法承受。
+    这里的两种草图都只占用有界的内存，查询结果是近似值：
+
+    - TDigest：把相邻的数据点聚合成带权重的质心（centroid），
+      靠近两端（q 接近 0 或 1）的质心更小，因此尾部分位数（p99 等）的精度更高。
+    - GKSummary：Greenwald-Khanna 摘要，保证返回值的排名误差不超过 ε·n。
+
+    两者都可以合并，例如每个线程各自维护一个草图，最后再合并成一个。
+*/
+use std::f64::consts::PI;
+
+/// 近似分位数草图的公共接口
+pub trait QuantileSketch {
+    /// 加入一个数据点，NaN 会被忽略
+    fn add(&mut self, value: f64);
+
+    /// 已经加入的数据点个数
+    fn count(&self) -> usize;
+
+    /// 返回近似的 q 分位数，即大约有 q·n 个元素不大于它的值，为空时返回 None
+    fn quantile(&self, q: f64) -> Option<f64>;
+
+    /// 返回不大于 x 的元素个数的近似值
+    fn rank(&self, x: f64) -> f64;
+
+    /// 把另一个草图合并进来
+    fn merge(&mut self, other: &Self);
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+impl Centroid {
+    fn absorb(&mut self, other: &Centroid) {
+        self.weight += other.weight;
+        self.mean += (other.mean - self.mean) * other.weight / self.weight;
+    }
+}
+
+/// 基于合并的 t-digest，使用 k1 尺度函数 k(q) = δ / 2π · asin(2q - 1)
+#[derive(Debug, Clone)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>, // 已经压缩过的质心，按 mean 有序
+    unmerged: Vec<Centroid>,  // 新加入但尚未压缩的点
+    count: usize,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    /// compression 即 δ，质心个数大约不超过 δ，越大精度越高，常用取值为 100
+    pub fn new(compression: f64) -> Self {
+        assert!(compression >= 1.0, "compression must be at least 1");
+        TDigest {
+            compression,
+            centroids: Vec::new(),
+            unmerged: Vec::new(),
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// 当前保存的质心个数（不含尚未压缩的点）
+    pub fn centroid_count(&self) -> usize {
+        self.centroids.len()
+    }
+
+    /// 把缓冲区中的点合并进质心
+    pub fn flush(&mut self) {
+        if !self.unmerged.is_empty() {
+            let pending = std::mem::take(&mut self.unmerged);
+            self.centroids = self.compressed(&pending);
+        }
+    }
+
+    fn k_scale(&self, q: f64) -> f64 {
+        self.compression / (2.0 * PI) * (2.0 * q - 1.0).asin()
+    }
+
+    fn k_scale_inverse(&self, k: f64) -> f64 {
+        if k >= self.compression / 4.0 {
+            1.0
+        } else {
+            ((2.0 * PI * k / self.compression).sin() + 1.0) / 2.0
+        }
+    }
+
+    /// 把现有质心和 pending 一起排序，再把相邻的质心贪心地合并，
+    /// 每个质心覆盖的 k 尺度范围不超过 1
+    fn compressed(&self, pending: &[Centroid]) -> Vec<Centroid> {
+        let mut all: Vec<Centroid> = self.centroids.iter().chain(pending).copied().collect();
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total = self.count as f64;
+        let mut result = Vec::new();
+        let mut iter = all.into_iter();
+        let mut current = match iter.next() {
+            Some(c) => c,
+            None => return result,
+        };
+        let mut weight_so_far = 0.0;
+        let mut q_limit = self.k_scale_inverse(self.k_scale(0.0) + 1.0);
+
+        for next in iter {
+            let q = (weight_so_far + current.weight + next.weight) / total;
+            if q <= q_limit {
+                current.absorb(&next);
+            } else {
+                weight_so_far += current.weight;
+                result.push(current);
+                q_limit = self.k_scale_inverse(self.k_scale(weight_so_far / total) + 1.0);
+                current = next;
+            }
+        }
+        result.push(current);
+        result
+    }
+
+    /// 查询时使用的质心：如果缓冲区不为空，返回压缩后的副本
+    fn merged_centroids(&self) -> Vec<Centroid> {
+        if self.unmerged.is_empty() {
+            self.centroids.clone()
+        } else {
+            self.compressed(&self.unmerged)
+        }
+    }
+
+    /// 把质心看作分段线性的累计分布上的点：(0, min)、(质心中心的累计权重, mean)、(n, max)
+    fn cdf_points(&self) -> Vec<(f64, f64)> {
+        let centroids = self.merged_centroids();
+        let mut points = Vec::with_capacity(centroids.len() + 2);
+        points.push((0.0, self.min));
+        let mut cumulative = 0.0;
+        for c in &centroids {
+            points.push((cumulative + c.weight / 2.0, c.mean));
+            cumulative += c.weight;
+        }
+        points.push((cumulative, self.max));
+        points
+    }
+}
+
+impl QuantileSketch for TDigest {
+    fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.unmerged.push(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+        if self.unmerged.len() as f64 >= 5.0 * self.compression {
+            self.flush();
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        let target = q.clamp(0.0, 1.0) * self.count as f64;
+        let points = self.cdf_points();
+        for pair in points.windows(2) {
+            let ((r0, v0), (r1, v1)) = (pair[0], pair[1]);
+            if target <= r1 {
+                if r1 <= r0 {
+                    return Some(v1);
+                }
+                return Some(v0 + (v1 - v0) * (target - r0) / (r1 - r0));
+            }
+        }
+        Some(self.max)
+    }
+
+    fn rank(&self, x: f64) -> f64 {
+        if self.count == 0 || x < self.min {
+            return 0.0;
+        }
+        if x >= self.max {
+            return self.count as f64;
+        }
+        let points = self.cdf_points();
+        for pair in points.windows(2) {
+            let ((r0, v0), (r1, v1)) = (pair[0], pair[1]);
+            if x < v1 {
+                return r0 + (r1 - r0) * (x - v0) / (v1 - v0);
+            }
+        }
+        self.count as f64
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if other.count == 0 {
+            return;
+        }
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        let mut pending = std::mem::take(&mut self.unmerged);
+        pending.extend(other.centroids.iter().chain(&other.unmerged).copied());
+        self.centroids = self.compressed(&pending);
+    }
+}
+
+/// GK 摘要中的一个元组：value 的最小排名是前面所有 g 之和，最大排名再加上 delta
+#[derive(Debug, Clone, Copy)]
+struct GKTuple {
+    value: f64,
+    g: usize,
+    delta: usize,
+}
+
+/// Greenwald-Khanna 摘要，任意分位数查询的排名误差不超过 ε·n
+#[derive(Debug, Clone)]
+pub struct GKSummary {
+    epsilon: f64,
+    tuples: Vec<GKTuple>,
+    count: usize,
+    inserts_since_compress: usize,
+}
+
+impl GKSummary {
+    pub fn new(epsilon: f64) -> Self {
+        assert!(epsilon > 0.0 && epsilon < 1.0, "epsilon must be in (0, 1)");
+        GKSummary {
+            epsilon,
+            tuples: Vec::new(),
+            count: 0,
+            inserts_since_compress: 0,
+        }
+    }
+
+    /// 误差参数 ε
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    /// 当前保存的元组个数
+    pub fn tuple_count(&self) -> usize {
+        self.tuples.len()
+    }
+
+    /// 每个元组允许覆盖的最大排名区间 floor(2εn)
+    fn threshold(&self) -> usize {
+        (2.0 * self.epsilon * self.count as f64).floor() as usize
+    }
+
+    /// 从后往前把能合并的相邻元组合并，第一个元组始终保留（它记录着最小值）
+    fn compress(&mut self) {
+        let threshold = self.threshold();
+        let mut tuples = std::mem::take(&mut self.tuples);
+        let mut last = match tuples.pop() {
+            Some(t) => t,
+            None => return,
+        };
+        let mut merged = Vec::with_capacity(tuples.len() + 1);
+        for (i, t) in tuples.into_iter().enumerate().rev() {
+            if i != 0 && t.g + last.g + last.delta <= threshold {
+                last.g += t.g;
+            } else {
+                merged.push(last);
+                last = t;
+            }
+        }
+        merged.push(last);
+        merged.reverse();
+        self.tuples = merged;
+    }
+
+    /// 每个元组的 (rmin, rmax)
+    fn rank_bounds(&self) -> Vec<(usize, usize)> {
+        let mut rmin = 0;
+        self.tuples
+            .iter()
+            .map(|t| {
+                rmin += t.g;
+                (rmin, rmin + t.delta)
+            })
+            .collect()
+    }
+}
+
+impl QuantileSketch for GKSummary {
+    fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        let pos = self.tuples.partition_point(|t| t.value <= value);
+        // 新的最小值或最大值的排名是确定的
+        let delta = if pos == 0 || pos == self.tuples.len() {
+            0
+        } else {
+            self.threshold().max(1) - 1
+        };
+        self.tuples.insert(pos, GKTuple { value, g: 1, delta });
+        self.count += 1;
+
+        self.inserts_since_compress += 1;
+        if self.inserts_since_compress as f64 >= 1.0 / (2.0 * self.epsilon) {
+            self.compress();
+            self.inserts_since_compress = 0;
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.count
+    }
+
+    fn quantile(&self, q: f64) -> Option<f64> {
+        if self.tuples.is_empty() {
+            return None;
+        }
+        let r = (q.clamp(0.0, 1.0) * self.count as f64).ceil().max(1.0);
+        let e = self.epsilon * self.count as f64;
+
+        // 找到第一个 rmax 超过 r + e 的元组，它前面的那个元组的排名一定落在 [r - e, r + e] 内
+        let mut prev = self.tuples[0].value;
+        for ((_, rmax), t) in self.rank_bounds().into_iter().zip(&self.tuples) {
+            if rmax as f64 > r + e {
+                return Some(prev);
+            }
+            prev = t.value;
+        }
+        Some(prev)
+    }
+
+    fn rank(&self, x: f64) -> f64 {
+        let pos = self.tuples.partition_point(|t| t.value <= x);
+        if pos == 0 {
+            return 0.0;
+        }
+        if pos == self.tuples.len() {
+            return self.count as f64;
+        }
+        // 真实排名落在 [rmin(pos - 1), rmax(pos) - 1] 内，取中点
+        let bounds = self.rank_bounds();
+        let low = bounds[pos - 1].0 as f64;
+        let high = (bounds[pos].1 - 1) as f64;
+        (low + high) / 2.0
+    }
+
+    fn merge(&mut self, other: &Self) {
+        if other.tuples.is_empty() {
+            return;
+        }
+        if self.tuples.is_empty() {
+            self.tuples = other.tuples.clone();
+            self.count = other.count;
+            return;
+        }
+
+        // 对于一侧的元组，另一侧中排在它前面的元组贡献 rmin，紧跟在它后面的元组贡献 rmax；
+        // 值相等时约定 self 的元组排在前面
+        let ours = self.rank_bounds();
+        let theirs = other.rank_bounds();
+        let mut merged: Vec<(f64, usize, usize)> = Vec::with_capacity(ours.len() + theirs.len());
+        for (t, &(rmin, rmax)) in self.tuples.iter().zip(&ours) {
+            let before = other.tuples.partition_point(|o| o.value < t.value);
+            let extra_min = if before == 0 { 0 } else { theirs[before - 1].0 };
+            let extra_max = theirs.get(before).map_or(other.count, |b| b.1 - 1);
+            merged.push((t.value, rmin + extra_min, rmax + extra_max));
+        }
+        for (t, &(rmin, rmax)) in other.tuples.iter().zip(&theirs) {
+            let before = self.tuples.partition_point(|o| o.value <= t.value);
+            let extra_min = if before == 0 { 0 } else { ours[before - 1].0 };
+            let extra_max = ours.get(before).map_or(self.count, |b| b.1 - 1);
+            merged.push((t.value, rmin + extra_min, rmax + extra_max));
+        }
+        // rmin 在合并后的顺序中严格递增，按它排序即可得到正确的顺序
+        merged.sort_by_key(|&(_, rmin, _)| rmin);
+
+        let mut previous = 0;
+        self.tuples = merged
+            .into_iter()
+            .map(|(value, rmin, rmax)| {
+                let g = rmin - previous;
+                previous = rmin;
+                GKTuple {
+                    value,
+                    g,
+                    delta: rmax - rmin,
+                }
+            })
+            .collect();
+        self.count += other.count;
+        self.compress();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::median_finder::MedianFinder;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const N: usize = 20_000;
+    const QUANTILES: [f64; 9] = [0.0, 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 1.0];
+
+    /// 带有重复值、偏斜分布的整数数据
+    fn seeded_data(seed: u64) -> Vec<i32> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..N)
+            .map(|_| {
+                let u: f64 = rng.gen();
+                (-u.ln() * 10_000.0) as i32
+            })
+            .collect()
+    }
+
+    /// 返回值 value 在有序数据中可能占据的排名区间 [lo, hi]（从 1 开始）
+    fn rank_range(sorted: &[i32], value: f64) -> (usize, usize) {
+        let lo = sorted.partition_point(|&x| (x as f64) < value) + 1;
+        let hi = sorted.partition_point(|&x| (x as f64) <= value);
+        (lo, hi)
+    }
+
+    /// 检查草图对每个分位数返回值的排名误差不超过 max_error，
+    /// 目标排名由精确的 MedianFinder 给出
+    fn check_rank_error<S: QuantileSketch>(sketch: &S, data: &[i32], max_error: f64) {
+        let mut sorted = data.to_vec();
+        sorted.sort();
+        for &q in &QUANTILES {
+            let mut exact = MedianFinder::with_quantile(q);
+            for &x in data {
+                exact.add_num(x);
+            }
+            let exact_value = *exact.quantile_element().unwrap();
+            let target = sorted.partition_point(|&x| x <= exact_value) as f64;
+
+            let approx = sketch.quantile(q).unwrap();
+            let (lo, hi) = rank_range(&sorted, approx);
+            let error = if (target as usize) < lo {
+                lo as f64 - target
+            } else if (target as usize) > hi {
+                target - hi as f64
+            } else {
+                0.0
+            };
+            assert!(
+                error <= max_error,
+                "q = {}: approx {} (ranks {}..={}), exact {} (rank {}), error {} > {}",
+                q,
+                approx,
+                lo,
+                hi,
+                exact_value,
+                target,
+                error,
+                max_error
+            );
+        }
+    }
+
+    #[test]
+    fn empty_sketches() {
+        let digest = TDigest::new(100.0);
+        let gk = GKSummary::new(0.01);
+        assert_eq!(digest.quantile(0.5), None);
+        assert_eq!(gk.quantile(0.5), None);
+        assert_eq!(digest.rank(1.0), 0.0);
+        assert_eq!(gk.rank(1.0), 0.0);
+    }
+
+    #[test]
+    fn tdigest_extremes_are_exact() {
+        let mut digest = TDigest::new(50.0);
+        for x in seeded_data(1) {
+            digest.add(x as f64);
+        }
+        let data = seeded_data(1);
+        assert_eq!(
+            digest.quantile(0.0),
+            Some(*data.iter().min().unwrap() as f64)
+        );
+        assert_eq!(
+            digest.quantile(1.0),
+            Some(*data.iter().max().unwrap() as f64)
+        );
+        assert_eq!(digest.rank(f64::MAX), N as f64);
+    }
+
+    #[test]
+    fn tdigest_rank_error_is_bounded() {
+        let data = seeded_data(30);
+        let mut digest = TDigest::new(200.0);
+        for &x in &data {
+            digest.add(x as f64);
+        }
+        digest.flush();
+        assert!(digest.centroid_count() <= 200);
+        check_rank_error(&digest, &data, 0.01 * N as f64);
+    }
+
+    #[test]
+    fn tdigest_rank_matches_quantile() {
+        let data = seeded_data(31);
+        let mut digest = TDigest::new(200.0);
+        for &x in &data {
+            digest.add(x as f64);
+        }
+        let mut sorted = data.clone();
+        sorted.sort();
+        for &q in &QUANTILES[1..8] {
+            let value = sorted[(q * N as f64) as usize] as f64;
+            let exact = sorted.partition_point(|&x| (x as f64) <= value) as f64;
+            assert!((digest.rank(value) - exact).abs() <= 0.01 * N as f64);
+        }
+    }
+
+    #[test]
+    fn tdigest_merge_keeps_accuracy() {
+        let data = seeded_data(32);
+        let (left, right) = data.split_at(N / 3);
+        let mut a = TDigest::new(200.0);
+        let mut b = TDigest::new(200.0);
+        left.iter().for_each(|&x| a.add(x as f64));
+        right.iter().for_each(|&x| b.add(x as f64));
+        a.merge(&b);
+        assert_eq!(a.count(), N);
+        check_rank_error(&a, &data, 0.01 * N as f64);
+    }
+
+    #[test]
+    fn gk_rank_error_is_within_epsilon() {
+        let data = seeded_data(33);
+        let epsilon = 0.005;
+        let mut gk = GKSummary::new(epsilon);
+        for &x in &data {
+            gk.add(x as f64);
+        }
+        // 内存远小于数据量
+        assert!(gk.tuple_count() < N / 10);
+        check_rank_error(&gk, &data, epsilon * N as f64);
+    }
+
+    #[test]
+    fn gk_rank_is_within_epsilon() {
+        let data = seeded_data(34);
+        let epsilon = 0.005;
+        let mut gk = GKSummary::new(epsilon);
+        for &x in &data {
+            gk.add(x as f64);
+        }
+        let mut sorted = data.clone();
+        sorted.sort();
+        let mut rng = StdRng::seed_from_u64(35);
+        for _ in 0..200 {
+            let x = sorted[rng.gen_range(0..N)] as f64;
+            let exact = sorted.partition_point(|&v| (v as f64) <= x) as f64;
+            assert!((gk.rank(x) - exact).abs() <= epsilon * N as f64);
+        }
+    }
+
+    #[test]
+    fn gk_merge_is_within_epsilon() {
+        let data = seeded_data(36);
+        let epsilon = 0.005;
+        let mut parts: Vec<GKSummary> = data
+            .chunks(N / 4)
+            .map(|chunk| {
+                let mut gk = GKSummary::new(epsilon);
+                chunk.iter().for_each(|&x| gk.add(x as f64));
+                gk
+            })
+            .collect();
+        let mut merged = parts.remove(0);
+        for part in &parts {
+            merged.merge(part);
+        }
+        assert_eq!(merged.count(), N);
+        check_rank_error(&merged, &data, epsilon * N as f64);
+    }
+}
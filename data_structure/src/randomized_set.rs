@@ -1,51 +1,218 @@
-use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 
-struct RandomizedSet {
-    map: HashMap<i32, usize>, // 记录元素及其在vec中的索引
-    vec: Vec<i32>,            // 存储元素
+/// 支持 O(1) 插入、删除和等概率随机取值的集合
+///
+/// 随机数来源 `R` 可以由调用方注入，测试时传入固定种子的 `StdRng` 即可得到可复现的结果。
+pub struct RandomizedSet<T, R = StdRng> {
+    map: HashMap<T, usize>, // 记录元素及其在vec中的索引
+    vec: Vec<T>,            // 存储元素
+    rng: R,
 }
 
-impl RandomizedSet {
-    // 初始化一个新的 RandomizedSet 对象
-    fn new() -> Self {
+impl<T> RandomizedSet<T, StdRng>
+where
+    T: Hash + Eq + Clone,
+{
+    /// 初始化一个新的 RandomizedSet 对象，随机数生成器使用系统熵作为种子
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    /// 使用固定的种子初始化，方便复现随机结果
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<T> Default for RandomizedSet<T, StdRng>
+where
+    T: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, R> RandomizedSet<T, R>
+where
+    T: Hash + Eq + Clone,
+    R: Rng,
+{
+    /// 使用调用方提供的随机数生成器初始化
+    pub fn with_rng(rng: R) -> Self {
         RandomizedSet {
             map: HashMap::new(),
             vec: Vec::new(),
+            rng,
         }
     }
 
-    // 插入一个元素，如果元素不存在则插入并返回 true，否则返回 false
-    fn insert(&mut self, val: i32) -> bool {
+    /// 返回集合中元素的个数
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// 判断集合是否为空
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// 判断元素是否在集合中
+    pub fn contains(&self, val: &T) -> bool {
+        self.map.contains_key(val)
+    }
+
+    /// 插入一个元素，如果元素不存在则插入并返回 true，否则返回 false
+    pub fn insert(&mut self, val: T) -> bool {
         if self.map.contains_key(&val) {
             return false;
         }
+        self.map.insert(val.clone(), self.vec.len()); // 记录元素在 vec 中的索引
         self.vec.push(val);
-        self.map.insert(val, self.vec.len() - 1); // 记录元素在 vec 中的索引
         true
     }
 
-    // 删除一个元素，如果元素存在则删除并返回 true，否则返回 false
-    fn remove(&mut self, val: i32) -> bool {
-        if let Some(&index) = self.map.get(&val) {
-            let last = *self.vec.last().unwrap();
-            self.vec[index] = last; // 用最后一个元素覆盖要删除的元素
-            self.map.insert(last, index); // 更新最后一个元素的索引
-            self.vec.pop(); // 删除最后一个元素
-            self.map.remove(&val); // 从 map 中删除
-            return true;
+    /// 删除一个元素，如果元素存在则删除并返回 true，否则返回 false
+    pub fn remove(&mut self, val: &T) -> bool {
+        match self.map.remove(val) {
+            Some(index) => {
+                // 用最后一个元素覆盖要删除的元素，再更新被移动元素的索引
+                self.vec.swap_remove(index);
+                if let Some(moved) = self.vec.get(index) {
+                    self.map.insert(moved.clone(), index);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 等概率地随机返回一个集合中的元素，集合为空时返回 None
+    pub fn get_random(&mut self) -> Option<&T> {
+        if self.vec.is_empty() {
+            return None;
+        }
+        let index = self.rng.gen_range(0..self.vec.len());
+        Some(&self.vec[index])
+    }
+
+    /// 不放回地随机抽取 k 个元素，k 超过集合大小时返回全部元素（顺序随机）
+    pub fn sample_k(&mut self, k: usize) -> Vec<&T> {
+        let amount = k.min(self.vec.len());
+        rand::seq::index::sample(&mut self.rng, self.vec.len(), amount)
+            .into_iter()
+            .map(|index| &self.vec[index])
+            .collect()
+    }
+
+    /// 按内部存储顺序遍历集合中的元素
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.vec.iter()
+    }
+}
+
+/// 允许重复元素的随机集合，`get_random` 返回某个值的概率与它出现的次数成正比
+pub struct RandomizedCollection<T, R = StdRng> {
+    map: HashMap<T, HashSet<usize>>, // 元素 -> 它在 vec 中出现的所有位置
+    vec: Vec<T>,
+    rng: R,
+}
+
+impl<T> RandomizedCollection<T, StdRng>
+where
+    T: Hash + Eq + Clone,
+{
+    /// 初始化一个新的 RandomizedCollection 对象，随机数生成器使用系统熵作为种子
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_entropy())
+    }
+
+    /// 使用固定的种子初始化，方便复现随机结果
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<T> Default for RandomizedCollection<T, StdRng>
+where
+    T: Hash + Eq + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, R> RandomizedCollection<T, R>
+where
+    T: Hash + Eq + Clone,
+    R: Rng,
+{
+    /// 使用调用方提供的随机数生成器初始化
+    pub fn with_rng(rng: R) -> Self {
+        RandomizedCollection {
+            map: HashMap::new(),
+            vec: Vec::new(),
+            rng,
+        }
+    }
+
+    /// 返回集合中元素的总个数（重复元素分别计数）
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// 判断集合是否为空
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// 返回某个值在集合中出现的次数
+    pub fn count(&self, val: &T) -> usize {
+        self.map.get(val).map_or(0, |indices| indices.len())
+    }
+
+    /// 插入一个元素，如果插入前集合中没有这个值则返回 true，否则返回 false
+    pub fn insert(&mut self, val: T) -> bool {
+        let indices = self.map.entry(val.clone()).or_default();
+        indices.insert(self.vec.len());
+        self.vec.push(val);
+        indices.len() == 1
+    }
+
+    /// 删除一个元素的一次出现，如果元素存在则删除并返回 true，否则返回 false
+    pub fn remove(&mut self, val: &T) -> bool {
+        let indices = match self.map.get_mut(val) {
+            Some(indices) => indices,
+            None => return false,
+        };
+        // 任取一个该值出现的位置
+        let index = *indices.iter().next().unwrap();
+        indices.remove(&index);
+        if indices.is_empty() {
+            self.map.remove(val);
         }
-        false
+
+        // 用最后一个元素填补空位，并把它的位置从 last 改成 index
+        let last = self.vec.len() - 1;
+        self.vec.swap_remove(index);
+        if index != last {
+            let moved = self.map.get_mut(&self.vec[index]).unwrap();
+            moved.remove(&last);
+            moved.insert(index);
+        }
+        true
     }
 
-    // 随机返回一个集合中的元素
-    fn get_random(&self) -> i32 {
-        let mut seed = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_nanos();
-        seed = seed % self.vec.len() as u128;
-        self.vec[seed as usize]
+    /// 随机返回一个元素，每个值被选中的概率与它出现的次数成正比，集合为空时返回 None
+    pub fn get_random(&mut self) -> Option<&T> {
+        if self.vec.is_empty() {
+            return None;
+        }
+        let index = self.rng.gen_range(0..self.vec.len());
+        Some(&self.vec[index])
     }
 }
 
@@ -55,12 +222,108 @@ mod test {
 
     #[test]
     fn simple_test() {
-        let mut obj = RandomizedSet::new();
-        assert_eq!(obj.insert(1), true);
-        assert_eq!(obj.insert(1), false);
-        assert_eq!(obj.insert(2), true);
-        assert_eq!(obj.remove(1), true);
-        assert_eq!(obj.remove(1), false);
-        println!("Random element: {}", obj.get_random());
+        let mut obj = RandomizedSet::with_seed(7);
+        assert!(obj.insert(1));
+        assert!(!obj.insert(1));
+        assert!(obj.insert(2));
+        assert!(obj.remove(&1));
+        assert!(!obj.remove(&1));
+        assert_eq!(obj.get_random(), Some(&2));
+    }
+
+    #[test]
+    fn empty_set_returns_none() {
+        let mut obj = RandomizedSet::<i32>::with_seed(1);
+        assert_eq!(obj.get_random(), None);
+        assert!(obj.sample_k(3).is_empty());
+        obj.insert(5);
+        obj.remove(&5);
+        assert_eq!(obj.get_random(), None);
+    }
+
+    #[test]
+    fn generic_keys_and_index_bookkeeping() {
+        let mut obj = RandomizedSet::with_seed(3);
+        for word in ["a", "b", "c", "d"] {
+            obj.insert(word.to_string());
+        }
+        assert!(obj.remove(&"a".to_string()));
+        assert!(obj.remove(&"c".to_string()));
+        assert!(obj.insert("e".to_string()));
+        assert_eq!(obj.len(), 3);
+        for word in ["b", "d", "e"] {
+            assert!(obj.contains(&word.to_string()));
+        }
+        // 删除后剩下的元素仍然都能被正确删除，说明索引没有错乱
+        for word in ["d", "e", "b"] {
+            assert!(obj.remove(&word.to_string()));
+        }
+        assert!(obj.is_empty());
+    }
+
+    #[test]
+    fn get_random_is_roughly_uniform() {
+        let mut obj = RandomizedSet::with_seed(42);
+        for i in 0..4 {
+            obj.insert(i);
+        }
+        let mut counts = [0; 4];
+        for _ in 0..40_000 {
+            counts[*obj.get_random().unwrap()] += 1;
+        }
+        for count in counts {
+            assert!((9_000..11_000).contains(&count), "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn sample_k_without_replacement() {
+        let mut obj = RandomizedSet::with_seed(11);
+        for i in 0..10 {
+            obj.insert(i);
+        }
+        let mut sample: Vec<i32> = obj.sample_k(4).into_iter().copied().collect();
+        assert_eq!(sample.len(), 4);
+        sample.sort();
+        sample.dedup();
+        assert_eq!(sample.len(), 4);
+
+        let mut all: Vec<i32> = obj.sample_k(20).into_iter().copied().collect();
+        all.sort();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn collection_with_duplicates() {
+        let mut obj = RandomizedCollection::with_seed(5);
+        assert!(obj.insert(1));
+        assert!(!obj.insert(1));
+        assert!(obj.insert(2));
+        assert_eq!(obj.count(&1), 2);
+        assert_eq!(obj.len(), 3);
+
+        assert!(obj.remove(&1));
+        assert_eq!(obj.count(&1), 1);
+        assert!(obj.remove(&1));
+        assert!(!obj.remove(&1));
+        assert_eq!(obj.get_random(), Some(&2));
+        assert!(obj.remove(&2));
+        assert_eq!(obj.get_random(), None);
+    }
+
+    #[test]
+    fn collection_random_is_weighted_by_multiplicity() {
+        let mut obj = RandomizedCollection::with_seed(9);
+        obj.insert('a');
+        for _ in 0..3 {
+            obj.insert('b');
+        }
+        let mut b = 0;
+        for _ in 0..40_000 {
+            if *obj.get_random().unwrap() == 'b' {
+                b += 1;
+            }
+        }
+        assert!((29_000..31_000).contains(&b), "{}", b);
     }
 }
@@ -0,0 +1,737 @@
+/*
+    环形缓冲区双端队列（RingDeque）
+
+    元素保存在一块长度为 capacity 的连续内存中，head 指向队首元素，
+    逻辑下标 i 对应物理位置 (head + i) % capacity。两端的 push 和 pop 都是 O(1)，
+    存满时容量翻倍，因此均摊 O(1)。
+
+    - 可增长模式（new / with_capacity）：存满时扩容。
+    - 固定容量模式（bounded）：存满时不再扩容，而是覆盖另一端最旧的元素，
+      适合用作有界的日志或者指标环。
+
+    缓冲区的槽位是 MaybeUninit<T>，只有 [head, head + len) 范围内（环形意义下）的槽位是初始化的，
+    所有 unsafe 代码都依赖这个不变量。
+*/
+use std::fmt;
+use std::iter::FusedIterator;
+use std::mem::MaybeUninit;
+use std::ops::{Index, IndexMut};
+use std::slice;
+
+pub struct RingDeque<T> {
+    buf: Box<[MaybeUninit<T>]>,
+    head: usize,
+    len: usize,
+    bounded: bool,
+}
+
+fn uninit_buffer<T>(capacity: usize) -> Box<[MaybeUninit<T>]> {
+    let mut buf = Vec::with_capacity(capacity);
+    buf.resize_with(capacity, MaybeUninit::uninit);
+    buf.into_boxed_slice()
+}
+
+impl<T> RingDeque<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        RingDeque {
+            buf: uninit_buffer(capacity),
+            head: 0,
+            len: 0,
+            bounded: false,
+        }
+    }
+
+    /// 固定容量的队列：存满后 push_back 会覆盖队首，push_front 会覆盖队尾
+    pub fn bounded(capacity: usize) -> Self {
+        let mut deque = Self::with_capacity(capacity);
+        deque.bounded = true;
+        deque
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+
+    pub fn is_bounded(&self) -> bool {
+        self.bounded
+    }
+
+    // 逻辑下标对应的物理位置，要求 i <= capacity
+    fn physical(&self, i: usize) -> usize {
+        let j = self.head + i;
+        if j >= self.capacity() {
+            j - self.capacity()
+        } else {
+            j
+        }
+    }
+
+    fn grow(&mut self) {
+        let new_capacity = (self.capacity() * 2).max(4);
+        let mut buf = uninit_buffer(new_capacity);
+        let (front, back) = self.as_slices();
+        // 按逻辑顺序把元素搬到新缓冲区的开头，旧缓冲区只释放内存，不会再 drop 元素
+        unsafe {
+            let dst = buf.as_mut_ptr() as *mut T;
+            std::ptr::copy_nonoverlapping(front.as_ptr(), dst, front.len());
+            std::ptr::copy_nonoverlapping(back.as_ptr(), dst.add(front.len()), back.len());
+        }
+        self.buf = buf;
+        self.head = 0;
+    }
+
+    /// 在队尾插入。固定容量模式下队列已满时返回被覆盖的队首元素
+    pub fn push_back(&mut self, value: T) -> Option<T> {
+        if self.is_full() {
+            if self.bounded {
+                if self.capacity() == 0 {
+                    return Some(value);
+                }
+                // 队尾的下一个位置就是队首
+                let slot = &mut self.buf[self.head];
+                let evicted = unsafe { slot.assume_init_read() };
+                slot.write(value);
+                self.head = self.physical(1);
+                return Some(evicted);
+            }
+            self.grow();
+        }
+        let index = self.physical(self.len);
+        self.buf[index].write(value);
+        self.len += 1;
+        None
+    }
+
+    /// 在队首插入。固定容量模式下队列已满时返回被覆盖的队尾元素
+    pub fn push_front(&mut self, value: T) -> Option<T> {
+        if self.is_full() {
+            if self.bounded {
+                if self.capacity() == 0 {
+                    return Some(value);
+                }
+                // 队首的前一个位置就是队尾
+                self.head = self.physical(self.capacity() - 1);
+                let slot = &mut self.buf[self.head];
+                let evicted = unsafe { slot.assume_init_read() };
+                slot.write(value);
+                return Some(evicted);
+            }
+            self.grow();
+        }
+        self.head = self.physical(self.capacity() - 1);
+        self.buf[self.head].write(value);
+        self.len += 1;
+        None
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        let value = unsafe { self.buf[self.head].assume_init_read() };
+        self.head = self.physical(1);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        let index = self.physical(self.len);
+        Some(unsafe { self.buf[index].assume_init_read() })
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        if i >= self.len {
+            return None;
+        }
+        Some(unsafe { self.buf[self.physical(i)].assume_init_ref() })
+    }
+
+    pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+        if i >= self.len {
+            return None;
+        }
+        let index = self.physical(i);
+        Some(unsafe { self.buf[index].assume_init_mut() })
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.wrapping_sub(1))
+    }
+
+    pub fn swap(&mut self, i: usize, j: usize) {
+        assert!(
+            i < self.len && j < self.len,
+            "index out of bounds: the len is {} but the indices are {} and {}",
+            self.len,
+            i,
+            j
+        );
+        let (i, j) = (self.physical(i), self.physical(j));
+        self.buf.swap(i, j);
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// 只保留前 len 个元素
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.pop_back();
+        }
+    }
+
+    // 两段已初始化区域的物理范围
+    fn ranges(&self) -> ((usize, usize), (usize, usize)) {
+        let first_len = self.len.min(self.capacity() - self.head);
+        ((self.head, first_len), (0, self.len - first_len))
+    }
+
+    /// 按逻辑顺序返回两段切片，第二段在缓冲区的开头（没有绕回时为空）
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let ((a, a_len), (b, b_len)) = self.ranges();
+        let ptr = self.buf.as_ptr() as *const T;
+        unsafe {
+            (
+                slice::from_raw_parts(ptr.add(a), a_len),
+                slice::from_raw_parts(ptr.add(b), b_len),
+            )
+        }
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let ((a, a_len), (b, b_len)) = self.ranges();
+        let ptr = self.buf.as_mut_ptr() as *mut T;
+        // 两段区域互不重叠
+        unsafe {
+            (
+                slice::from_raw_parts_mut(ptr.add(a), a_len),
+                slice::from_raw_parts_mut(ptr.add(b), b_len),
+            )
+        }
+    }
+
+    /// 移动元素使它们在内存中连续，返回包含所有元素的切片
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        if self.head + self.len > self.capacity() {
+            // 整体旋转缓冲区，让队首回到位置 0。MaybeUninit 的移动不会读取或者 drop 元素
+            self.buf.rotate_left(self.head);
+            self.head = 0;
+        }
+        self.as_mut_slices().0
+    }
+
+    /// 把前 n 个元素移到队尾，O(min(n, len - n))
+    pub fn rotate_left(&mut self, n: usize) {
+        assert!(n <= self.len, "rotate by {} exceeds len {}", n, self.len);
+        if self.is_full() {
+            self.head = self.physical(n);
+        } else if n <= self.len - n {
+            for _ in 0..n {
+                let value = self.pop_front().unwrap();
+                self.push_back(value);
+            }
+        } else {
+            for _ in n..self.len {
+                let value = self.pop_back().unwrap();
+                self.push_front(value);
+            }
+        }
+    }
+
+    /// 把后 n 个元素移到队首
+    pub fn rotate_right(&mut self, n: usize) {
+        assert!(n <= self.len, "rotate by {} exceeds len {}", n, self.len);
+        self.rotate_left(self.len - n);
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+        Iter {
+            front: front.iter(),
+            back: back.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+        IterMut {
+            front: front.iter_mut(),
+            back: back.iter_mut(),
+        }
+    }
+
+    /// 按顺序移出所有元素，迭代器提前销毁时剩下的元素也会被删除
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain { deque: self }
+    }
+}
+
+impl<T> Drop for RingDeque<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Default for RingDeque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for RingDeque<T> {
+    fn clone(&self) -> Self {
+        let mut deque = Self::with_capacity(self.capacity());
+        deque.bounded = self.bounded;
+        deque.extend(self.iter().cloned());
+        deque
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RingDeque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for RingDeque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for RingDeque<T> {}
+
+impl<T> Index<usize> for RingDeque<T> {
+    type Output = T;
+
+    fn index(&self, i: usize) -> &T {
+        let len = self.len;
+        self.get(i).unwrap_or_else(|| {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, i
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<usize> for RingDeque<T> {
+    fn index_mut(&mut self, i: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(i).unwrap_or_else(|| {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, i
+            )
+        })
+    }
+}
+
+impl<T> Extend<T> for RingDeque<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for RingDeque<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut deque = Self::with_capacity(iter.size_hint().0);
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T> From<Vec<T>> for RingDeque<T> {
+    fn from(values: Vec<T>) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: slice::Iter<'a, T>,
+    back: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    front: slice::IterMut<'a, T>,
+    back: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.front.next().or_else(|| self.back.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.front.len() + self.back.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        self.back.next_back().or_else(|| self.front.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T> {
+    deque: RingDeque<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.deque.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.deque.len, Some(self.deque.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.deque.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+pub struct Drain<'a, T> {
+    deque: &'a mut RingDeque<T>,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.deque.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.deque.len, Some(self.deque.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.deque.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> FusedIterator for Drain<'_, T> {}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        self.deque.clear();
+    }
+}
+
+impl<T> IntoIterator for RingDeque<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { deque: self }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingDeque<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut RingDeque<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+// 测试规模保持较小，以便在 Miri 下运行：cargo +nightly miri test ring_deque
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    // 被 drop 时给计数器加一
+    struct DropCounter(Rc<Cell<usize>>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_push_pop_both_ends() {
+        let mut deque = RingDeque::new();
+        assert_eq!(deque.pop_front(), None);
+        assert_eq!(deque.back(), None);
+        for i in 0..5 {
+            deque.push_back(i);
+            deque.push_front(-i);
+        }
+        assert_eq!(deque.len(), 10);
+        assert_eq!(deque.front(), Some(&-4));
+        assert_eq!(deque.back(), Some(&4));
+        assert_eq!(deque[5], 0);
+        deque[5] = 100;
+        *deque.back_mut().unwrap() += 1;
+        let collected: Vec<i32> = deque.iter().copied().collect();
+        assert_eq!(collected, vec![-4, -3, -2, -1, 0, 100, 1, 2, 3, 5]);
+        assert_eq!(deque.pop_front(), Some(-4));
+        assert_eq!(deque.pop_back(), Some(5));
+        deque.swap(0, 1);
+        assert_eq!(deque.front(), Some(&-2));
+        assert_eq!(deque.get(8), None);
+    }
+
+    #[test]
+    fn test_slices_and_make_contiguous() {
+        let mut deque = RingDeque::with_capacity(8);
+        for i in 0..6 {
+            deque.push_back(i);
+        }
+        for _ in 0..4 {
+            deque.pop_front();
+        }
+        for i in 6..10 {
+            deque.push_back(i);
+        }
+        // 缓冲区中的布局：[8, 9, _, _, 4, 5, 6, 7]
+        assert_eq!(deque.capacity(), 8);
+        assert_eq!(deque.as_slices(), (&[4, 5, 6, 7][..], &[8, 9][..]));
+        deque.as_mut_slices().1[0] = 80;
+        assert_eq!(deque.make_contiguous(), &mut [4, 5, 6, 7, 80, 9]);
+        assert_eq!(deque.as_slices(), (&[4, 5, 6, 7, 80, 9][..], &[][..]));
+        deque.push_front(3);
+        assert_eq!(deque.iter().next_back(), Some(&9));
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut deque: RingDeque<i32> = (0..7).collect();
+        deque.rotate_left(2);
+        assert_eq!(deque, (2..7).chain(0..2).collect());
+        deque.rotate_right(2);
+        assert_eq!(deque, (0..7).collect());
+        deque.rotate_left(6);
+        assert_eq!(deque, [6, 0, 1, 2, 3, 4, 5].into_iter().collect());
+        deque.rotate_left(0);
+        deque.rotate_right(7);
+        assert_eq!(deque[0], 6);
+
+        let mut full = RingDeque::bounded(4);
+        full.extend(0..4);
+        full.rotate_left(3);
+        assert_eq!(full.iter().copied().collect::<Vec<_>>(), vec![3, 0, 1, 2]);
+    }
+
+    #[test]
+    fn test_bounded_overwrite() {
+        let mut ring = RingDeque::bounded(3);
+        assert_eq!(ring.push_back(1), None);
+        assert_eq!(ring.push_back(2), None);
+        assert_eq!(ring.push_back(3), None);
+        assert!(ring.is_full());
+        assert_eq!(ring.push_back(4), Some(1));
+        assert_eq!(ring.push_back(5), Some(2));
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(ring.push_front(0), Some(5));
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), vec![0, 3, 4]);
+        assert_eq!(ring.capacity(), 3);
+
+        let mut empty = RingDeque::bounded(0);
+        assert_eq!(empty.push_back("x"), Some("x"));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut deque: RingDeque<String> =
+            ["a", "b", "c", "d"].iter().map(|s| s.to_string()).collect();
+        deque.push_front("z".to_string());
+        let mut iter = deque.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next().map(String::as_str), Some("z"));
+        assert_eq!(iter.next_back().map(String::as_str), Some("d"));
+        assert_eq!(iter.len(), 3);
+
+        for s in &mut deque {
+            s.push('!');
+        }
+        let reversed: Vec<String> = deque.clone().into_iter().rev().collect();
+        assert_eq!(reversed, vec!["d!", "c!", "b!", "a!", "z!"]);
+
+        let mut drain = deque.drain();
+        assert_eq!(drain.next().as_deref(), Some("z!"));
+        assert_eq!(drain.next_back().as_deref(), Some("d!"));
+        drop(drain);
+        assert!(deque.is_empty());
+        assert_eq!(format!("{:?}", RingDeque::from(vec![1, 2])), "[1, 2]");
+    }
+
+    #[test]
+    fn test_drop_correctness() {
+        let counter = Rc::new(Cell::new(0));
+        let make = || DropCounter(counter.clone());
+
+        let mut deque = RingDeque::with_capacity(2);
+        for _ in 0..5 {
+            deque.push_back(make());
+            deque.push_front(make());
+        }
+        drop(deque.pop_front());
+        assert_eq!(counter.get(), 1);
+        deque.truncate(6);
+        assert_eq!(counter.get(), 4);
+        deque.make_contiguous();
+        deque.rotate_left(4);
+        assert_eq!(counter.get(), 4);
+        drop(deque);
+        assert_eq!(counter.get(), 10);
+
+        let mut ring = RingDeque::bounded(2);
+        for _ in 0..5 {
+            drop(ring.push_back(make()));
+        }
+        assert_eq!(counter.get(), 13);
+        let mut into_iter = ring.into_iter();
+        drop(into_iter.next());
+        drop(into_iter);
+        assert_eq!(counter.get(), 15);
+
+        let mut deque: RingDeque<DropCounter> = (0..3).map(|_| make()).collect();
+        deque.drain().next();
+        assert_eq!(counter.get(), 18);
+        deque.push_back(make());
+        deque.clear();
+        assert_eq!(counter.get(), 19);
+    }
+
+    #[test]
+    fn test_zero_sized_type() {
+        let mut deque = RingDeque::new();
+        for _ in 0..10 {
+            deque.push_back(());
+        }
+        deque.rotate_left(3);
+        assert_eq!(deque.len(), 10);
+        assert_eq!(deque.iter().count(), 10);
+        assert_eq!(deque.pop_front(), Some(()));
+    }
+
+    #[test]
+    fn test_against_vec_deque() {
+        let mut rng = StdRng::seed_from_u64(44);
+        let mut deque = RingDeque::new();
+        let mut expected = VecDeque::new();
+        for step in 0..2_000 {
+            match rng.gen_range(0..6) {
+                0 | 1 => {
+                    deque.push_back(step);
+                    expected.push_back(step);
+                }
+                2 => {
+                    deque.push_front(step);
+                    expected.push_front(step);
+                }
+                3 => assert_eq!(deque.pop_front(), expected.pop_front()),
+                4 => assert_eq!(deque.pop_back(), expected.pop_back()),
+                _ if !expected.is_empty() => {
+                    let n = rng.gen_range(0..=expected.len());
+                    deque.rotate_left(n);
+                    expected.rotate_left(n);
+                }
+                _ => {}
+            }
+            assert_eq!(deque.len(), expected.len());
+            if step % 100 == 0 {
+                assert!(deque.iter().eq(expected.iter()));
+                assert!(deque.make_contiguous().iter().eq(expected.iter()));
+            }
+        }
+        assert!(deque.into_iter().eq(expected.into_iter()));
+    }
+}
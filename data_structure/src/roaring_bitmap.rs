@@ -0,0 +1,842 @@
+/*
+    Roaring 压缩位图
+
+    把 u32 按高 16 位分块，每块（2^16 个值）用一个容器保存低 16 位，按数据的疏密选择表示方式：
+    - Array：有序的 u16 数组，元素不超过 4096 个时使用（每个元素 2 字节）
+    - Bitmap：65536 位的 BitSet（固定 8KB），元素多于 4096 个时使用
+    - Run：有序的区间列表 [start, last]（每个区间 4 字节），适合大段连续的值，
+      由 run_optimize 在更省空间时转换得到
+
+    集合运算按块对齐：只在一侧出现的块直接复制或丢弃，两侧都有的块做容器之间的运算。
+    两个数组容器直接归并，其余情况先展开成位图再按字运算，最后根据结果的大小选回合适的容器。
+
+    序列化格式（小端序）：容器个数 u32，然后每个容器依次是
+    高 16 位 u16、类型 u8、长度 u32 和数据（数组元素 / 位图的 1024 个字 / 区间端点）。
+*/
+use crate::bit_set::{BitSet, ByteReader, MalformedBitmap};
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+
+// 数组容器的最大元素个数，超过后数组比位图更占空间
+const ARRAY_MAX: usize = 4096;
+const BITMAP_WORDS: usize = 1024;
+const BITMAP_BYTES: usize = BITMAP_WORDS * 8;
+
+const ARRAY_TAG: u8 = 0;
+const BITMAP_TAG: u8 = 1;
+const RUN_TAG: u8 = 2;
+
+#[derive(Clone)]
+enum Container {
+    Array(Vec<u16>),
+    // 位图和缓存的元素个数
+    Bitmap(BitSet, usize),
+    // 有序、互不相邻的闭区间
+    Run(Vec<(u16, u16)>),
+}
+
+#[derive(Clone, Copy)]
+enum SetOp {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl SetOp {
+    // 是否保留：只在左侧出现、两侧都出现、只在右侧出现的元素
+    fn keeps(self) -> (bool, bool, bool) {
+        match self {
+            SetOp::Union => (true, true, true),
+            SetOp::Intersection => (false, true, false),
+            SetOp::Difference => (true, false, false),
+            SetOp::SymmetricDifference => (true, false, true),
+        }
+    }
+
+    fn apply(self, a: &mut BitSet, b: &BitSet) {
+        match self {
+            SetOp::Union => a.union_with(b),
+            SetOp::Intersection => a.intersect_with(b),
+            SetOp::Difference => a.difference_with(b),
+            SetOp::SymmetricDifference => a.symmetric_difference_with(b),
+        }
+    }
+}
+
+// 归并两个有序数组
+fn merge_sorted(a: &[u16], b: &[u16], op: SetOp) -> Vec<u16> {
+    let (left, both, right) = op.keeps();
+    let mut result = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] < b[j] {
+            if left {
+                result.push(a[i]);
+            }
+            i += 1;
+        } else if a[i] > b[j] {
+            if right {
+                result.push(b[j]);
+            }
+            j += 1;
+        } else {
+            if both {
+                result.push(a[i]);
+            }
+            i += 1;
+            j += 1;
+        }
+    }
+    if left {
+        result.extend_from_slice(&a[i..]);
+    }
+    if right {
+        result.extend_from_slice(&b[j..]);
+    }
+    result
+}
+
+impl Container {
+    // 根据元素个数选择数组或位图，空集合返回 None
+    fn from_sorted(values: Vec<u16>) -> Option<Container> {
+        match values.len() {
+            0 => None,
+            n if n <= ARRAY_MAX => Some(Container::Array(values)),
+            n => Some(Container::Bitmap(
+                values.into_iter().map(usize::from).collect(),
+                n,
+            )),
+        }
+    }
+
+    fn from_bitset(bits: BitSet) -> Option<Container> {
+        match bits.len() {
+            0 => None,
+            n if n <= ARRAY_MAX => Some(Container::Array(
+                bits.iter_ones().map(|x| x as u16).collect(),
+            )),
+            n => Some(Container::Bitmap(bits, n)),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Container::Array(values) => values.len(),
+            Container::Bitmap(_, len) => *len,
+            Container::Run(runs) => runs
+                .iter()
+                .map(|&(start, last)| (last - start) as usize + 1)
+                .sum(),
+        }
+    }
+
+    fn contains(&self, x: u16) -> bool {
+        match self {
+            Container::Array(values) => values.binary_search(&x).is_ok(),
+            Container::Bitmap(bits, _) => bits.contains(x as usize),
+            Container::Run(runs) => {
+                let i = runs.partition_point(|&(_, last)| last < x);
+                i < runs.len() && runs[i].0 <= x
+            }
+        }
+    }
+
+    fn insert(&mut self, x: u16) -> bool {
+        match self {
+            Container::Array(values) => {
+                let Err(i) = values.binary_search(&x) else {
+                    return false;
+                };
+                values.insert(i, x);
+                if values.len() > ARRAY_MAX {
+                    *self = Container::from_sorted(std::mem::take(values)).unwrap();
+                }
+                true
+            }
+            Container::Bitmap(bits, len) => {
+                let inserted = bits.insert(x as usize);
+                *len += inserted as usize;
+                inserted
+            }
+            Container::Run(runs) => {
+                let i = runs.partition_point(|&(_, last)| last < x);
+                if i < runs.len() && runs[i].0 <= x {
+                    return false;
+                }
+                let joins_left = i > 0 && runs[i - 1].1 + 1 == x;
+                let joins_right = i < runs.len() && x + 1 == runs[i].0;
+                match (joins_left, joins_right) {
+                    (true, true) => {
+                        runs[i - 1].1 = runs[i].1;
+                        runs.remove(i);
+                    }
+                    (true, false) => runs[i - 1].1 = x,
+                    (false, true) => runs[i].0 = x,
+                    (false, false) => runs.insert(i, (x, x)),
+                }
+                self.shrink_runs();
+                true
+            }
+        }
+    }
+
+    fn remove(&mut self, x: u16) -> bool {
+        match self {
+            Container::Array(values) => match values.binary_search(&x) {
+                Ok(i) => {
+                    values.remove(i);
+                    true
+                }
+                Err(_) => false,
+            },
+            Container::Bitmap(bits, len) => {
+                if !bits.remove(x as usize) {
+                    return false;
+                }
+                *len -= 1;
+                if *len <= ARRAY_MAX {
+                    *self = Container::from_bitset(std::mem::take(bits)).unwrap();
+                }
+                true
+            }
+            Container::Run(runs) => {
+                let i = runs.partition_point(|&(_, last)| last < x);
+                if i == runs.len() || runs[i].0 > x {
+                    return false;
+                }
+                let (start, last) = runs[i];
+                if start == last {
+                    runs.remove(i);
+                } else if x == start {
+                    runs[i].0 += 1;
+                } else if x == last {
+                    runs[i].1 -= 1;
+                } else {
+                    runs[i].1 = x - 1;
+                    runs.insert(i + 1, (x + 1, last));
+                }
+                self.shrink_runs();
+                true
+            }
+        }
+    }
+
+    // 区间太多、不再比位图省空间时，转换回数组或者位图
+    fn shrink_runs(&mut self) {
+        if let Container::Run(runs) = self {
+            if runs.len() * 4 > BITMAP_BYTES {
+                *self = Container::from_bitset(self.to_bitset()).unwrap();
+            }
+        }
+    }
+
+    fn to_bitset(&self) -> BitSet {
+        let mut bits = BitSet::with_capacity(1 << 16);
+        match self {
+            Container::Array(values) => bits.extend(values.iter().map(|&x| x as usize)),
+            Container::Bitmap(other, _) => bits.union_with(other),
+            Container::Run(runs) => {
+                for &(start, last) in runs {
+                    bits.insert_range(start as usize..last as usize + 1);
+                }
+            }
+        }
+        bits
+    }
+
+    fn runs(&self) -> Vec<(u16, u16)> {
+        if let Container::Run(runs) = self {
+            return runs.clone();
+        }
+        let mut runs: Vec<(u16, u16)> = Vec::new();
+        for x in self.iter() {
+            match runs.last_mut() {
+                Some((_, last)) if *last + 1 == x => *last = x,
+                _ => runs.push((x, x)),
+            }
+        }
+        runs
+    }
+
+    // 选择序列化后占空间最小的表示
+    fn optimize(self) -> Container {
+        let len = self.len();
+        let runs = self.runs();
+        let array_size = if len <= ARRAY_MAX {
+            2 * len
+        } else {
+            usize::MAX
+        };
+        let best_other = array_size.min(BITMAP_BYTES);
+        if runs.len() * 4 < best_other {
+            Container::Run(runs)
+        } else if matches!(self, Container::Run(_)) {
+            Container::from_bitset(self.to_bitset()).unwrap()
+        } else {
+            self
+        }
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = u16> + '_> {
+        match self {
+            Container::Array(values) => Box::new(values.iter().copied()),
+            Container::Bitmap(bits, _) => Box::new(bits.iter_ones().map(|x| x as u16)),
+            Container::Run(runs) => Box::new(runs.iter().flat_map(|&(start, last)| start..=last)),
+        }
+    }
+
+    // 小于 x 的元素个数
+    fn rank(&self, x: u16) -> usize {
+        match self {
+            Container::Array(values) => values.partition_point(|&v| v < x),
+            Container::Bitmap(bits, _) => bits.rank(x as usize),
+            Container::Run(runs) => runs
+                .iter()
+                .take_while(|&&(start, _)| start < x)
+                .map(|&(start, last)| (last.min(x - 1) - start) as usize + 1)
+                .sum(),
+        }
+    }
+
+    // 第 k 小的元素，要求 k < len
+    fn select(&self, k: usize) -> u16 {
+        match self {
+            Container::Array(values) => values[k],
+            Container::Bitmap(bits, _) => bits.select(k).unwrap() as u16,
+            Container::Run(runs) => {
+                let mut k = k;
+                for &(start, last) in runs {
+                    let len = (last - start) as usize + 1;
+                    if k < len {
+                        return start + k as u16;
+                    }
+                    k -= len;
+                }
+                unreachable!("select out of range")
+            }
+        }
+    }
+
+    fn combine(&self, other: &Container, op: SetOp) -> Option<Container> {
+        if let (Container::Array(a), Container::Array(b)) = (self, other) {
+            return Container::from_sorted(merge_sorted(a, b, op));
+        }
+        if let (SetOp::Intersection, Container::Array(a), _)
+        | (SetOp::Intersection, _, Container::Array(a)) = (op, self, other)
+        {
+            // 数组与其它容器求交：逐个检查数组中的元素
+            let other = if matches!(self, Container::Array(_)) {
+                other
+            } else {
+                self
+            };
+            return Container::from_sorted(
+                a.iter().copied().filter(|&x| other.contains(x)).collect(),
+            );
+        }
+        let mut bits = self.to_bitset();
+        op.apply(&mut bits, &other.to_bitset());
+        Container::from_bitset(bits)
+    }
+
+    fn write(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Container::Array(values) => {
+                bytes.push(ARRAY_TAG);
+                bytes.extend_from_slice(&(values.len() as u32).to_le_bytes());
+                for x in values {
+                    bytes.extend_from_slice(&x.to_le_bytes());
+                }
+            }
+            Container::Bitmap(bits, _) => {
+                bytes.push(BITMAP_TAG);
+                bytes.extend_from_slice(&(BITMAP_WORDS as u32).to_le_bytes());
+                let words = bits.words();
+                for i in 0..BITMAP_WORDS {
+                    let word = words.get(i).copied().unwrap_or(0);
+                    bytes.extend_from_slice(&word.to_le_bytes());
+                }
+            }
+            Container::Run(runs) => {
+                bytes.push(RUN_TAG);
+                bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+                for (start, last) in runs {
+                    bytes.extend_from_slice(&start.to_le_bytes());
+                    bytes.extend_from_slice(&last.to_le_bytes());
+                }
+            }
+        }
+    }
+
+    // 读取并校验一个容器：数组严格递增，区间有序且互不相邻，容器非空
+    fn read(reader: &mut ByteReader) -> Result<Container, MalformedBitmap> {
+        let tag = reader.u8()?;
+        let len = reader.u32()? as usize;
+        let container = match tag {
+            ARRAY_TAG if (1..=ARRAY_MAX).contains(&len) => {
+                let values = (0..len)
+                    .map(|_| reader.u16())
+                    .collect::<Result<Vec<_>, _>>()?;
+                if values.windows(2).any(|w| w[0] >= w[1]) {
+                    return Err(MalformedBitmap);
+                }
+                Container::Array(values)
+            }
+            BITMAP_TAG if len == BITMAP_WORDS => {
+                let words = (0..len).map(|_| reader.u64()).collect::<Result<_, _>>()?;
+                let bits = BitSet::from_words(words);
+                let n = bits.len();
+                if n <= ARRAY_MAX {
+                    return Err(MalformedBitmap);
+                }
+                Container::Bitmap(bits, n)
+            }
+            RUN_TAG if len > 0 && len * 4 <= BITMAP_BYTES => {
+                let runs = (0..len)
+                    .map(|_| Ok((reader.u16()?, reader.u16()?)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let sorted = runs
+                    .windows(2)
+                    .all(|w| w[0].1 < u16::MAX && w[0].1 + 1 < w[1].0);
+                if !sorted || runs.iter().any(|&(start, last)| start > last) {
+                    return Err(MalformedBitmap);
+                }
+                Container::Run(runs)
+            }
+            _ => return Err(MalformedBitmap),
+        };
+        Ok(container)
+    }
+}
+
+fn split(x: u32) -> (u16, u16) {
+    ((x >> 16) as u16, x as u16)
+}
+
+fn join(high: u16, low: u16) -> u32 {
+    (high as u32) << 16 | low as u32
+}
+
+#[derive(Clone, Default)]
+pub struct RoaringBitmap {
+    // 按高 16 位排序，容器都非空
+    containers: Vec<(u16, Container)>,
+}
+
+impl RoaringBitmap {
+    pub fn new() -> Self {
+        RoaringBitmap {
+            containers: Vec::new(),
+        }
+    }
+
+    fn find(&self, high: u16) -> Result<usize, usize> {
+        self.containers.binary_search_by_key(&high, |&(key, _)| key)
+    }
+
+    /// 插入元素，原来不存在时返回 true
+    pub fn insert(&mut self, x: u32) -> bool {
+        let (high, low) = split(x);
+        match self.find(high) {
+            Ok(i) => self.containers[i].1.insert(low),
+            Err(i) => {
+                self.containers
+                    .insert(i, (high, Container::Array(vec![low])));
+                true
+            }
+        }
+    }
+
+    /// 删除元素，原来存在时返回 true
+    pub fn remove(&mut self, x: u32) -> bool {
+        let (high, low) = split(x);
+        let Ok(i) = self.find(high) else {
+            return false;
+        };
+        let removed = self.containers[i].1.remove(low);
+        if self.containers[i].1.len() == 0 {
+            self.containers.remove(i);
+        }
+        removed
+    }
+
+    pub fn contains(&self, x: u32) -> bool {
+        let (high, low) = split(x);
+        self.find(high)
+            .is_ok_and(|i| self.containers[i].1.contains(low))
+    }
+
+    /// 元素个数，O(容器个数)
+    pub fn len(&self) -> u64 {
+        self.containers.iter().map(|(_, c)| c.len() as u64).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.containers.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.containers.clear();
+    }
+
+    pub fn min(&self) -> Option<u32> {
+        let (high, container) = self.containers.first()?;
+        Some(join(*high, container.select(0)))
+    }
+
+    pub fn max(&self) -> Option<u32> {
+        let (high, container) = self.containers.last()?;
+        Some(join(*high, container.select(container.len() - 1)))
+    }
+
+    /// 从小到大遍历所有元素
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.containers
+            .iter()
+            .flat_map(|(high, container)| container.iter().map(move |low| join(*high, low)))
+    }
+
+    /// 集合中小于 x 的元素个数
+    pub fn rank(&self, x: u32) -> u64 {
+        let (high, low) = split(x);
+        let mut rank = 0;
+        for (key, container) in &self.containers {
+            if *key < high {
+                rank += container.len() as u64;
+            } else {
+                if *key == high {
+                    rank += container.rank(low) as u64;
+                }
+                break;
+            }
+        }
+        rank
+    }
+
+    /// 第 k 小的元素（从 0 开始），k 不小于元素个数时返回 None
+    pub fn select(&self, k: u64) -> Option<u32> {
+        let mut k = k;
+        for (high, container) in &self.containers {
+            let len = container.len() as u64;
+            if k < len {
+                return Some(join(*high, container.select(k as usize)));
+            }
+            k -= len;
+        }
+        None
+    }
+
+    /// 把每个容器转换成序列化后最小的表示，大段连续的值会变成区间容器
+    pub fn run_optimize(&mut self) {
+        for (_, container) in &mut self.containers {
+            let old = std::mem::replace(container, Container::Array(Vec::new()));
+            *container = old.optimize();
+        }
+    }
+
+    fn combine(&self, other: &RoaringBitmap, op: SetOp) -> RoaringBitmap {
+        let (left, _, right) = op.keeps();
+        let (a, b) = (&self.containers, &other.containers);
+        let mut containers = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            let ka = a.get(i).map(|&(key, _)| key);
+            let kb = b.get(j).map(|&(key, _)| key);
+            match (ka, kb) {
+                (Some(x), Some(y)) if x == y => {
+                    if let Some(c) = a[i].1.combine(&b[j].1, op) {
+                        containers.push((x, c));
+                    }
+                    i += 1;
+                    j += 1;
+                }
+                (Some(x), y) if y.is_none_or(|y| x < y) => {
+                    if left {
+                        containers.push(a[i].clone());
+                    }
+                    i += 1;
+                }
+                _ => {
+                    if right {
+                        containers.push(b[j].clone());
+                    }
+                    j += 1;
+                }
+            }
+        }
+        RoaringBitmap { containers }
+    }
+
+    pub fn union(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, SetOp::Union)
+    }
+
+    pub fn intersection(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, SetOp::Intersection)
+    }
+
+    pub fn difference(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, SetOp::Difference)
+    }
+
+    pub fn symmetric_difference(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.combine(other, SetOp::SymmetricDifference)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.containers.len() as u32).to_le_bytes());
+        for (high, container) in &self.containers {
+            bytes.extend_from_slice(&high.to_le_bytes());
+            container.write(&mut bytes);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MalformedBitmap> {
+        let mut reader = ByteReader { bytes };
+        let count = reader.u32()?;
+        let mut containers: Vec<(u16, Container)> = Vec::new();
+        for _ in 0..count {
+            let high = reader.u16()?;
+            if containers.last().is_some_and(|&(prev, _)| prev >= high) {
+                return Err(MalformedBitmap);
+            }
+            containers.push((high, Container::read(&mut reader)?));
+        }
+        reader.finish()?;
+        Ok(RoaringBitmap { containers })
+    }
+}
+
+// 同一个集合可能有不同的容器表示，按元素比较
+impl PartialEq for RoaringBitmap {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for RoaringBitmap {}
+
+impl fmt::Debug for RoaringBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl Extend<u32> for RoaringBitmap {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+        for x in iter {
+            self.insert(x);
+        }
+    }
+}
+
+impl FromIterator<u32> for RoaringBitmap {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut bitmap = RoaringBitmap::new();
+        bitmap.extend(iter);
+        bitmap
+    }
+}
+
+macro_rules! impl_set_operator {
+    ($trait:ident, $method:ident, $op:ident) => {
+        impl $trait<&RoaringBitmap> for &RoaringBitmap {
+            type Output = RoaringBitmap;
+
+            fn $method(self, other: &RoaringBitmap) -> RoaringBitmap {
+                self.$op(other)
+            }
+        }
+    };
+}
+
+impl_set_operator!(BitOr, bitor, union);
+impl_set_operator!(BitAnd, bitand, intersection);
+impl_set_operator!(Sub, sub, difference);
+impl_set_operator!(BitXor, bitxor, symmetric_difference);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeSet;
+
+    fn kinds(bitmap: &RoaringBitmap) -> Vec<u8> {
+        bitmap
+            .containers
+            .iter()
+            .map(|(_, c)| match c {
+                Container::Array(_) => ARRAY_TAG,
+                Container::Bitmap(..) => BITMAP_TAG,
+                Container::Run(_) => RUN_TAG,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_basic_operations() {
+        let mut bitmap = RoaringBitmap::new();
+        assert!(bitmap.insert(5));
+        assert!(!bitmap.insert(5));
+        assert!(bitmap.insert(1 << 20));
+        assert!(bitmap.insert(u32::MAX));
+        assert_eq!(bitmap.len(), 3);
+        assert!(bitmap.contains(1 << 20) && !bitmap.contains(6));
+        assert_eq!(bitmap.min(), Some(5));
+        assert_eq!(bitmap.max(), Some(u32::MAX));
+        assert!(bitmap.remove(1 << 20));
+        assert!(!bitmap.remove(1 << 20));
+        assert_eq!(bitmap.iter().collect::<Vec<_>>(), vec![5, u32::MAX]);
+        assert_eq!(bitmap.containers.len(), 2);
+        bitmap.clear();
+        assert!(bitmap.is_empty() && bitmap.min().is_none());
+    }
+
+    #[test]
+    fn test_container_conversions() {
+        let mut bitmap: RoaringBitmap = (0..ARRAY_MAX as u32).map(|x| 2 * x).collect();
+        assert_eq!(kinds(&bitmap), vec![ARRAY_TAG]);
+        bitmap.insert(1);
+        assert_eq!(kinds(&bitmap), vec![BITMAP_TAG]);
+        bitmap.remove(0);
+        assert_eq!(kinds(&bitmap), vec![ARRAY_TAG]);
+
+        // 大段连续的值压缩成区间
+        let mut dense: RoaringBitmap = (1000..60_000).chain(70_000..70_010).collect();
+        assert_eq!(kinds(&dense), vec![BITMAP_TAG, ARRAY_TAG]);
+        let before = dense.to_bytes().len();
+        dense.run_optimize();
+        assert_eq!(kinds(&dense), vec![RUN_TAG, RUN_TAG]);
+        assert!(dense.to_bytes().len() < before / 100);
+        assert_eq!(dense.len(), 59_010);
+
+        // 在区间容器上修改
+        assert!(dense.remove(30_000));
+        assert!(dense.insert(999));
+        assert!(dense.insert(30_000));
+        assert!(!dense.insert(30_000));
+        assert!(dense.remove(70_005));
+        assert_eq!(dense.len(), 59_010);
+        assert!(!dense.contains(70_005) && dense.contains(999));
+        assert_eq!(kinds(&dense), vec![RUN_TAG, RUN_TAG]);
+
+        // 零散的值用区间表示反而更大，run_optimize 保持原样
+        let mut sparse: RoaringBitmap = (0..100).map(|x| x * 7).collect();
+        sparse.run_optimize();
+        assert_eq!(kinds(&sparse), vec![ARRAY_TAG]);
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let mut bitmap: RoaringBitmap = [3, 10, 70_000, 70_001, 1 << 30].into_iter().collect();
+        bitmap.extend(200_000..210_000);
+        for optimized in [false, true] {
+            if optimized {
+                bitmap.run_optimize();
+            }
+            assert_eq!(bitmap.rank(0), 0);
+            assert_eq!(bitmap.rank(10), 1);
+            assert_eq!(bitmap.rank(11), 2);
+            assert_eq!(bitmap.rank(70_001), 3);
+            assert_eq!(bitmap.rank(205_000), 4 + 5_000);
+            assert_eq!(bitmap.rank(u32::MAX), 10_005);
+            for (k, x) in bitmap.iter().enumerate().step_by(97) {
+                assert_eq!(bitmap.select(k as u64), Some(x));
+                assert_eq!(bitmap.rank(x), k as u64);
+            }
+            assert_eq!(bitmap.select(10_004), Some(1 << 30));
+            assert_eq!(bitmap.select(10_005), None);
+        }
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut bitmap: RoaringBitmap = (0..10).chain(100_000..110_000).chain([1 << 31]).collect();
+        bitmap.insert(200_000);
+        let copy = bitmap.clone();
+        bitmap.run_optimize();
+        for b in [&copy, &bitmap] {
+            let bytes = b.to_bytes();
+            assert_eq!(RoaringBitmap::from_bytes(&bytes).as_ref(), Ok(b));
+        }
+        assert_eq!(RoaringBitmap::from_bytes(&[]), Err(MalformedBitmap));
+        let bytes = bitmap.to_bytes();
+        assert_eq!(
+            RoaringBitmap::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(MalformedBitmap)
+        );
+        // 数组容器中的元素不是递增的
+        let unsorted = [1, 0, 0, 0, 0, 0, ARRAY_TAG, 2, 0, 0, 0, 5, 0, 3, 0];
+        assert_eq!(RoaringBitmap::from_bytes(&unsorted), Err(MalformedBitmap));
+        let sorted = [1, 0, 0, 0, 0, 0, ARRAY_TAG, 2, 0, 0, 0, 3, 0, 5, 0];
+        assert_eq!(
+            RoaringBitmap::from_bytes(&sorted)
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>(),
+            vec![3, 5]
+        );
+    }
+
+    #[test]
+    fn test_against_btree_set() {
+        let mut rng = StdRng::seed_from_u64(460);
+        // 混合稀疏的值、稠密的块和连续的区间，覆盖所有容器组合
+        let random_bitmap = |rng: &mut StdRng| -> (RoaringBitmap, BTreeSet<u32>) {
+            let mut set = BTreeSet::new();
+            for _ in 0..rng.gen_range(0..200) {
+                set.insert(rng.gen_range(0..300_000));
+            }
+            for _ in 0..rng.gen_range(0..6_000) {
+                set.insert(rng.gen_range(65_536..80_000));
+            }
+            let start = rng.gen_range(0..200_000);
+            set.extend(start..start + rng.gen_range(0..20_000));
+            let mut bitmap: RoaringBitmap = set.iter().copied().collect();
+            if rng.gen_bool(0.5) {
+                bitmap.run_optimize();
+            }
+            (bitmap, set)
+        };
+        for _ in 0..20 {
+            let (a, sa) = random_bitmap(&mut rng);
+            let (b, sb) = random_bitmap(&mut rng);
+            assert_eq!(a.len(), sa.len() as u64);
+            assert!(a.iter().eq(sa.iter().copied()));
+            assert!((&a | &b).iter().eq(sa.union(&sb).copied()));
+            assert!((&a & &b).iter().eq(sa.intersection(&sb).copied()));
+            assert!((&a - &b).iter().eq(sa.difference(&sb).copied()));
+            assert!((&a ^ &b).iter().eq(sa.symmetric_difference(&sb).copied()));
+            for _ in 0..20 {
+                let x = rng.gen_range(0..320_000);
+                assert_eq!(a.contains(x), sa.contains(&x));
+                assert_eq!(a.rank(x), sa.range(..x).count() as u64);
+            }
+            assert_eq!(RoaringBitmap::from_bytes(&a.to_bytes()), Ok(a));
+        }
+    }
+
+    #[test]
+    fn test_random_mutations() {
+        let mut rng = StdRng::seed_from_u64(4600);
+        let mut bitmap = RoaringBitmap::new();
+        let mut set = BTreeSet::new();
+        for step in 0..30_000 {
+            let x = rng.gen_range(0..140_000);
+            if rng.gen_bool(0.7) {
+                assert_eq!(bitmap.insert(x), set.insert(x));
+            } else {
+                assert_eq!(bitmap.remove(x), set.remove(&x));
+            }
+            if step % 5_000 == 0 {
+                bitmap.run_optimize();
+            }
+        }
+        assert_eq!(bitmap.len(), set.len() as u64);
+        assert!(bitmap.iter().eq(set.iter().copied()));
+    }
+}
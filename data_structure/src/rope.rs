@@ -0,0 +1,849 @@
+/*
+    绳索（Rope）：适合频繁在中间插入、删除的大文本
+
+    文本被切成不超过 MAX_CHUNK 字节的小块，保存在一棵 AVL 树的叶子中。
+    每个节点缓存子树的字节数、字符数和换行符个数，因此按字符、字节或者行定位都是 O(log n)。
+
+    - insert：短字符串直接插入所在的叶子，叶子过大时一分为二，沿路径做 AVL 旋转；
+      长字符串先建成一棵平衡的子树，再用 split + join 拼接。
+    - remove：递归删除区间，被完全覆盖的子树整个丢弃，再用 join 把左右两部分合并。
+      join 按高度差沿着较高一侧的边缘下降，合并后做一次旋转即可恢复平衡。
+    - slice 返回借用原文本的 RopeSlice，分块迭代器按顺序给出每个叶子（的一部分）。
+
+    编辑接口使用字符下标，所有切分点都在字符边界上，不会破坏 UTF-8 编码。
+*/
+use crate::segment_tree::to_half_open;
+use std::fmt;
+use std::ops::{Add, RangeBounds};
+
+#[cfg(not(test))]
+const MAX_CHUNK: usize = 1024;
+// 测试时使用很小的块，让较短的文本也能形成多层的树
+#[cfg(test)]
+const MAX_CHUNK: usize = 16;
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+struct TextInfo {
+    bytes: usize,
+    chars: usize,
+    newlines: usize,
+}
+
+impl TextInfo {
+    fn of(text: &str) -> Self {
+        TextInfo {
+            bytes: text.len(),
+            chars: text.chars().count(),
+            newlines: text.bytes().filter(|&b| b == b'\n').count(),
+        }
+    }
+}
+
+impl Add for TextInfo {
+    type Output = TextInfo;
+
+    fn add(self, other: TextInfo) -> TextInfo {
+        TextInfo {
+            bytes: self.bytes + other.bytes,
+            chars: self.chars + other.chars,
+            newlines: self.newlines + other.newlines,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Node {
+    // 叶子中的文本非空，且不超过 MAX_CHUNK 字节
+    Leaf {
+        text: String,
+        info: TextInfo,
+    },
+    Internal {
+        left: Box<Node>,
+        right: Box<Node>,
+        info: TextInfo,
+        height: usize,
+    },
+}
+
+// 不超过 i 的最大字符边界
+fn floor_char_boundary(text: &str, mut i: usize) -> usize {
+    while !text.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+// 第 c 个字符在 text 中的字节偏移
+fn char_to_byte_in(text: &str, c: usize) -> usize {
+    text.char_indices().nth(c).map_or(text.len(), |(i, _)| i)
+}
+
+fn leaf_node(text: String) -> Node {
+    let info = TextInfo::of(&text);
+    Node::Leaf { text, info }
+}
+
+fn leaf(text: String) -> Box<Node> {
+    Box::new(leaf_node(text))
+}
+
+fn internal(left: Box<Node>, right: Box<Node>) -> Box<Node> {
+    Box::new(Node::Internal {
+        info: left.info() + right.info(),
+        height: left.height().max(right.height()) + 1,
+        left,
+        right,
+    })
+}
+
+// 把文本切成块，自底向上建一棵平衡的树
+fn build(text: &str) -> Option<Box<Node>> {
+    let mut leaves = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let end = floor_char_boundary(text, (start + MAX_CHUNK).min(text.len()));
+        leaves.push(leaf_node(text[start..end].to_string()));
+        start = end;
+    }
+    build_from_leaves(leaves)
+}
+
+fn build_from_leaves(mut nodes: Vec<Node>) -> Option<Box<Node>> {
+    if nodes.len() <= 1 {
+        return nodes.pop().map(Box::new);
+    }
+    let right = nodes.split_off(nodes.len() / 2);
+    Some(internal(
+        build_from_leaves(nodes)?,
+        build_from_leaves(right)?,
+    ))
+}
+
+// 以 left、right 为左右子树建立节点，高度差为 2 时做单旋或者双旋
+fn balance(left: Box<Node>, right: Box<Node>) -> Box<Node> {
+    if left.height() > right.height() + 1 {
+        let (ll, lr) = left.into_children();
+        if ll.height() >= lr.height() {
+            internal(ll, internal(lr, right))
+        } else {
+            let (lrl, lrr) = lr.into_children();
+            internal(internal(ll, lrl), internal(lrr, right))
+        }
+    } else if right.height() > left.height() + 1 {
+        let (rl, rr) = right.into_children();
+        if rr.height() >= rl.height() {
+            internal(internal(left, rl), rr)
+        } else {
+            let (rll, rlr) = rl.into_children();
+            internal(internal(left, rll), internal(rlr, rr))
+        }
+    } else {
+        internal(left, right)
+    }
+}
+
+// 拼接两棵树（left 的文本在前），沿较高一侧的边缘下降到高度相近处再合并
+fn join(left: Box<Node>, right: Box<Node>) -> Box<Node> {
+    let (hl, hr) = (left.height(), right.height());
+    if hl > hr + 1 {
+        let (ll, lr) = left.into_children();
+        balance(ll, join(lr, right))
+    } else if hr > hl + 1 {
+        let (rl, rr) = right.into_children();
+        balance(join(left, rl), rr)
+    } else if let (Node::Leaf { text: a, .. }, Node::Leaf { text: b, .. }) = (&*left, &*right) {
+        // 两个相邻的小叶子合并成一个，避免删除后留下大量碎片
+        if a.len() + b.len() <= MAX_CHUNK {
+            leaf(format!("{}{}", a, b))
+        } else {
+            internal(left, right)
+        }
+    } else {
+        internal(left, right)
+    }
+}
+
+fn join_opt(left: Option<Box<Node>>, right: Option<Box<Node>>) -> Option<Box<Node>> {
+    match (left, right) {
+        (Some(l), Some(r)) => Some(join(l, r)),
+        (l, None) => l,
+        (None, r) => r,
+    }
+}
+
+impl Node {
+    fn info(&self) -> TextInfo {
+        match self {
+            Node::Leaf { info, .. } | Node::Internal { info, .. } => *info,
+        }
+    }
+
+    fn height(&self) -> usize {
+        match self {
+            Node::Leaf { .. } => 0,
+            Node::Internal { height, .. } => *height,
+        }
+    }
+
+    fn into_children(self) -> (Box<Node>, Box<Node>) {
+        match self {
+            Node::Internal { left, right, .. } => (left, right),
+            Node::Leaf { .. } => unreachable!("a leaf has no children"),
+        }
+    }
+
+    // 把第 at 个字符之前与之后的文本分成两棵树
+    fn split(self: Box<Self>, at: usize) -> (Option<Box<Node>>, Option<Box<Node>>) {
+        if at == 0 {
+            return (None, Some(self));
+        }
+        if at == self.info().chars {
+            return (Some(self), None);
+        }
+        match *self {
+            Node::Leaf { mut text, .. } => {
+                let tail = text.split_off(char_to_byte_in(&text, at));
+                (Some(leaf(text)), Some(leaf(tail)))
+            }
+            Node::Internal { left, right, .. } => {
+                let left_chars = left.info().chars;
+                if at <= left_chars {
+                    let (a, b) = left.split(at);
+                    (a, join_opt(b, Some(right)))
+                } else {
+                    let (a, b) = right.split(at - left_chars);
+                    (join_opt(Some(left), a), b)
+                }
+            }
+        }
+    }
+
+    // 插入不超过 MAX_CHUNK / 2 字节的文本，返回的树高度最多增加 1
+    fn insert_small(self, at: usize, s: &str) -> Box<Node> {
+        match self {
+            Node::Leaf { mut text, .. } => {
+                text.insert_str(char_to_byte_in(&text, at), s);
+                if text.len() <= MAX_CHUNK {
+                    return leaf(text);
+                }
+                let tail = text.split_off(floor_char_boundary(&text, text.len() / 2));
+                internal(leaf(text), leaf(tail))
+            }
+            Node::Internal { left, right, .. } => {
+                let left_chars = left.info().chars;
+                if at <= left_chars {
+                    balance(left.insert_small(at, s), right)
+                } else {
+                    balance(left, right.insert_small(at - left_chars, s))
+                }
+            }
+        }
+    }
+
+    // 删除字符区间 [start, end)，整棵树被删空时返回 None
+    fn remove(self, start: usize, end: usize) -> Option<Box<Node>> {
+        if start == 0 && end == self.info().chars {
+            return None;
+        }
+        match self {
+            Node::Leaf { mut text, .. } => {
+                let (a, b) = (char_to_byte_in(&text, start), char_to_byte_in(&text, end));
+                text.replace_range(a..b, "");
+                Some(leaf(text))
+            }
+            Node::Internal { left, right, .. } => {
+                let left_chars = left.info().chars;
+                let left = if start < left_chars {
+                    left.remove(start, end.min(left_chars))
+                } else {
+                    Some(left)
+                };
+                let right = if end > left_chars {
+                    right.remove(start.saturating_sub(left_chars), end - left_chars)
+                } else {
+                    Some(right)
+                };
+                join_opt(left, right)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Rope {
+    root: Option<Box<Node>>,
+}
+
+impl Rope {
+    pub fn new() -> Self {
+        Rope { root: None }
+    }
+
+    fn info(&self) -> TextInfo {
+        self.root
+            .as_ref()
+            .map_or(TextInfo::default(), |root| root.info())
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.info().bytes
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.info().chars
+    }
+
+    /// 行数等于换行符个数加一，空文本也算一行
+    pub fn len_lines(&self) -> usize {
+        self.info().newlines + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// 在第 at 个字符之前插入文本，O(log n + s.len())
+    pub fn insert(&mut self, at: usize, s: &str) {
+        let len = self.len_chars();
+        assert!(
+            at <= len,
+            "char index {} out of bounds for length {}",
+            at,
+            len
+        );
+        if s.is_empty() {
+            return;
+        }
+        self.root = match self.root.take() {
+            Some(root) if s.len() <= MAX_CHUNK / 2 => Some(root.insert_small(at, s)),
+            root => {
+                let (left, right) = match root {
+                    Some(root) => root.split(at),
+                    None => (None, None),
+                };
+                join_opt(join_opt(left, build(s)), right)
+            }
+        };
+    }
+
+    /// 删除一段字符，O(log n) 加上被删除部分的释放开销
+    pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = to_half_open(range, self.len_chars());
+        if start < end {
+            self.root = self.root.take().and_then(|root| root.remove(start, end));
+        }
+    }
+
+    /// 在末尾追加文本
+    pub fn push_str(&mut self, s: &str) {
+        self.insert(self.len_chars(), s);
+    }
+
+    /// 借用一段字符区间，O(log n)
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> RopeSlice<'_> {
+        let (start, end) = to_half_open(range, self.len_chars());
+        RopeSlice {
+            rope: self,
+            start_byte: self.char_to_byte(start),
+            end_byte: self.char_to_byte(end),
+            chars: end - start,
+        }
+    }
+
+    /// 第 i 行（包含行尾的换行符）
+    pub fn line(&self, i: usize) -> RopeSlice<'_> {
+        let len = self.len_lines();
+        assert!(i < len, "line index {} out of bounds for {} lines", i, len);
+        self.slice(self.line_to_char(i)..self.line_to_char(i + 1))
+    }
+
+    /// 第 i 个字符
+    pub fn char(&self, i: usize) -> char {
+        let len = self.len_chars();
+        assert!(i < len, "char index {} out of bounds for length {}", i, len);
+        let mut node = self.root.as_deref().unwrap();
+        let mut i = i;
+        loop {
+            match node {
+                Node::Leaf { text, .. } => return text.chars().nth(i).unwrap(),
+                Node::Internal { left, right, .. } => {
+                    if i < left.info().chars {
+                        node = left;
+                    } else {
+                        i -= left.info().chars;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 第 c 个字符的字节偏移，c 可以等于字符数
+    pub fn char_to_byte(&self, c: usize) -> usize {
+        let len = self.len_chars();
+        assert!(
+            c <= len,
+            "char index {} out of bounds for length {}",
+            c,
+            len
+        );
+        let mut bytes = 0;
+        let mut c = c;
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            match current {
+                Node::Leaf { text, .. } => return bytes + char_to_byte_in(text, c),
+                Node::Internal { left, right, .. } => {
+                    let info = left.info();
+                    if c < info.chars {
+                        node = Some(left);
+                    } else {
+                        c -= info.chars;
+                        bytes += info.bytes;
+                        node = Some(right);
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    /// 字节偏移 b 所在字符的下标；b 落在多字节字符中间时返回该字符，b 可以等于字节数
+    pub fn byte_to_char(&self, b: usize) -> usize {
+        let len = self.len_bytes();
+        assert!(
+            b <= len,
+            "byte index {} out of bounds for length {}",
+            b,
+            len
+        );
+        let mut chars = 0;
+        let mut b = b;
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            match current {
+                Node::Leaf { text, .. } => {
+                    return chars
+                        + text
+                            .char_indices()
+                            .take_while(|&(i, ch)| i + ch.len_utf8() <= b)
+                            .count();
+                }
+                Node::Internal { left, right, .. } => {
+                    let info = left.info();
+                    if b < info.bytes {
+                        node = Some(left);
+                    } else {
+                        b -= info.bytes;
+                        chars += info.chars;
+                        node = Some(right);
+                    }
+                }
+            }
+        }
+        chars
+    }
+
+    /// 第 c 个字符所在的行号
+    pub fn char_to_line(&self, c: usize) -> usize {
+        let len = self.len_chars();
+        assert!(
+            c <= len,
+            "char index {} out of bounds for length {}",
+            c,
+            len
+        );
+        let mut lines = 0;
+        let mut c = c;
+        let mut node = self.root.as_deref();
+        while let Some(current) = node {
+            match current {
+                Node::Leaf { text, .. } => {
+                    return lines + text.chars().take(c).filter(|&ch| ch == '\n').count();
+                }
+                Node::Internal { left, right, .. } => {
+                    let info = left.info();
+                    if c < info.chars {
+                        node = Some(left);
+                    } else {
+                        c -= info.chars;
+                        lines += info.newlines;
+                        node = Some(right);
+                    }
+                }
+            }
+        }
+        lines
+    }
+
+    /// 第 line 行第一个字符的下标；line 等于行数时返回字符数
+    pub fn line_to_char(&self, line: usize) -> usize {
+        let len = self.len_lines();
+        assert!(
+            line <= len,
+            "line index {} out of bounds for {} lines",
+            line,
+            len
+        );
+        if line == len {
+            return self.len_chars();
+        }
+        // 找到第 line 个换行符，答案是它后面的位置
+        let mut newlines = line;
+        let mut chars = 0;
+        let mut node = self.root.as_deref();
+        while let (Some(current), true) = (node, newlines > 0) {
+            match current {
+                Node::Leaf { text, .. } => {
+                    let (i, _) = text
+                        .chars()
+                        .enumerate()
+                        .filter(|&(_, ch)| ch == '\n')
+                        .nth(newlines - 1)
+                        .unwrap();
+                    return chars + i + 1;
+                }
+                Node::Internal { left, right, .. } => {
+                    let info = left.info();
+                    if newlines <= info.newlines {
+                        node = Some(left);
+                    } else {
+                        newlines -= info.newlines;
+                        chars += info.chars;
+                        node = Some(right);
+                    }
+                }
+            }
+        }
+        chars
+    }
+
+    pub fn byte_to_line(&self, b: usize) -> usize {
+        self.char_to_line(self.byte_to_char(b))
+    }
+
+    pub fn line_to_byte(&self, line: usize) -> usize {
+        self.char_to_byte(self.line_to_char(line))
+    }
+
+    /// 按顺序给出所有文本块
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks::new(self.root.as_deref(), 0, self.len_bytes())
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.chunks().flat_map(str::bytes)
+    }
+}
+
+/// Rope 中一段字符区间的只读视图
+#[derive(Clone, Copy)]
+pub struct RopeSlice<'a> {
+    rope: &'a Rope,
+    start_byte: usize,
+    end_byte: usize,
+    chars: usize,
+}
+
+impl<'a> RopeSlice<'a> {
+    pub fn len_bytes(&self) -> usize {
+        self.end_byte - self.start_byte
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.chars
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars == 0
+    }
+
+    pub fn chunks(&self) -> Chunks<'a> {
+        Chunks::new(self.rope.root.as_deref(), self.start_byte, self.len_bytes())
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + 'a {
+        self.chunks().flat_map(str::chars)
+    }
+}
+
+/// 文本块迭代器：从某个字节偏移开始，依次给出叶子中的文本，总共 remaining 个字节
+pub struct Chunks<'a> {
+    stack: Vec<&'a Node>,
+    skip: usize,
+    remaining: usize,
+}
+
+impl<'a> Chunks<'a> {
+    fn new(root: Option<&'a Node>, start: usize, len: usize) -> Self {
+        let mut stack = Vec::new();
+        let mut skip = start;
+        let mut node = root;
+        // 下降到包含起点的叶子，沿途记下之后要访问的右子树
+        while let Some(current) = node {
+            match current {
+                Node::Leaf { .. } => {
+                    stack.push(current);
+                    node = None;
+                }
+                Node::Internal { left, right, .. } => {
+                    let left_bytes = left.info().bytes;
+                    if skip < left_bytes {
+                        stack.push(right);
+                        node = Some(left);
+                    } else {
+                        skip -= left_bytes;
+                        node = Some(right);
+                    }
+                }
+            }
+        }
+        Chunks {
+            stack,
+            skip,
+            remaining: len,
+        }
+    }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.remaining == 0 {
+            return None;
+        }
+        while let Some(node) = self.stack.pop() {
+            match node {
+                Node::Leaf { text, .. } => {
+                    let text = &text[self.skip..];
+                    let take = text.len().min(self.remaining);
+                    self.skip = 0;
+                    self.remaining -= take;
+                    return Some(&text[..take]);
+                }
+                Node::Internal { left, right, .. } => {
+                    self.stack.push(right);
+                    self.stack.push(left);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        Rope { root: build(text) }
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Self {
+        Rope::from(text.as_str())
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl fmt::Display for RopeSlice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl fmt::Debug for RopeSlice<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Self) -> bool {
+        self.len_bytes() == other.len_bytes() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        self.len_bytes() == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<&str> for RopeSlice<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.len_bytes() == other.len() && self.chunks().flat_map(str::bytes).eq(other.bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    // 检查缓存的统计信息、高度和 AVL 平衡条件，返回子树的高度
+    fn check(node: &Node) -> usize {
+        match node {
+            Node::Leaf { text, info } => {
+                assert!(!text.is_empty() && text.len() <= MAX_CHUNK);
+                assert_eq!(*info, TextInfo::of(text));
+                0
+            }
+            Node::Internal {
+                left,
+                right,
+                info,
+                height,
+            } => {
+                let (hl, hr) = (check(left), check(right));
+                assert!(hl.abs_diff(hr) <= 1, "unbalanced: {} vs {}", hl, hr);
+                assert_eq!(*height, hl.max(hr) + 1);
+                assert_eq!(*info, left.info() + right.info());
+                *height
+            }
+        }
+    }
+
+    fn check_rope(rope: &Rope, expected: &str) {
+        if let Some(root) = &rope.root {
+            check(root);
+        }
+        assert_eq!(rope.to_string(), expected);
+        assert_eq!(rope.len_bytes(), expected.len());
+        assert_eq!(rope.len_chars(), expected.chars().count());
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let mut rope = Rope::new();
+        assert!(rope.is_empty());
+        rope.insert(0, "world");
+        rope.insert(0, "hello ");
+        rope.push_str("!");
+        check_rope(&rope, "hello world!");
+        rope.remove(5..11);
+        check_rope(&rope, "hello!");
+        rope.insert(5, ", 世界");
+        check_rope(&rope, "hello, 世界!");
+        rope.remove(..);
+        assert!(rope.is_empty());
+
+        let long = "abcdefghij".repeat(30);
+        let mut rope = Rope::from(long.as_str());
+        check_rope(&rope, &long);
+        rope.insert(150, &long);
+        let expected = format!("{}{}{}", &long[..150], long, &long[150..]);
+        check_rope(&rope, &expected);
+        rope.remove(10..=589);
+        check_rope(&rope, &format!("{}{}", &expected[..10], &expected[590..]));
+    }
+
+    #[test]
+    fn test_slice_and_chunks() {
+        let text = "αβγ😀abc\n".repeat(20);
+        let rope = Rope::from(text.as_str());
+        assert!(rope.chunks().count() > 1);
+        assert_eq!(rope.chunks().collect::<String>(), text);
+
+        let chars: Vec<char> = text.chars().collect();
+        for (start, end) in [(0, 0), (1, 5), (3, 100), (0, chars.len()), (57, 150)] {
+            let slice = rope.slice(start..end);
+            let expected: String = chars[start..end].iter().collect();
+            assert_eq!(slice.to_string(), expected);
+            assert_eq!(slice.len_chars(), end - start);
+            assert_eq!(slice.len_bytes(), expected.len());
+            assert!(slice.chars().eq(expected.chars()));
+        }
+        assert_eq!(rope.slice(4..7), "abc");
+        assert_eq!(rope.char(3), '😀');
+    }
+
+    #[test]
+    fn test_index_conversions() {
+        let text = "ab\nçd\n\n😀e\nlast";
+        let rope = Rope::from(text);
+        assert_eq!(rope.len_lines(), 5);
+        for (c, (b, _)) in text.char_indices().enumerate() {
+            assert_eq!(rope.char_to_byte(c), b);
+            assert_eq!(rope.byte_to_char(b), c);
+            assert_eq!(rope.char_to_line(c), text[..b].matches('\n').count());
+        }
+        assert_eq!(rope.char_to_byte(rope.len_chars()), text.len());
+        assert_eq!(rope.byte_to_char(text.len()), rope.len_chars());
+        // 落在 '😀' 中间的字节属于这个字符
+        assert_eq!(rope.byte_to_char(10), 7);
+        assert_eq!(rope.byte_to_char(11), 7);
+
+        let starts = [0, 3, 6, 7, 10, rope.len_chars()];
+        for (line, &start) in starts.iter().enumerate() {
+            assert_eq!(rope.line_to_char(line), start);
+        }
+        assert_eq!(rope.line(1), "çd\n");
+        assert_eq!(rope.line(2), "\n");
+        assert_eq!(rope.line(4), "last");
+        assert_eq!(rope.line_to_byte(3), 8);
+        assert_eq!(rope.byte_to_line(8), 3);
+        assert_eq!(Rope::new().line_to_char(0), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn test_insert_out_of_bounds() {
+        Rope::from("abc").insert(4, "x");
+    }
+
+    #[test]
+    fn test_against_string() {
+        let mut rng = StdRng::seed_from_u64(45);
+        let pieces = ["a", "bc", "\n", "é", "中文", "😀\n", "0123456789"];
+        let mut rope = Rope::new();
+        let mut expected: Vec<char> = Vec::new();
+        for step in 0..2_000 {
+            if rng.gen_bool(0.6) || expected.is_empty() {
+                let repeat = if rng.gen_bool(0.05) { 20 } else { 1 };
+                let s = pieces[rng.gen_range(0..pieces.len())].repeat(repeat);
+                let at = rng.gen_range(0..=expected.len());
+                rope.insert(at, &s);
+                expected.splice(at..at, s.chars());
+            } else {
+                let start = rng.gen_range(0..expected.len());
+                let end = rng.gen_range(start..=expected.len().min(start + 30));
+                rope.remove(start..end);
+                expected.drain(start..end);
+            }
+            if step % 50 == 0 {
+                let text: String = expected.iter().collect();
+                check_rope(&rope, &text);
+                let c = rng.gen_range(0..=expected.len());
+                let b = text.char_indices().nth(c).map_or(text.len(), |(i, _)| i);
+                assert_eq!(rope.char_to_byte(c), b);
+                assert_eq!(rope.byte_to_char(b), c);
+                assert_eq!(rope.char_to_line(c), text[..b].matches('\n').count());
+            }
+        }
+    }
+}
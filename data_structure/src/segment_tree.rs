@@ -0,0 +1,754 @@
+/*
+    线段树
+
+    - SegmentTree：单点修改、区间折叠（区间和、最值等），元素类型和运算由 Monoid 决定。
+    - LazySegmentTree：在 SegmentTree 的基础上支持区间修改（区间加、区间赋值等），
+      修改操作由 Action 描述，未下传的修改以懒标记的形式保存在内部节点上。
+
+    两者都是自底向上的非递归实现：叶子存放在 d[size..size + n]，节点 k 的两个子节点
+    为 2k 和 2k + 1，size 是不小于 n 的 2 的幂。
+    max_right / min_left 在树上二分，找出满足谓词的最长区间，复杂度 O(log n)。
+*/
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+/// 幺半群：满足结合律的二元运算 op 以及它的单位元
+pub trait Monoid {
+    type S: Clone;
+    fn identity() -> Self::S;
+    fn op(a: &Self::S, b: &Self::S) -> Self::S;
+}
+
+/// 作用在幺半群 M 上的一族映射 F，用于区间修改
+///
+/// 要求 mapping(f, op(a, b)) == op(mapping(f, a), mapping(f, b))，
+/// composition(f, g) 表示先作用 g 再作用 f。
+pub trait Action {
+    type M: Monoid;
+    type F: Clone;
+    fn identity_map() -> Self::F;
+    fn mapping(f: &Self::F, x: &<Self::M as Monoid>::S) -> <Self::M as Monoid>::S;
+    fn composition(f: &Self::F, g: &Self::F) -> Self::F;
+}
+
+/// 求和
+pub struct Sum<T>(PhantomData<T>);
+/// 求最小值
+pub struct Min<T>(PhantomData<T>);
+/// 求最大值
+pub struct Max<T>(PhantomData<T>);
+/// 元素为 (区间和, 区间长度) 的求和，区间加和区间赋值需要知道区间长度
+pub struct SumWithLen<T>(PhantomData<T>);
+
+/// 区间加，查询区间和；元素为 (值, 1)
+pub struct RangeAddSum<T>(PhantomData<T>);
+/// 区间加，查询区间最小值
+pub struct RangeAddMin<T>(PhantomData<T>);
+/// 区间加，查询区间最大值
+pub struct RangeAddMax<T>(PhantomData<T>);
+/// 区间赋值，查询区间和；元素为 (值, 1)
+pub struct RangeAssignSum<T>(PhantomData<T>);
+/// 区间赋值，查询区间最小值
+pub struct RangeAssignMin<T>(PhantomData<T>);
+/// 区间赋值，查询区间最大值
+pub struct RangeAssignMax<T>(PhantomData<T>);
+
+macro_rules! impl_integer_monoids {
+    ($($t:ty),*) => {
+        $(
+            impl Monoid for Sum<$t> {
+                type S = $t;
+                fn identity() -> $t {
+                    0
+                }
+                fn op(a: &$t, b: &$t) -> $t {
+                    a + b
+                }
+            }
+            impl Monoid for Min<$t> {
+                type S = $t;
+                fn identity() -> $t {
+                    <$t>::MAX
+                }
+                fn op(a: &$t, b: &$t) -> $t {
+                    *a.min(b)
+                }
+            }
+            impl Monoid for Max<$t> {
+                type S = $t;
+                fn identity() -> $t {
+                    <$t>::MIN
+                }
+                fn op(a: &$t, b: &$t) -> $t {
+                    *a.max(b)
+                }
+            }
+            impl Monoid for SumWithLen<$t> {
+                type S = ($t, usize);
+                fn identity() -> ($t, usize) {
+                    (0, 0)
+                }
+                fn op(a: &($t, usize), b: &($t, usize)) -> ($t, usize) {
+                    (a.0 + b.0, a.1 + b.1)
+                }
+            }
+            impl Action for RangeAddSum<$t> {
+                type M = SumWithLen<$t>;
+                type F = $t;
+                fn identity_map() -> $t {
+                    0
+                }
+                fn mapping(f: &$t, x: &($t, usize)) -> ($t, usize) {
+                    (x.0 + f * x.1 as $t, x.1)
+                }
+                fn composition(f: &$t, g: &$t) -> $t {
+                    f + g
+                }
+            }
+            impl Action for RangeAssignSum<$t> {
+                type M = SumWithLen<$t>;
+                type F = Option<$t>;
+                fn identity_map() -> Option<$t> {
+                    None
+                }
+                fn mapping(f: &Option<$t>, x: &($t, usize)) -> ($t, usize) {
+                    match f {
+                        Some(v) => (v * x.1 as $t, x.1),
+                        None => *x,
+                    }
+                }
+                fn composition(f: &Option<$t>, g: &Option<$t>) -> Option<$t> {
+                    f.or(*g)
+                }
+            }
+            impl_integer_monoids!(@extremum $t, Min, RangeAddMin, RangeAssignMin);
+            impl_integer_monoids!(@extremum $t, Max, RangeAddMax, RangeAssignMax);
+        )*
+    };
+    (@extremum $t:ty, $monoid:ident, $add:ident, $assign:ident) => {
+        impl Action for $add<$t> {
+            type M = $monoid<$t>;
+            type F = $t;
+            fn identity_map() -> $t {
+                0
+            }
+            fn mapping(f: &$t, x: &$t) -> $t {
+                // 单位元（空区间）保持不变，避免溢出
+                if *x == <$monoid<$t> as Monoid>::identity() {
+                    *x
+                } else {
+                    x + f
+                }
+            }
+            fn composition(f: &$t, g: &$t) -> $t {
+                f + g
+            }
+        }
+        impl Action for $assign<$t> {
+            type M = $monoid<$t>;
+            type F = Option<$t>;
+            fn identity_map() -> Option<$t> {
+                None
+            }
+            fn mapping(f: &Option<$t>, x: &$t) -> $t {
+                f.unwrap_or(*x)
+            }
+            fn composition(f: &Option<$t>, g: &Option<$t>) -> Option<$t> {
+                f.or(*g)
+            }
+        }
+    };
+}
+
+impl_integer_monoids!(i8, i16, i32, i64, i128, isize);
+
+// 把任意形式的区间转换成 [l, r)，并检查是否越界
+pub(crate) fn to_half_open<R: RangeBounds<usize>>(range: R, n: usize) -> (usize, usize) {
+    let l = match range.start_bound() {
+        Bound::Included(&l) => l,
+        Bound::Excluded(&l) => l + 1,
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(&r) => r + 1,
+        Bound::Excluded(&r) => r,
+        Bound::Unbounded => n,
+    };
+    assert!(
+        l <= r && r <= n,
+        "range {}..{} out of bounds for length {}",
+        l,
+        r,
+        n
+    );
+    (l, r)
+}
+
+fn check_index(p: usize, bound: usize) {
+    assert!(p < bound, "index {} out of bounds", p);
+}
+
+pub struct SegmentTree<M: Monoid> {
+    n: usize,
+    size: usize,
+    log: u32,
+    d: Vec<M::S>,
+}
+
+impl<M: Monoid> From<Vec<M::S>> for SegmentTree<M> {
+    fn from(values: Vec<M::S>) -> Self {
+        let n = values.len();
+        let size = n.next_power_of_two();
+        let mut d = vec![M::identity(); 2 * size];
+        for (i, value) in values.into_iter().enumerate() {
+            d[size + i] = value;
+        }
+        let mut tree = SegmentTree {
+            n,
+            size,
+            log: size.trailing_zeros(),
+            d,
+        };
+        for k in (1..size).rev() {
+            tree.update(k);
+        }
+        tree
+    }
+}
+
+impl<M: Monoid> SegmentTree<M> {
+    /// 创建长度为 n、所有元素都是单位元的线段树
+    pub fn new(n: usize) -> Self {
+        Self::from(vec![M::identity(); n])
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// 把第 p 个元素修改为 value
+    pub fn set(&mut self, p: usize, value: M::S) {
+        check_index(p, self.n);
+        let p = p + self.size;
+        self.d[p] = value;
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    pub fn get(&self, p: usize) -> &M::S {
+        check_index(p, self.n);
+        &self.d[p + self.size]
+    }
+
+    /// 区间内所有元素按顺序折叠的结果，空区间返回单位元
+    pub fn fold<R: RangeBounds<usize>>(&self, range: R) -> M::S {
+        let (l, r) = to_half_open(range, self.n);
+        let (mut l, mut r) = (l + self.size, r + self.size);
+        let mut left = M::identity();
+        let mut right = M::identity();
+        while l < r {
+            if l & 1 == 1 {
+                left = M::op(&left, &self.d[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = M::op(&self.d[r], &right);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        M::op(&left, &right)
+    }
+
+    pub fn all_fold(&self) -> M::S {
+        self.d[1].clone()
+    }
+
+    /// 返回最大的 r，使得 pred(fold(l..r)) 为真。
+    /// 要求 pred(单位元) 为真，并且 pred 关于区间扩张是单调的
+    pub fn max_right<P>(&self, l: usize, mut pred: P) -> usize
+    where
+        P: FnMut(&M::S) -> bool,
+    {
+        check_index(l, self.n + 1);
+        assert!(pred(&M::identity()), "predicate must hold for the identity");
+        if l == self.n {
+            return self.n;
+        }
+        let mut l = l + self.size;
+        let mut acc = M::identity();
+        loop {
+            while l.is_multiple_of(2) {
+                l >>= 1;
+            }
+            if !pred(&M::op(&acc, &self.d[l])) {
+                // 答案在以 l 为根的子树中，向下二分
+                while l < self.size {
+                    l *= 2;
+                    let next = M::op(&acc, &self.d[l]);
+                    if pred(&next) {
+                        acc = next;
+                        l += 1;
+                    }
+                }
+                return l - self.size;
+            }
+            acc = M::op(&acc, &self.d[l]);
+            l += 1;
+            if l.is_power_of_two() {
+                return self.n;
+            }
+        }
+    }
+
+    /// 返回最小的 l，使得 pred(fold(l..r)) 为真，要求同 max_right
+    pub fn min_left<P>(&self, r: usize, mut pred: P) -> usize
+    where
+        P: FnMut(&M::S) -> bool,
+    {
+        check_index(r, self.n + 1);
+        assert!(pred(&M::identity()), "predicate must hold for the identity");
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.size;
+        let mut acc = M::identity();
+        loop {
+            r -= 1;
+            while r > 1 && r % 2 == 1 {
+                r >>= 1;
+            }
+            if !pred(&M::op(&self.d[r], &acc)) {
+                while r < self.size {
+                    r = 2 * r + 1;
+                    let next = M::op(&self.d[r], &acc);
+                    if pred(&next) {
+                        acc = next;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.size;
+            }
+            acc = M::op(&self.d[r], &acc);
+            if r.is_power_of_two() {
+                return 0;
+            }
+        }
+    }
+
+    fn update(&mut self, k: usize) {
+        self.d[k] = M::op(&self.d[2 * k], &self.d[2 * k + 1]);
+    }
+}
+
+pub struct LazySegmentTree<A: Action> {
+    n: usize,
+    size: usize,
+    log: u32,
+    d: Vec<<A::M as Monoid>::S>,
+    lazy: Vec<A::F>, // 只有内部节点有懒标记，表示还没有下传给子节点的修改
+}
+
+impl<A: Action> From<Vec<<A::M as Monoid>::S>> for LazySegmentTree<A> {
+    fn from(values: Vec<<A::M as Monoid>::S>) -> Self {
+        let n = values.len();
+        let size = n.next_power_of_two();
+        let mut d = vec![A::M::identity(); 2 * size];
+        for (i, value) in values.into_iter().enumerate() {
+            d[size + i] = value;
+        }
+        let mut tree = LazySegmentTree {
+            n,
+            size,
+            log: size.trailing_zeros(),
+            d,
+            lazy: vec![A::identity_map(); size],
+        };
+        for k in (1..size).rev() {
+            tree.update(k);
+        }
+        tree
+    }
+}
+
+impl<A: Action> LazySegmentTree<A> {
+    pub fn new(n: usize) -> Self {
+        Self::from(vec![A::M::identity(); n])
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn set(&mut self, p: usize, value: <A::M as Monoid>::S) {
+        check_index(p, self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.d[p] = value;
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    // 需要先把路径上的懒标记下传，所以要求 &mut self
+    pub fn get(&mut self, p: usize) -> &<A::M as Monoid>::S {
+        check_index(p, self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        &self.d[p]
+    }
+
+    pub fn fold<R: RangeBounds<usize>>(&mut self, range: R) -> <A::M as Monoid>::S {
+        let (l, r) = to_half_open(range, self.n);
+        if l == r {
+            return A::M::identity();
+        }
+        let (mut l, mut r) = (l + self.size, r + self.size);
+        self.push_boundaries(l, r);
+        let mut left = A::M::identity();
+        let mut right = A::M::identity();
+        while l < r {
+            if l & 1 == 1 {
+                left = A::M::op(&left, &self.d[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = A::M::op(&self.d[r], &right);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        A::M::op(&left, &right)
+    }
+
+    pub fn all_fold(&self) -> <A::M as Monoid>::S {
+        self.d[1].clone()
+    }
+
+    /// 对第 p 个元素作用 f
+    pub fn apply(&mut self, p: usize, f: &A::F) {
+        check_index(p, self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.d[p] = A::mapping(f, &self.d[p]);
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    /// 对区间内的每个元素作用 f
+    pub fn apply_range<R: RangeBounds<usize>>(&mut self, range: R, f: &A::F) {
+        let (l, r) = to_half_open(range, self.n);
+        if l == r {
+            return;
+        }
+        let (l, r) = (l + self.size, r + self.size);
+        self.push_boundaries(l, r);
+        {
+            let (mut l, mut r) = (l, r);
+            while l < r {
+                if l & 1 == 1 {
+                    self.apply_to_node(l, f);
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    self.apply_to_node(r, f);
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for i in 1..=self.log {
+            if ((l >> i) << i) != l {
+                self.update(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.update((r - 1) >> i);
+            }
+        }
+    }
+
+    /// 同 SegmentTree::max_right
+    pub fn max_right<P>(&mut self, l: usize, mut pred: P) -> usize
+    where
+        P: FnMut(&<A::M as Monoid>::S) -> bool,
+    {
+        check_index(l, self.n + 1);
+        assert!(
+            pred(&A::M::identity()),
+            "predicate must hold for the identity"
+        );
+        if l == self.n {
+            return self.n;
+        }
+        let mut l = l + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(l >> i);
+        }
+        let mut acc = A::M::identity();
+        loop {
+            while l.is_multiple_of(2) {
+                l >>= 1;
+            }
+            if !pred(&A::M::op(&acc, &self.d[l])) {
+                while l < self.size {
+                    self.push(l);
+                    l *= 2;
+                    let next = A::M::op(&acc, &self.d[l]);
+                    if pred(&next) {
+                        acc = next;
+                        l += 1;
+                    }
+                }
+                return l - self.size;
+            }
+            acc = A::M::op(&acc, &self.d[l]);
+            l += 1;
+            if l.is_power_of_two() {
+                return self.n;
+            }
+        }
+    }
+
+    /// 同 SegmentTree::min_left
+    pub fn min_left<P>(&mut self, r: usize, mut pred: P) -> usize
+    where
+        P: FnMut(&<A::M as Monoid>::S) -> bool,
+    {
+        check_index(r, self.n + 1);
+        assert!(
+            pred(&A::M::identity()),
+            "predicate must hold for the identity"
+        );
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.size;
+        for i in (1..=self.log).rev() {
+            self.push((r - 1) >> i);
+        }
+        let mut acc = A::M::identity();
+        loop {
+            r -= 1;
+            while r > 1 && r % 2 == 1 {
+                r >>= 1;
+            }
+            if !pred(&A::M::op(&self.d[r], &acc)) {
+                while r < self.size {
+                    self.push(r);
+                    r = 2 * r + 1;
+                    let next = A::M::op(&self.d[r], &acc);
+                    if pred(&next) {
+                        acc = next;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.size;
+            }
+            acc = A::M::op(&self.d[r], &acc);
+            if r.is_power_of_two() {
+                return 0;
+            }
+        }
+    }
+
+    fn update(&mut self, k: usize) {
+        self.d[k] = A::M::op(&self.d[2 * k], &self.d[2 * k + 1]);
+    }
+
+    fn apply_to_node(&mut self, k: usize, f: &A::F) {
+        self.d[k] = A::mapping(f, &self.d[k]);
+        if k < self.size {
+            self.lazy[k] = A::composition(f, &self.lazy[k]);
+        }
+    }
+
+    // 把节点 k 的懒标记下传给两个子节点
+    fn push(&mut self, k: usize) {
+        let f = std::mem::replace(&mut self.lazy[k], A::identity_map());
+        self.apply_to_node(2 * k, &f);
+        self.apply_to_node(2 * k + 1, &f);
+    }
+
+    // 访问叶子区间 [l, r) 之前，先下传两个端点的祖先上的懒标记
+    fn push_boundaries(&mut self, l: usize, r: usize) {
+        for i in (1..=self.log).rev() {
+            if ((l >> i) << i) != l {
+                self.push(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.push((r - 1) >> i);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_segment_tree_basic() {
+        let mut tree: SegmentTree<Sum<i64>> = SegmentTree::from(vec![1, 2, 3, 4, 5]);
+        assert_eq!(tree.fold(..), 15);
+        assert_eq!(tree.fold(1..3), 5);
+        assert_eq!(tree.fold(1..=3), 9);
+        assert_eq!(tree.fold(2..2), 0);
+        tree.set(2, 10);
+        assert_eq!(*tree.get(2), 10);
+        assert_eq!(tree.all_fold(), 22);
+
+        // 前缀和不超过 13 的最长前缀是 [0, 3)
+        assert_eq!(tree.max_right(0, |&s| s <= 13), 3);
+        assert_eq!(tree.max_right(0, |&s| s <= 100), 5);
+        assert_eq!(tree.min_left(5, |&s| s <= 9), 3);
+        assert_eq!(tree.min_left(5, |&s| s <= 100), 0);
+
+        let empty: SegmentTree<Max<i32>> = SegmentTree::new(0);
+        assert_eq!(empty.fold(..), i32::MIN);
+        assert_eq!(empty.max_right(0, |_| true), 0);
+    }
+
+    #[test]
+    fn test_segment_tree_against_naive() {
+        let mut rng = StdRng::seed_from_u64(38);
+        for n in [1, 2, 3, 7, 8, 9, 31, 64, 100] {
+            let mut values: Vec<i64> = (0..n).map(|_| rng.gen_range(-50..50)).collect();
+            let mut sum: SegmentTree<Sum<i64>> = SegmentTree::from(values.clone());
+            let mut max: SegmentTree<Max<i64>> = SegmentTree::from(values.clone());
+            for _ in 0..500 {
+                let l = rng.gen_range(0..=n);
+                let r = rng.gen_range(l..=n);
+                match rng.gen_range(0..4) {
+                    0 => {
+                        let p = rng.gen_range(0..n);
+                        let value = rng.gen_range(-50..50);
+                        values[p] = value;
+                        sum.set(p, value);
+                        max.set(p, value);
+                    }
+                    1 => {
+                        assert_eq!(sum.fold(l..r), values[l..r].iter().sum::<i64>());
+                        let naive = values[l..r].iter().copied().max().unwrap_or(i64::MIN);
+                        assert_eq!(max.fold(l..r), naive);
+                    }
+                    2 => {
+                        // 从 l 开始最大值不超过 limit 的最长区间
+                        let limit = rng.gen_range(-50..50);
+                        let naive = (l..n).find(|&i| values[i] > limit).unwrap_or(n);
+                        assert_eq!(max.max_right(l, |&m| m <= limit), naive);
+                    }
+                    _ => {
+                        let limit = rng.gen_range(-50..50);
+                        let naive = (0..r)
+                            .rev()
+                            .find(|&i| values[i] > limit)
+                            .map_or(0, |i| i + 1);
+                        assert_eq!(max.min_left(r, |&m| m <= limit), naive);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_range_add_sum() {
+        let mut tree: LazySegmentTree<RangeAddSum<i64>> = LazySegmentTree::from(vec![(0, 1); 10]);
+        tree.apply_range(2..6, &3);
+        tree.apply_range(4.., &-1);
+        tree.apply(0, &7);
+        assert_eq!(tree.fold(..).0, 7 + 3 * 4 - 6);
+        assert_eq!(tree.fold(3..5).0, 3 + 2);
+        assert_eq!(tree.get(9).0, -1);
+    }
+
+    #[test]
+    fn test_lazy_against_naive() {
+        let mut rng = StdRng::seed_from_u64(380);
+        for n in [1, 2, 5, 16, 33, 100] {
+            let mut values: Vec<i64> = (0..n).map(|_| rng.gen_range(-50..50)).collect();
+            let mut add_sum: LazySegmentTree<RangeAddSum<i64>> =
+                LazySegmentTree::from(values.iter().map(|&v| (v, 1)).collect::<Vec<_>>());
+            let mut add_min: LazySegmentTree<RangeAddMin<i64>> =
+                LazySegmentTree::from(values.clone());
+            for _ in 0..500 {
+                let l = rng.gen_range(0..=n);
+                let r = rng.gen_range(l..=n);
+                match rng.gen_range(0..4) {
+                    0 => {
+                        let delta = rng.gen_range(-20..20);
+                        values[l..r].iter_mut().for_each(|v| *v += delta);
+                        add_sum.apply_range(l..r, &delta);
+                        add_min.apply_range(l..r, &delta);
+                    }
+                    1 => {
+                        assert_eq!(add_sum.fold(l..r), (values[l..r].iter().sum(), r - l));
+                        let naive = values[l..r].iter().copied().min().unwrap_or(i64::MAX);
+                        assert_eq!(add_min.fold(l..r), naive);
+                    }
+                    2 => {
+                        let limit = rng.gen_range(-80..80);
+                        let naive = (l..n).find(|&i| values[i] < limit).unwrap_or(n);
+                        assert_eq!(add_min.max_right(l, |&m| m >= limit), naive);
+                    }
+                    _ => {
+                        let limit = rng.gen_range(-80..80);
+                        let naive = (0..r)
+                            .rev()
+                            .find(|&i| values[i] < limit)
+                            .map_or(0, |i| i + 1);
+                        assert_eq!(add_min.min_left(r, |&m| m >= limit), naive);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_assign_against_naive() {
+        let mut rng = StdRng::seed_from_u64(3800);
+        let n = 57;
+        let mut values: Vec<i32> = (0..n).map(|_| rng.gen_range(-50..50)).collect();
+        let mut assign_sum: LazySegmentTree<RangeAssignSum<i32>> =
+            LazySegmentTree::from(values.iter().map(|&v| (v, 1)).collect::<Vec<_>>());
+        let mut assign_max: LazySegmentTree<RangeAssignMax<i32>> =
+            LazySegmentTree::from(values.clone());
+        for _ in 0..2000 {
+            let l = rng.gen_range(0..=n);
+            let r = rng.gen_range(l..=n);
+            if rng.gen_bool(0.5) {
+                let value = rng.gen_range(-50..50);
+                values[l..r].iter_mut().for_each(|v| *v = value);
+                assign_sum.apply_range(l..r, &Some(value));
+                assign_max.apply_range(l..r, &Some(value));
+            } else {
+                assert_eq!(assign_sum.fold(l..r).0, values[l..r].iter().sum::<i32>());
+                let naive = values[l..r].iter().copied().max().unwrap_or(i32::MIN);
+                assert_eq!(assign_max.fold(l..r), naive);
+            }
+        }
+        for (p, &value) in values.iter().enumerate() {
+            assert_eq!(*assign_max.get(p), value);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+/*
+    稀疏表（Sparse Table）与最近公共祖先（LCA）
+
+    - SparseTable：table[j][i] 保存区间 [i, i + 2^j) 的运算结果，构建 O(n log n)。
+      对于幂等运算（min、max、gcd 等，满足 op(x, x) == x），任意区间都可以被两个
+      长度为 2^k 的区间覆盖（允许重叠），因此查询是 O(1) 的。
+    - Lca：对有根树做欧拉序遍历，u 和 v 的最近公共祖先就是欧拉序中两者第一次出现的
+      位置之间深度最小的节点，用 SparseTable 做 O(1) 查询；
+      另外用倍增表 up[j][v]（v 的第 2^j 个祖先）回答第 k 个祖先的查询。
+*/
+use crate::segment_tree::to_half_open;
+use std::ops::RangeBounds;
+
+pub struct SparseTable<T, F> {
+    table: Vec<Vec<T>>,
+    op: F,
+}
+
+impl<T, F> SparseTable<T, F>
+where
+    T: Clone,
+    F: Fn(&T, &T) -> T,
+{
+    /// op 必须满足结合律和幂等性
+    pub fn new(values: Vec<T>, op: F) -> Self {
+        let n = values.len();
+        let mut table = vec![values];
+        let mut width = 1;
+        while 2 * width <= n {
+            let prev = table.last().unwrap();
+            let next: Vec<T> = (0..=n - 2 * width)
+                .map(|i| op(&prev[i], &prev[i + width]))
+                .collect();
+            table.push(next);
+            width *= 2;
+        }
+        SparseTable { table, op }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table[0].is_empty()
+    }
+
+    /// 区间内所有元素的运算结果，空区间返回 None
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> Option<T> {
+        let (l, r) = to_half_open(range, self.len());
+        if l == r {
+            return None;
+        }
+        let j = (r - l).ilog2() as usize;
+        let row = &self.table[j];
+        Some((self.op)(&row[l], &row[r - (1 << j)]))
+    }
+}
+
+// 欧拉序中的元素：(深度, 节点)，按字典序取最小值即得到深度最小的节点
+type DepthNode = (usize, usize);
+type EulerTable = SparseTable<DepthNode, fn(&DepthNode, &DepthNode) -> DepthNode>;
+
+/// 有根树上的最近公共祖先查询
+pub struct Lca {
+    depth: Vec<usize>,
+    first: Vec<usize>,    // 节点在欧拉序中第一次出现的位置
+    up: Vec<Vec<usize>>,  // up[j][v]：v 的第 2^j 个祖先，根的祖先是它自己
+    reachable: Vec<bool>, // 从根出发能否到达
+    euler: EulerTable,
+}
+
+fn min_by_depth(a: &DepthNode, b: &DepthNode) -> DepthNode {
+    *a.min(b)
+}
+
+impl Lca {
+    /// adjacency[v] 是与 v 相邻的节点，可以只包含子节点，也可以像无向图一样
+    /// 同时包含父节点（与 simple_dfs 中的邻接表布局相同）。从根不可达的节点不参与查询
+    pub fn new(adjacency: &[Vec<usize>], root: usize) -> Self {
+        let n = adjacency.len();
+        assert!(root < n, "root {} out of bounds", root);
+        let mut depth = vec![0; n];
+        let mut parent = vec![root; n];
+        let mut first = vec![0; n];
+        let mut reachable = vec![false; n];
+        let mut tour: Vec<DepthNode> = Vec::with_capacity(2 * n);
+
+        // 非递归的深度优先遍历，栈中保存 (节点, 下一个要访问的邻居下标)
+        let mut stack = vec![(root, 0)];
+        reachable[root] = true;
+        tour.push((0, root));
+        while let Some(&mut (v, ref mut next)) = stack.last_mut() {
+            if let Some(&child) = adjacency[v].get(*next) {
+                *next += 1;
+                if reachable[child] {
+                    continue;
+                }
+                reachable[child] = true;
+                parent[child] = v;
+                depth[child] = depth[v] + 1;
+                first[child] = tour.len();
+                tour.push((depth[child], child));
+                stack.push((child, 0));
+            } else {
+                stack.pop();
+                if let Some(&(p, _)) = stack.last() {
+                    // 从子树返回后再次经过父节点
+                    tour.push((depth[p], p));
+                }
+            }
+        }
+
+        let levels = (usize::BITS - n.leading_zeros()).max(1) as usize;
+        let mut up = vec![parent];
+        for j in 1..levels {
+            let prev = &up[j - 1];
+            let next: Vec<usize> = (0..n).map(|v| prev[prev[v]]).collect();
+            up.push(next);
+        }
+
+        Lca {
+            depth,
+            first,
+            up,
+            reachable,
+            euler: SparseTable::new(tour, min_by_depth),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.depth.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.depth.is_empty()
+    }
+
+    /// 节点的深度（根为 0），不可达的节点返回 None
+    pub fn depth(&self, v: usize) -> Option<usize> {
+        self.reachable[v].then_some(self.depth[v])
+    }
+
+    /// u 和 v 的最近公共祖先，O(1)
+    pub fn lca(&self, u: usize, v: usize) -> Option<usize> {
+        if !self.reachable[u] || !self.reachable[v] {
+            return None;
+        }
+        let (l, r) = if self.first[u] <= self.first[v] {
+            (self.first[u], self.first[v])
+        } else {
+            (self.first[v], self.first[u])
+        };
+        self.euler.query(l..=r).map(|(_, node)| node)
+    }
+
+    /// u 和 v 之间路径的边数
+    pub fn distance(&self, u: usize, v: usize) -> Option<usize> {
+        let w = self.lca(u, v)?;
+        Some(self.depth[u] + self.depth[v] - 2 * self.depth[w])
+    }
+
+    /// u 的第 k 个祖先（k = 0 时为 u 本身），k 超过 u 的深度时返回 None，O(log n)
+    pub fn kth_ancestor(&self, u: usize, k: usize) -> Option<usize> {
+        if !self.reachable[u] || k > self.depth[u] {
+            return None;
+        }
+        let mut v = u;
+        for (j, level) in self.up.iter().enumerate() {
+            if k >> j & 1 == 1 {
+                v = level[v];
+            }
+        }
+        Some(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn gcd(a: &u64, b: &u64) -> u64 {
+        if *b == 0 {
+            *a
+        } else {
+            gcd(b, &(a % b))
+        }
+    }
+
+    #[test]
+    fn test_sparse_table() {
+        let values = vec![5, 2, 8, 1, 9, 3];
+        let min = SparseTable::new(values.clone(), |a: &i32, b: &i32| *a.min(b));
+        let max = SparseTable::new(values, |a: &i32, b: &i32| *a.max(b));
+        assert_eq!(min.query(..), Some(1));
+        assert_eq!(min.query(0..3), Some(2));
+        assert_eq!(max.query(4..=5), Some(9));
+        assert_eq!(max.query(2..2), None);
+
+        let g = SparseTable::new(vec![12, 18, 24, 9], gcd);
+        assert_eq!(g.query(0..3), Some(6));
+        assert_eq!(g.query(..), Some(3));
+
+        let empty = SparseTable::new(Vec::<i32>::new(), |a: &i32, b: &i32| *a.min(b));
+        assert!(empty.is_empty());
+        assert_eq!(empty.query(..), None);
+    }
+
+    #[test]
+    fn test_sparse_table_against_naive() {
+        let mut rng = StdRng::seed_from_u64(40);
+        for n in [1, 2, 3, 5, 8, 17, 100] {
+            let values: Vec<i64> = (0..n).map(|_| rng.gen_range(-100..100)).collect();
+            let table = SparseTable::new(values.clone(), |a: &i64, b: &i64| *a.min(b));
+            for l in 0..n {
+                for r in l + 1..=n {
+                    assert_eq!(table.query(l..r), values[l..r].iter().copied().min());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lca() {
+        //        0
+        //      /   \
+        //     1     2
+        //    / \     \
+        //   3   4     5
+        //       |
+        //       6
+        // 使用无向的邻接表，7 与树不连通
+        let mut adjacency = vec![Vec::new(); 8];
+        for (u, v) in [(0, 1), (0, 2), (1, 3), (1, 4), (2, 5), (4, 6)] {
+            adjacency[u].push(v);
+            adjacency[v].push(u);
+        }
+        let lca = Lca::new(&adjacency, 0);
+        assert_eq!(lca.lca(3, 6), Some(1));
+        assert_eq!(lca.lca(6, 5), Some(0));
+        assert_eq!(lca.lca(4, 6), Some(4));
+        assert_eq!(lca.lca(2, 2), Some(2));
+        assert_eq!(lca.distance(3, 5), Some(4));
+        assert_eq!(lca.distance(6, 6), Some(0));
+        assert_eq!(lca.kth_ancestor(6, 2), Some(1));
+        assert_eq!(lca.kth_ancestor(6, 3), Some(0));
+        assert_eq!(lca.kth_ancestor(6, 4), None);
+        assert_eq!(lca.depth(7), None);
+        assert_eq!(lca.lca(7, 0), None);
+    }
+
+    #[test]
+    fn test_lca_against_naive() {
+        let mut rng = StdRng::seed_from_u64(400);
+        for n in [1, 2, 10, 200] {
+            // 随机树：节点 v 的父节点从编号更小的节点中选，只存子节点
+            let mut parent = vec![0; n];
+            let mut children = vec![Vec::new(); n];
+            for v in 1..n {
+                parent[v] = rng.gen_range(0..v);
+                children[parent[v]].push(v);
+            }
+            let lca = Lca::new(&children, 0);
+            let ancestors = |mut v: usize| {
+                let mut path = vec![v];
+                while v != 0 {
+                    v = parent[v];
+                    path.push(v);
+                }
+                path
+            };
+            for _ in 0..200 {
+                let (u, v) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                let (pu, pv) = (ancestors(u), ancestors(v));
+                let common = *pu.iter().find(|a| pv.contains(a)).unwrap();
+                assert_eq!(lca.lca(u, v), Some(common));
+                let expected = pu.iter().position(|&a| a == common).unwrap()
+                    + pv.iter().position(|&a| a == common).unwrap();
+                assert_eq!(lca.distance(u, v), Some(expected));
+                let k = rng.gen_range(0..=pu.len());
+                assert_eq!(lca.kth_ancestor(u, k), pu.get(k).copied());
+            }
+        }
+    }
+}
@@ -0,0 +1,554 @@
+/*
+    数据流统计的概要数据结构
+
+    - CountMinSketch：depth 行、每行 width 个计数器。元素在每一行被哈希到一个计数器上，
+      估计值取 depth 个计数器的最小值，只会高估不会低估。取 width = e / ε、
+      depth = ln(1 / δ) 时，以 1 - δ 的概率有 估计值 <= 真实值 + ε * 总数。
+      保守更新（conservative update）只把计数器增加到“新的估计值”为止，
+      仍然只会高估，但误差更小。
+    - HeavyHitters：在 CountMinSketch 之上用一个大小为 k 的小根堆维护估计频次最高的 k 个元素。
+    - HyperLogLog：m = 2^p 个寄存器，元素哈希的前 p 位选择寄存器，寄存器记录剩余位中
+      前导零个数 + 1 的最大值。相对误差约为 1.04 / sqrt(m)。寄存器大多为 0 时只保存非零的
+      寄存器：每个占 4 字节（下标 << 8 | 值），按下标排序存放在 Vec<u32> 中；非零寄存器
+      超过 m / 16 个后转换成 m 字节的稠密数组。Vec 的容量不超过 m / 16 + 1 项，
+      稀疏表示最多占用约 m / 4 字节。
+*/
+use crate::heap::Heap;
+use crate::membership_filter::{hash_of, mix};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncompatibleSketches;
+
+impl fmt::Display for IncompatibleSketches {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sketches have different parameters")
+    }
+}
+
+pub struct CountMinSketch<T: ?Sized> {
+    width: usize,
+    depth: usize,
+    counters: Vec<u64>, // depth 行 width 列，按行存储
+    total: u64,
+    conservative: bool,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> CountMinSketch<T> {
+    /// 以 1 - delta 的概率保证误差不超过 epsilon * 总数
+    pub fn new(epsilon: f64, delta: f64) -> Self {
+        assert!(epsilon > 0.0 && delta > 0.0 && delta < 1.0);
+        let width = (std::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+        Self::with_dimensions(width, depth)
+    }
+
+    /// 与 new 相同，但使用保守更新
+    pub fn new_conservative(epsilon: f64, delta: f64) -> Self {
+        let mut sketch = Self::new(epsilon, delta);
+        sketch.conservative = true;
+        sketch
+    }
+
+    pub fn with_dimensions(width: usize, depth: usize) -> Self {
+        assert!(width > 0 && depth > 0);
+        CountMinSketch {
+            width,
+            depth,
+            counters: vec![0; width * depth],
+            total: 0,
+            conservative: false,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// 所有元素的总次数
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    // 元素在每一行中对应的计数器下标
+    fn cells(&self, item: &T) -> impl Iterator<Item = usize> {
+        let hash = hash_of(item);
+        let h2 = mix(hash) | 1;
+        let width = self.width;
+        (0..self.depth).map(move |row| {
+            let h = hash.wrapping_add((row as u64).wrapping_mul(h2));
+            row * width + (h % width as u64) as usize
+        })
+    }
+
+    /// 元素出现 count 次，返回加入之后的估计频次
+    pub fn add(&mut self, item: &T, count: u64) -> u64 {
+        let cells: Vec<usize> = self.cells(item).collect();
+        self.total += count;
+        if self.conservative {
+            let target = cells.iter().map(|&c| self.counters[c]).min().unwrap() + count;
+            for c in cells {
+                self.counters[c] = self.counters[c].max(target);
+            }
+            target
+        } else {
+            let mut estimate = u64::MAX;
+            for c in cells {
+                self.counters[c] += count;
+                estimate = estimate.min(self.counters[c]);
+            }
+            estimate
+        }
+    }
+
+    /// 频次的估计值，不小于真实值
+    pub fn estimate(&self, item: &T) -> u64 {
+        self.cells(item).map(|c| self.counters[c]).min().unwrap()
+    }
+
+    /// 合并另一个尺寸相同的 sketch，相当于两个数据流拼接在一起
+    pub fn merge(&mut self, other: &Self) -> Result<(), IncompatibleSketches> {
+        if self.width != other.width || self.depth != other.depth {
+            return Err(IncompatibleSketches);
+        }
+        for (c, other) in self.counters.iter_mut().zip(&other.counters) {
+            *c += other;
+        }
+        self.total += other.total;
+        Ok(())
+    }
+}
+
+/// 估计频次最高的 k 个元素
+pub struct HeavyHitters<T> {
+    k: usize,
+    sketch: CountMinSketch<T>,
+    candidates: HashMap<T, (usize, u64)>, // 元素 -> (槽位, 最新的估计频次)
+    slots: Vec<Option<T>>,                // 槽位 -> 元素，被淘汰的元素留下 None
+    // 小根堆，元素为 (估计频次, 槽位)。同一个槽位可能有多个过期的条目，弹出时再丢弃
+    heap: Heap<(u64, usize)>,
+}
+
+impl<T> HeavyHitters<T>
+where
+    T: Hash + Eq + Clone,
+{
+    pub fn new(k: usize, epsilon: f64, delta: f64) -> Self {
+        assert!(k > 0);
+        HeavyHitters {
+            k,
+            sketch: CountMinSketch::new_conservative(epsilon, delta),
+            candidates: HashMap::new(),
+            slots: Vec::new(),
+            heap: Heap::new_min(),
+        }
+    }
+
+    pub fn sketch(&self) -> &CountMinSketch<T> {
+        &self.sketch
+    }
+
+    pub fn add(&mut self, item: &T, count: u64) {
+        let estimate = self.sketch.add(item, count);
+        if let Some(entry) = self.candidates.get_mut(item) {
+            entry.1 = estimate;
+            self.heap.add((estimate, entry.0));
+            return;
+        }
+        if self.candidates.len() < self.k {
+            self.track(item, estimate);
+            return;
+        }
+        // 找出当前候选中估计频次最小的一个，新元素更大时替换它
+        while let Some((smallest, slot)) = self.heap.pop() {
+            let Some(tracked) = &self.slots[slot] else {
+                continue;
+            };
+            if self.candidates[tracked] != (slot, smallest) {
+                continue; // 过期的条目
+            }
+            if estimate > smallest {
+                let evicted = self.slots[slot].take().unwrap();
+                self.candidates.remove(&evicted);
+                self.track(item, estimate);
+            } else {
+                self.heap.add((smallest, slot));
+            }
+            break;
+        }
+        // 过期条目太多时重建堆，避免堆无限增长
+        if self.heap.len() > 8 * self.k + 64 {
+            self.rebuild_heap();
+        }
+    }
+
+    fn track(&mut self, item: &T, estimate: u64) {
+        let slot = self.slots.len();
+        self.slots.push(Some(item.clone()));
+        self.candidates.insert(item.clone(), (slot, estimate));
+        self.heap.add((estimate, slot));
+    }
+
+    fn rebuild_heap(&mut self) {
+        // 同时压缩槽位，丢掉已经被淘汰的元素
+        let mut slots = Vec::with_capacity(self.k);
+        let mut heap = Heap::new_min();
+        for (item, entry) in self.candidates.iter_mut() {
+            entry.0 = slots.len();
+            heap.add((entry.1, entry.0));
+            slots.push(Some(item.clone()));
+        }
+        self.slots = slots;
+        self.heap = heap;
+    }
+
+    /// 按估计频次从高到低返回候选元素
+    pub fn top(&self) -> Vec<(T, u64)> {
+        let mut top: Vec<(T, u64)> = self
+            .candidates
+            .iter()
+            .map(|(item, &(_, estimate))| (item.clone(), estimate))
+            .collect();
+        top.sort_by_key(|&(_, estimate)| std::cmp::Reverse(estimate));
+        top
+    }
+}
+
+// 寄存器较少时的稀疏表示与稠密表示，稀疏表示中每一项为 下标 << 8 | 值，按下标排序
+enum Registers {
+    Sparse(Vec<u32>),
+    Dense(Vec<u8>),
+}
+
+pub struct HyperLogLog<T: ?Sized> {
+    precision: u32,
+    registers: Registers,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> HyperLogLog<T> {
+    /// precision 为 4 到 16，寄存器个数为 2^precision
+    pub fn new(precision: u32) -> Self {
+        assert!((4..=16).contains(&precision), "precision must be in 4..=16");
+        HyperLogLog {
+            precision,
+            registers: Registers::Sparse(Vec::new()),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+
+    fn num_registers(&self) -> usize {
+        1 << self.precision
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.registers, Registers::Sparse(_))
+    }
+
+    /// 标准误差 1.04 / sqrt(m)
+    pub fn relative_error(&self) -> f64 {
+        1.04 / (self.num_registers() as f64).sqrt()
+    }
+
+    pub fn add(&mut self, item: &T) {
+        let hash = mix(hash_of(item));
+        let index = (hash >> (64 - self.precision)) as u32;
+        // 剩余的 64 - p 位的前导零个数 + 1，最低位补 1 保证结果有上界
+        let rest = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        self.update_register(index, rank);
+    }
+
+    fn update_register(&mut self, index: u32, rank: u8) {
+        // 非零寄存器超过 limit 个后转换成稠密表示
+        let limit = self.num_registers() / 16;
+        match &mut self.registers {
+            Registers::Sparse(entries) => {
+                match entries.binary_search_by_key(&index, |&entry| entry >> 8) {
+                    Ok(i) => {
+                        if rank as u32 > entries[i] & 0xff {
+                            entries[i] = index << 8 | rank as u32;
+                        }
+                    }
+                    Err(i) => {
+                        // 按倍增扩容，但容量不超过转换前最多需要的 limit + 1 项
+                        if entries.len() == entries.capacity() {
+                            let extra = entries.len().max(4).min(limit + 1 - entries.len());
+                            entries.reserve_exact(extra);
+                        }
+                        entries.insert(i, index << 8 | rank as u32);
+                    }
+                }
+                if entries.len() > limit {
+                    self.promote();
+                }
+            }
+            Registers::Dense(registers) => {
+                let register = &mut registers[index as usize];
+                *register = (*register).max(rank);
+            }
+        }
+    }
+
+    // 稀疏表示转换成稠密表示
+    fn promote(&mut self) {
+        if let Registers::Sparse(entries) = &self.registers {
+            let mut dense = vec![0; self.num_registers()];
+            for &entry in entries {
+                dense[(entry >> 8) as usize] = entry as u8;
+            }
+            self.registers = Registers::Dense(dense);
+        }
+    }
+
+    fn register(&self, index: usize) -> u8 {
+        match &self.registers {
+            Registers::Sparse(entries) => entries
+                .binary_search_by_key(&(index as u32), |&entry| entry >> 8)
+                .map_or(0, |i| entries[i] as u8),
+            Registers::Dense(registers) => registers[index],
+        }
+    }
+
+    /// 不同元素个数的估计值
+    pub fn count(&self) -> u64 {
+        let m = self.num_registers() as f64;
+        let alpha = match self.num_registers() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let mut sum = 0.0;
+        let mut zeros = 0;
+        for index in 0..self.num_registers() {
+            let rank = self.register(index);
+            sum += 2f64.powi(-(rank as i32));
+            if rank == 0 {
+                zeros += 1;
+            }
+        }
+        let estimate = alpha * m * m / sum;
+        // 基数较小时改用线性计数，偏差更小
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+
+    /// 合并另一个精度相同的 HyperLogLog，结果与把两个集合的并集插入同一个 HyperLogLog 完全相同
+    pub fn merge(&mut self, other: &Self) -> Result<(), IncompatibleSketches> {
+        if self.precision != other.precision {
+            return Err(IncompatibleSketches);
+        }
+        match &other.registers {
+            Registers::Sparse(entries) => {
+                for &entry in entries {
+                    self.update_register(entry >> 8, entry as u8);
+                }
+            }
+            Registers::Dense(registers) => {
+                self.promote();
+                for (index, &rank) in registers.iter().enumerate() {
+                    if rank > 0 {
+                        self.update_register(index as u32, rank);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashSet;
+
+    // 近似 Zipf 分布的数据流：小编号的元素出现得多
+    fn skewed_stream(seed: u64, len: usize, universe: u64) -> Vec<u64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..len)
+            .map(|_| {
+                let x: f64 = rng.gen();
+                (universe as f64).powf(x) as u64
+            })
+            .collect()
+    }
+
+    fn exact_counts(stream: &[u64]) -> HashMap<u64, u64> {
+        let mut counts = HashMap::new();
+        for &x in stream {
+            *counts.entry(x).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_count_min_error_bound() {
+        let stream = skewed_stream(42, 100_000, 50_000);
+        let counts = exact_counts(&stream);
+        let (epsilon, delta) = (0.001, 0.01);
+        let mut plain = CountMinSketch::new(epsilon, delta);
+        let mut conservative = CountMinSketch::new_conservative(epsilon, delta);
+        for x in &stream {
+            plain.add(x, 1);
+            conservative.add(x, 1);
+        }
+        assert_eq!(plain.total(), stream.len() as u64);
+
+        let bound = (epsilon * stream.len() as f64) as u64;
+        let mut violations = 0;
+        for (x, &count) in &counts {
+            let estimate = plain.estimate(x);
+            let conservative_estimate = conservative.estimate(x);
+            assert!(estimate >= count);
+            assert!(conservative_estimate >= count);
+            assert!(conservative_estimate <= estimate);
+            if estimate > count + bound {
+                violations += 1;
+            }
+        }
+        assert!((violations as f64) < delta * counts.len() as f64);
+    }
+
+    #[test]
+    fn test_count_min_merge() {
+        let stream = skewed_stream(43, 20_000, 1_000);
+        let (left, right) = stream.split_at(7_000);
+        let mut a = CountMinSketch::with_dimensions(300, 4);
+        let mut b = CountMinSketch::with_dimensions(300, 4);
+        let mut whole = CountMinSketch::with_dimensions(300, 4);
+        left.iter().for_each(|x| {
+            a.add(x, 1);
+        });
+        right.iter().for_each(|x| {
+            b.add(x, 1);
+        });
+        stream.iter().for_each(|x| {
+            whole.add(x, 1);
+        });
+        a.merge(&b).unwrap();
+        for x in 0..1_000u64 {
+            assert_eq!(a.estimate(&x), whole.estimate(&x));
+        }
+        let mut other: CountMinSketch<u64> = CountMinSketch::with_dimensions(10, 4);
+        assert_eq!(other.merge(&a), Err(IncompatibleSketches));
+    }
+
+    #[test]
+    fn test_heavy_hitters() {
+        let stream = skewed_stream(44, 100_000, 100_000);
+        let counts = exact_counts(&stream);
+        let mut exact: Vec<(u64, u64)> = counts.into_iter().collect();
+        exact.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+
+        let mut heavy = HeavyHitters::new(10, 0.001, 0.01);
+        for x in &stream {
+            heavy.add(x, 1);
+        }
+        let top = heavy.top();
+        assert_eq!(top.len(), 10);
+        // 真实的前 5 名都应该在候选中
+        for (x, count) in &exact[..5] {
+            let (_, estimate) = top.iter().find(|(y, _)| y == x).unwrap();
+            assert!(estimate >= count);
+        }
+        assert!(top.windows(2).all(|w| w[0].1 >= w[1].1));
+    }
+
+    #[test]
+    fn test_hyperloglog_error_bound() {
+        let mut rng = StdRng::seed_from_u64(45);
+        for distinct in [0usize, 10, 1_000, 100_000] {
+            let mut hll = HyperLogLog::new(12);
+            let mut exact = HashSet::new();
+            while exact.len() < distinct {
+                let x: u64 = rng.gen();
+                exact.insert(x);
+                // 重复插入不影响结果
+                hll.add(&x);
+                hll.add(&x);
+            }
+            // 基数很小时两个元素落进同一个寄存器就会少算一个，额外允许 1 的绝对误差
+            let estimate = hll.count() as f64;
+            let tolerance = 3.0 * hll.relative_error() * distinct as f64 + 1.0;
+            assert!(
+                (estimate - distinct as f64).abs() <= tolerance,
+                "distinct {} estimated {}",
+                distinct,
+                estimate
+            );
+        }
+    }
+
+    #[test]
+    fn test_hyperloglog_sparse_promotion_and_merge() {
+        let mut a: HyperLogLog<str> = HyperLogLog::new(10);
+        let mut b: HyperLogLog<str> = HyperLogLog::new(10);
+        let mut union: HyperLogLog<str> = HyperLogLog::new(10);
+        for i in 0..40 {
+            let key = format!("key-{}", i);
+            a.add(&key);
+            union.add(&key);
+        }
+        assert!(a.is_sparse());
+        for i in 20..5_000 {
+            let key = format!("key-{}", i);
+            b.add(&key);
+            union.add(&key);
+        }
+        assert!(!b.is_sparse());
+
+        let mut merged_into_sparse: HyperLogLog<str> = HyperLogLog::new(10);
+        merged_into_sparse.merge(&a).unwrap();
+        merged_into_sparse.merge(&b).unwrap();
+        a.merge(&b).unwrap();
+        assert_eq!(a.count(), union.count());
+        assert_eq!(merged_into_sparse.count(), union.count());
+        let error = (union.count() as f64 - 5_000.0).abs() / 5_000.0;
+        assert!(error <= 3.0 * union.relative_error());
+
+        let other: HyperLogLog<str> = HyperLogLog::new(11);
+        assert_eq!(a.merge(&other), Err(IncompatibleSketches));
+    }
+
+    #[test]
+    fn test_hyperloglog_sparse_smaller_than_dense() {
+        for precision in [4, 10, 16] {
+            let mut hll: HyperLogLog<u64> = HyperLogLog::new(precision);
+            let dense_bytes = hll.num_registers();
+            let mut largest_sparse = 0;
+            let mut key = 0;
+            while let Registers::Sparse(entries) = &hll.registers {
+                let bytes = entries.capacity() * std::mem::size_of::<u32>();
+                assert!(bytes < dense_bytes, "{} sparse bytes", bytes);
+                largest_sparse = entries.len();
+                hll.add(&key);
+                key += 1;
+            }
+            // 刚好在超过 m / 16 个非零寄存器时转换
+            assert_eq!(largest_sparse, dense_bytes / 16);
+            match &hll.registers {
+                Registers::Dense(registers) => assert_eq!(registers.len(), dense_bytes),
+                Registers::Sparse(_) => unreachable!(),
+            }
+        }
+    }
+}
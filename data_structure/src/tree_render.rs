@@ -0,0 +1,122 @@
+/*
+    树形结构的调试输出
+
+    render_ascii 把树画成类似 `tree` 命令的样子：
+
+        5
+        ├── 3
+        │   ├── 2
+        │   └── ∅
+        └── 8
+
+    每个结构先把自己转换成 Shape（只保留标签和子节点），再交给这里统一绘制。
+    子节点可以是 None，用来表示二叉树中缺失的左孩子或右孩子，绘制成 ∅。
+
+    Graphviz 输出由各个结构自己生成，这里只提供标签转义。
+*/
+
+/// 只保留标签和子节点的树
+pub(crate) struct Shape {
+    pub(crate) label: String,
+    pub(crate) children: Vec<Option<Shape>>,
+}
+
+/// 用制表符画出整棵树，每个节点占一行，结果以换行结尾
+pub(crate) fn render_ascii(root: &Shape) -> String {
+    let mut out = String::new();
+    out.push_str(&root.label);
+    out.push('\n');
+    render_children(root, &mut String::new(), &mut out);
+    out
+}
+
+// prefix 是当前深度下每一行开头的竖线和空白
+fn render_children(node: &Shape, prefix: &mut String, out: &mut String) {
+    let count = node.children.len();
+    for (i, child) in node.children.iter().enumerate() {
+        let last = i + 1 == count;
+        out.push_str(prefix);
+        out.push_str(if last { "└── " } else { "├── " });
+        match child {
+            Some(child) => {
+                out.push_str(&child.label);
+                out.push('\n');
+                let len = prefix.len();
+                prefix.push_str(if last { "    " } else { "│   " });
+                render_children(child, prefix, out);
+                prefix.truncate(len);
+            }
+            None => out.push_str("∅\n"),
+        }
+    }
+}
+
+/// 转义 Graphviz 双引号字符串中的特殊字符
+pub(crate) fn escape_dot(label: &str) -> String {
+    escape(label, &[])
+}
+
+/// 转义 record 形状节点的字段，除了引号以外还要转义字段分隔符、端口和花括号
+pub(crate) fn escape_record(label: &str) -> String {
+    escape(label, &['{', '}', '|', '<', '>'])
+}
+
+fn escape(label: &str, special: &[char]) -> String {
+    let mut out = String::with_capacity(label.len());
+    for c in label.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => {
+                if special.contains(&c) {
+                    out.push('\\');
+                }
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(label: &str) -> Shape {
+        Shape {
+            label: label.to_string(),
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let tree = Shape {
+            label: "a".to_string(),
+            children: vec![
+                Some(Shape {
+                    label: "b".to_string(),
+                    children: vec![Some(leaf("c")), None],
+                }),
+                Some(leaf("d")),
+            ],
+        };
+        assert_eq!(
+            render_ascii(&tree),
+            "a\n\
+             ├── b\n\
+             │   ├── c\n\
+             │   └── ∅\n\
+             └── d\n"
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape_dot("say \"hi\"\\n"), "say \\\"hi\\\"\\\\n");
+        assert_eq!(escape_record("a|b <c>"), "a\\|b \\<c\\>");
+    }
+}
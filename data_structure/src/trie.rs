@@ -1,3 +1,6 @@
+use crate::tree_render::{self, Shape};
+use std::fmt::Write;
+
 #[derive(Default)]
 struct TrieNode {
     children: std::collections::HashMap<char, TrieNode>,
@@ -5,29 +8,29 @@ struct TrieNode {
 }
 
 #[derive(Default)]
-struct Trie {
+pub struct Trie {
     root: TrieNode,
 }
 
 impl Trie {
     // 初始化 Trie 对象
-    fn new() -> Self {
+    pub fn new() -> Self {
         Trie {
             root: TrieNode::default(),
         }
     }
 
     // 向 Trie 中插入字符串
-    fn insert(&mut self, word: String) {
+    pub fn insert(&mut self, word: String) {
         let mut node = &mut self.root;
         for c in word.chars() {
-            node = node.children.entry(c).or_insert_with(TrieNode::default);
+            node = node.children.entry(c).or_default();
         }
         node.is_end_of_word = true;
     }
 
     // 搜索字符串是否存在于 Trie 中
-    fn search(&self, word: String) -> bool {
+    pub fn search(&self, word: String) -> bool {
         let mut node = &self.root;
         for c in word.chars() {
             if let Some(next_node) = node.children.get(&c) {
@@ -40,7 +43,7 @@ impl Trie {
     }
 
     // 检查 Trie 中是否有以 prefix 为前缀的字符串
-    fn starts_with(&self, prefix: String) -> bool {
+    pub fn starts_with(&self, prefix: String) -> bool {
         let mut node = &self.root;
         for c in prefix.chars() {
             if let Some(next_node) = node.children.get(&c) {
@@ -51,4 +54,124 @@ impl Trie {
         }
         true
     }
+
+    // 用制表符画出整棵树，子节点按字符排序；单词结尾的节点后面标出整个单词
+    pub fn render_ascii(&self) -> String {
+        let mut prefix = String::new();
+        let shape = Shape {
+            label: "(root)".to_string(),
+            children: Self::child_shapes(&self.root, &mut prefix),
+        };
+        tree_render::render_ascii(&shape)
+    }
+
+    // 生成 Graphviz 有向图，边上标出字符，单词结尾的节点画成填色的双圈
+    pub fn to_dot(&self) -> String {
+        let mut out =
+            String::from("digraph Trie {\n    node [shape=circle, label=\"\"];\n    n0;\n");
+        Self::write_dot(&self.root, 0, &mut 1, &mut out);
+        out.push_str("}\n");
+        out
+    }
+
+    fn sorted_children(node: &TrieNode) -> Vec<(&char, &TrieNode)> {
+        let mut children: Vec<_> = node.children.iter().collect();
+        children.sort_unstable_by_key(|&(c, _)| *c);
+        children
+    }
+
+    fn child_shapes(node: &TrieNode, prefix: &mut String) -> Vec<Option<Shape>> {
+        Self::sorted_children(node)
+            .into_iter()
+            .map(|(&c, child)| {
+                prefix.push(c);
+                let label = if child.is_end_of_word {
+                    format!("{} ({})", c, prefix)
+                } else {
+                    c.to_string()
+                };
+                let children = Self::child_shapes(child, prefix);
+                prefix.pop();
+                Some(Shape { label, children })
+            })
+            .collect()
+    }
+
+    // 按先序给节点编号，根节点是 n0
+    fn write_dot(node: &TrieNode, id: usize, next_id: &mut usize, out: &mut String) {
+        for (&c, child) in Self::sorted_children(node) {
+            let child_id = *next_id;
+            *next_id += 1;
+            if child.is_end_of_word {
+                writeln!(
+                    out,
+                    "    n{} [shape=doublecircle, style=filled, fillcolor=lightblue];",
+                    child_id
+                )
+                .unwrap();
+            } else {
+                writeln!(out, "    n{};", child_id).unwrap();
+            }
+            let label = tree_render::escape_dot(&c.to_string());
+            writeln!(out, "    n{} -> n{} [label=\"{}\"];", id, child_id, label).unwrap();
+            Self::write_dot(child, child_id, next_id, out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(words: &[&str]) -> Trie {
+        let mut trie = Trie::new();
+        for word in words {
+            trie.insert(word.to_string());
+        }
+        trie
+    }
+
+    #[test]
+    fn test_insert_and_search() {
+        let trie = build(&["apple", "app", "bat"]);
+        assert!(trie.search("app".to_string()));
+        assert!(!trie.search("ap".to_string()));
+        assert!(trie.starts_with("ap".to_string()));
+        assert!(!trie.starts_with("c".to_string()));
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        assert_eq!(Trie::new().render_ascii(), "(root)\n");
+        let trie = build(&["tea", "to", "ten", "a"]);
+        assert_eq!(
+            trie.render_ascii(),
+            "(root)\n\
+             ├── a (a)\n\
+             └── t\n\
+             \x20   ├── e\n\
+             \x20   │   ├── a (tea)\n\
+             \x20   │   └── n (ten)\n\
+             \x20   └── o (to)\n"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let trie = build(&["ab", "b"]);
+        assert_eq!(
+            trie.to_dot(),
+            r#"digraph Trie {
+    node [shape=circle, label=""];
+    n0;
+    n1;
+    n0 -> n1 [label="a"];
+    n2 [shape=doublecircle, style=filled, fillcolor=lightblue];
+    n1 -> n2 [label="b"];
+    n3 [shape=doublecircle, style=filled, fillcolor=lightblue];
+    n0 -> n3 [label="b"];
+}
+"#
+        );
+    }
 }
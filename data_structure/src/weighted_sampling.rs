@@ -0,0 +1,354 @@
+/*
+    按权重随机抽样
+
+    - AliasTable：Walker/Vose 别名表，构建 O(n)，之后每次抽样 O(1)，权重不可修改。
+    - DynamicWeightedSampler：基于树状数组（Fenwick tree）维护权重的前缀和，
+      支持 O(log n) 修改权重和 O(log n) 抽样。
+
+    两者抽样时返回的都是下标，调用方自己把下标映射回具体的元素；
+    随机数生成器在抽样时传入，因此同一张表可以被多个 rng 共享。
+*/
+use crate::fenwick_tree::lowbit;
+use rand::Rng;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidWeights;
+
+impl fmt::Display for InvalidWeights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "weights must be finite, non-negative and not all zero")
+    }
+}
+
+fn is_valid_weight(weight: f64) -> bool {
+    weight.is_finite() && weight >= 0.0
+}
+
+pub struct AliasTable {
+    prob: Vec<f64>,    // 落在第 i 列时保留 i 本身的概率
+    alias: Vec<usize>, // 落在第 i 列但没有保留 i 时改为返回的下标
+}
+
+impl AliasTable {
+    /// 根据权重构建别名表，权重为空、含有负数/非有限值或者全为 0 时返回错误
+    pub fn new(weights: &[f64]) -> Result<Self, InvalidWeights> {
+        if !weights.iter().all(|&w| is_valid_weight(w)) {
+            return Err(InvalidWeights);
+        }
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Err(InvalidWeights);
+        }
+
+        let n = weights.len();
+        // 把权重缩放到平均值为 1，每一列的容量也是 1
+        let mut scaled: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+        let mut prob = vec![1.0; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| scaled[i] < 1.0);
+
+        // 每次用一个“大”列去填满一个“小”列，大列剩下的部分再重新分类
+        while let (Some(&less), Some(&more)) = (small.last(), large.last()) {
+            small.pop();
+            large.pop();
+            prob[less] = scaled[less];
+            alias[less] = more;
+            scaled[more] = (scaled[more] + scaled[less]) - 1.0;
+            if scaled[more] < 1.0 {
+                small.push(more);
+            } else {
+                large.push(more);
+            }
+        }
+
+        // 剩下的列由于浮点误差可能不是恰好 1，直接视为满的
+        for i in small.into_iter().chain(large) {
+            prob[i] = 1.0;
+        }
+
+        Ok(AliasTable { prob, alias })
+    }
+
+    /// 返回表中元素的个数
+    pub fn len(&self) -> usize {
+        self.prob.len()
+    }
+
+    /// 判断表是否为空（构建成功的表永远不为空）
+    pub fn is_empty(&self) -> bool {
+        self.prob.is_empty()
+    }
+
+    /// 按权重随机抽取一个下标
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let column = rng.gen_range(0..self.prob.len());
+        if rng.gen::<f64>() < self.prob[column] {
+            column
+        } else {
+            self.alias[column]
+        }
+    }
+}
+
+pub struct DynamicWeightedSampler {
+    weights: Vec<f64>,
+    tree: Vec<f64>, // 树状数组，下标从 1 开始，tree[0] 不使用
+}
+
+impl Default for DynamicWeightedSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DynamicWeightedSampler {
+    /// 创建一个空的抽样器
+    pub fn new() -> Self {
+        DynamicWeightedSampler {
+            weights: Vec::new(),
+            tree: vec![0.0],
+        }
+    }
+
+    /// 根据一组初始权重在 O(n) 内构建抽样器
+    pub fn from_weights(weights: &[f64]) -> Result<Self, InvalidWeights> {
+        if !weights.iter().all(|&w| is_valid_weight(w)) {
+            return Err(InvalidWeights);
+        }
+        let n = weights.len();
+        let mut tree = vec![0.0; n + 1];
+        tree[1..].copy_from_slice(weights);
+        // 每个节点把自己的和累加到父节点上
+        for i in 1..=n {
+            let parent = i + lowbit(i);
+            if parent <= n {
+                tree[parent] += tree[i];
+            }
+        }
+        Ok(DynamicWeightedSampler {
+            weights: weights.to_vec(),
+            tree,
+        })
+    }
+
+    /// 返回元素的个数（包括权重为 0 的元素）
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// 判断是否没有任何元素
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// 返回第 index 个元素当前的权重
+    pub fn weight(&self, index: usize) -> Option<f64> {
+        self.weights.get(index).copied()
+    }
+
+    /// 返回所有权重之和
+    pub fn total(&self) -> f64 {
+        self.prefix_sum(self.weights.len())
+    }
+
+    /// 在末尾追加一个元素，返回它的下标
+    pub fn push(&mut self, weight: f64) -> Result<usize, InvalidWeights> {
+        if !is_valid_weight(weight) {
+            return Err(InvalidWeights);
+        }
+        let index = self.weights.len() + 1;
+        // 新节点负责的区间是 (index - lowbit(index), index]，其中除自己以外的部分已经存在
+        let node = weight + self.prefix_sum(index - 1) - self.prefix_sum(index - lowbit(index));
+        self.weights.push(weight);
+        self.tree.push(node);
+        Ok(index - 1)
+    }
+
+    /// 修改第 index 个元素的权重，O(log n)，设置为 0 相当于把它移出抽样范围
+    pub fn update(&mut self, index: usize, weight: f64) -> Result<(), InvalidWeights> {
+        if index >= self.weights.len() || !is_valid_weight(weight) {
+            return Err(InvalidWeights);
+        }
+        let delta = weight - self.weights[index];
+        self.weights[index] = weight;
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += lowbit(i);
+        }
+        Ok(())
+    }
+
+    /// 按权重随机抽取一个下标，总权重为 0 时返回 None
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<usize> {
+        let total = self.total();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = rng.gen::<f64>() * total;
+
+        // 在树状数组上二分：找到第一个前缀和大于 target 的位置
+        let n = self.weights.len();
+        let mut pos = 0;
+        let mut step = n.next_power_of_two();
+        while step > 0 {
+            if pos + step <= n && self.tree[pos + step] <= target {
+                pos += step;
+                target -= self.tree[pos];
+            }
+            step >>= 1;
+        }
+
+        if pos < n && self.weights[pos] > 0.0 {
+            Some(pos)
+        } else {
+            // 浮点误差导致越过了末尾，退回到最后一个权重非 0 的元素
+            self.weights.iter().rposition(|&w| w > 0.0)
+        }
+    }
+
+    /// 前 count 个元素的权重之和
+    fn prefix_sum(&self, count: usize) -> f64 {
+        let mut sum = 0.0;
+        let mut i = count;
+        while i > 0 {
+            sum += self.tree[i];
+            i -= lowbit(i);
+        }
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const SAMPLES: usize = 100_000;
+
+    /// 计算卡方统计量，expected 为按权重算出的理论概率
+    fn chi_square(counts: &[usize], weights: &[f64]) -> f64 {
+        let total: f64 = weights.iter().sum();
+        counts
+            .iter()
+            .zip(weights)
+            .filter(|(_, &w)| w > 0.0)
+            .map(|(&observed, &w)| {
+                let expected = SAMPLES as f64 * w / total;
+                (observed as f64 - expected).powi(2) / expected
+            })
+            .sum()
+    }
+
+    // 自由度为 4 时，显著性水平 0.001 对应的卡方临界值
+    const CHI_SQUARE_DF4_P999: f64 = 18.467;
+
+    #[test]
+    fn alias_table_rejects_invalid_weights() {
+        assert_eq!(AliasTable::new(&[]).err(), Some(InvalidWeights));
+        assert_eq!(AliasTable::new(&[0.0, 0.0]).err(), Some(InvalidWeights));
+        assert_eq!(AliasTable::new(&[1.0, -1.0]).err(), Some(InvalidWeights));
+        assert_eq!(
+            AliasTable::new(&[1.0, f64::NAN]).err(),
+            Some(InvalidWeights)
+        );
+    }
+
+    #[test]
+    fn alias_table_columns_reproduce_weights() {
+        // 每列被选中的概率是 1/n，把每列拆给 i 和 alias[i] 后累加，应该恰好等于归一化的权重
+        let weights = [1.0, 2.0, 3.0, 4.0, 10.0];
+        let table = AliasTable::new(&weights).unwrap();
+        let n = weights.len() as f64;
+        let mut prob = vec![0.0; weights.len()];
+        for i in 0..weights.len() {
+            prob[i] += table.prob[i] / n;
+            prob[table.alias[i]] += (1.0 - table.prob[i]) / n;
+        }
+        for (p, w) in prob.iter().zip(weights) {
+            assert!((p - w / 20.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn alias_table_never_returns_zero_weight() {
+        let table = AliasTable::new(&[0.0, 3.0, 0.0, 1.0]).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..10_000 {
+            let i = table.sample(&mut rng);
+            assert!(i == 1 || i == 3);
+        }
+    }
+
+    #[test]
+    fn alias_table_frequencies_pass_chi_square() {
+        let weights = [1.0, 2.0, 3.0, 4.0, 10.0];
+        let table = AliasTable::new(&weights).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut counts = vec![0; weights.len()];
+        for _ in 0..SAMPLES {
+            counts[table.sample(&mut rng)] += 1;
+        }
+        let chi2 = chi_square(&counts, &weights);
+        assert!(
+            chi2 < CHI_SQUARE_DF4_P999,
+            "chi2 = {}, counts = {:?}",
+            chi2,
+            counts
+        );
+    }
+
+    #[test]
+    fn dynamic_sampler_push_matches_from_weights() {
+        let weights = [0.5, 1.5, 2.0, 0.0, 4.0, 1.0, 3.0];
+        let built = DynamicWeightedSampler::from_weights(&weights).unwrap();
+        let mut pushed = DynamicWeightedSampler::new();
+        for &w in &weights {
+            pushed.push(w).unwrap();
+        }
+        for count in 0..=weights.len() {
+            let expected: f64 = weights[..count].iter().sum();
+            assert!((built.prefix_sum(count) - expected).abs() < 1e-9);
+            assert!((pushed.prefix_sum(count) - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn dynamic_sampler_empty_or_zero_returns_none() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut sampler = DynamicWeightedSampler::new();
+        assert_eq!(sampler.sample(&mut rng), None);
+        sampler.push(2.0).unwrap();
+        assert_eq!(sampler.sample(&mut rng), Some(0));
+        sampler.update(0, 0.0).unwrap();
+        assert_eq!(sampler.sample(&mut rng), None);
+        assert_eq!(sampler.update(1, 1.0), Err(InvalidWeights));
+    }
+
+    #[test]
+    fn dynamic_sampler_frequencies_pass_chi_square_after_updates() {
+        let mut sampler = DynamicWeightedSampler::from_weights(&[5.0, 5.0, 5.0, 5.0, 5.0]).unwrap();
+        let weights = [1.0, 2.0, 3.0, 4.0, 10.0];
+        for (i, &w) in weights.iter().enumerate() {
+            sampler.update(i, w).unwrap();
+        }
+        assert!((sampler.total() - 20.0).abs() < 1e-9);
+
+        let mut rng = StdRng::seed_from_u64(2024);
+        let mut counts = vec![0; weights.len()];
+        for _ in 0..SAMPLES {
+            counts[sampler.sample(&mut rng).unwrap()] += 1;
+        }
+        let chi2 = chi_square(&counts, &weights);
+        assert!(
+            chi2 < CHI_SQUARE_DF4_P999,
+            "chi2 = {}, counts = {:?}",
+            chi2,
+            counts
+        );
+    }
+}
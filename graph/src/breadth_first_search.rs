@@ -1,5 +1,5 @@
-use std::collections::HashSet;
-use std::collections::VecDeque;
+use data_structure::bit_set::BitSet;
+use std::collections::{HashMap, VecDeque};
 
 /// Perform a breadth-first search on Graph `graph`.
 ///
@@ -12,30 +12,36 @@ use std::collections::VecDeque;
 /// # Returns
 ///
 /// If the target is found, an Optional vector is returned with the history
-/// of nodes visited as its contents.
+/// of nodes visited as its contents. Use [`shortest_path`] or [`Bfs`] to get
+/// the path from `root` to `target` instead.
 ///
 /// If the target is not found or there is no path from the root,
 /// `None` is returned.
 ///
 pub fn breadth_first_search(graph: &Graph, root: Node, target: Node) -> Option<Vec<u32>> {
-    let mut visited: HashSet<Node> = HashSet::new();
+    // A root outside the graph has no edges, it can only be its own target
+    let Some(root) = graph.index_of(root) else {
+        return (root == target).then(|| vec![root.value()]);
+    };
+    // visited is keyed on the dense node indices, like the bookkeeping in Bfs
+    let mut visited = BitSet::with_capacity(graph.labels.len());
     let mut history: Vec<u32> = Vec::new();
     let mut queue = VecDeque::new();
 
     visited.insert(root);
     queue.push_back(root);
     while let Some(currentnode) = queue.pop_front() {
-        history.push(currentnode.value());
+        let node = graph.labels[currentnode];
+        history.push(node.value());
 
         // If we reach the goal, return our travel history.
-        if currentnode == target {
+        if node == target {
             return Some(history);
         }
 
         // Check the neighboring nodes for any that we've not visited yet.
-        for neighbor in currentnode.neighbors(graph) {
-            if !visited.contains(&neighbor) {
-                visited.insert(neighbor);
+        for &neighbor in &graph.adjacency[currentnode] {
+            if visited.insert(neighbor) {
                 queue.push_back(neighbor);
             }
         }
@@ -45,23 +51,252 @@ pub fn breadth_first_search(graph: &Graph, root: Node, target: Node) -> Option<V
     None
 }
 
+/// Find a shortest path (fewest edges) from `root` to `target`.
+///
+/// # Returns
+///
+/// The values of the nodes on the path, starting with `root` and ending with
+/// `target`, or `None` if `target` is not reachable from `root`.
+///
+pub fn shortest_path(graph: &Graph, root: Node, target: Node) -> Option<Vec<u32>> {
+    let mut bfs = Bfs::new(graph, [root]);
+    let found = bfs.run_until(&[target])?;
+    let path = bfs.path_to(found)?;
+    Some(path.into_iter().map(|node| node.value()).collect())
+}
+
+/// A breadth-first search that can start from several sources at once.
+///
+/// The search records, for every node it reaches, the hop distance to the
+/// nearest source and the node it was discovered from, so shortest paths
+/// can be reconstructed afterwards. It can be driven in three ways:
+///
+/// - [`Bfs::run`] explores everything reachable from the sources.
+/// - [`Bfs::run_until`] stops as soon as one node of a target set is reached.
+/// - Iterating yields the nodes level by level: first the sources (distance
+///   0), then all nodes at distance 1, and so on. `bfs.take(k + 1)` visits
+///   exactly the nodes within `k` hops.
+///
+/// The three can be mixed; each continues from where the previous one stopped.
+///
+/// All bookkeeping is keyed on the dense index the graph assigns to every
+/// node, so it costs O(1) per node no matter how large the labels are.
+/// Sources and targets that are not part of the graph are ignored.
+///
+pub struct Bfs<'a> {
+    graph: &'a Graph,
+    // parent[i] is the index node i was discovered from; sources have none
+    parent: Vec<Option<usize>>,
+    // distance[i] is set as soon as node i is discovered, so it doubles as
+    // the visited set
+    distance: Vec<Option<usize>>,
+    // indices of nodes that have been discovered but whose neighbors are not
+    // expanded yet, in non-decreasing order of distance
+    queue: VecDeque<usize>,
+}
+
+impl<'a> Bfs<'a> {
+    /// Start a search from every node in `sources`. Duplicate sources are
+    /// ignored.
+    pub fn new(graph: &'a Graph, sources: impl IntoIterator<Item = Node>) -> Self {
+        let n = graph.labels.len();
+        let mut bfs = Bfs {
+            graph,
+            parent: vec![None; n],
+            distance: vec![None; n],
+            queue: VecDeque::new(),
+        };
+        for source in sources {
+            if let Some(index) = graph.index_of(source) {
+                if bfs.distance[index].is_none() {
+                    bfs.distance[index] = Some(0);
+                    bfs.queue.push_back(index);
+                }
+            }
+        }
+        bfs
+    }
+
+    /// Hop distance from the nearest source, if `node` has been reached.
+    pub fn distance(&self, node: Node) -> Option<usize> {
+        self.distance[self.graph.index_of(node)?]
+    }
+
+    /// The node `node` was discovered from. Sources and nodes that have not
+    /// been reached have no parent.
+    pub fn parent(&self, node: Node) -> Option<Node> {
+        let parent = self.parent[self.graph.index_of(node)?]?;
+        Some(self.graph.labels[parent])
+    }
+
+    /// The parent of every node reached so far, except the sources.
+    pub fn parents(&self) -> HashMap<Node, Node> {
+        self.parent
+            .iter()
+            .enumerate()
+            .filter_map(|(i, parent)| Some((self.graph.labels[i], self.graph.labels[(*parent)?])))
+            .collect()
+    }
+
+    /// The hop distance of every node reached so far.
+    pub fn distances(&self) -> HashMap<Node, usize> {
+        self.distance
+            .iter()
+            .enumerate()
+            .filter_map(|(i, distance)| Some((self.graph.labels[i], (*distance)?)))
+            .collect()
+    }
+
+    /// A shortest path from one of the sources to `node`, if `node` has been
+    /// reached. The path starts with the source and ends with `node`.
+    pub fn path_to(&self, node: Node) -> Option<Vec<Node>> {
+        let mut current = self.graph.index_of(node)?;
+        self.distance[current]?;
+        let mut path = vec![node];
+        while let Some(parent) = self.parent[current] {
+            path.push(self.graph.labels[parent]);
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Explore every node reachable from the sources.
+    pub fn run(&mut self) {
+        while let Some(index) = self.queue.pop_front() {
+            self.expand(index);
+        }
+    }
+
+    /// Explore until one of `targets` is reached and return it, or `None` if
+    /// none of them is reachable. The returned target has the smallest
+    /// distance among all targets; ties go to the one discovered first.
+    pub fn run_until(&mut self, targets: &[Node]) -> Option<Node> {
+        // A target may already have been reached, e.g. it is a source
+        if let Some(&found) = targets
+            .iter()
+            .filter(|&&node| self.distance(node).is_some())
+            .min_by_key(|&&node| self.distance(node))
+        {
+            return Some(found);
+        }
+
+        let is_target = targets
+            .iter()
+            .filter_map(|&node| self.graph.index_of(node))
+            .collect::<BitSet>();
+        while let Some(index) = self.queue.pop_front() {
+            let discovered = self.queue.len();
+            self.expand(index);
+            // Every node at a smaller distance is already known, so the first
+            // target discovered is a nearest one
+            if let Some(&found) = self
+                .queue
+                .range(discovered..)
+                .find(|&&index| is_target.contains(index))
+            {
+                return Some(self.graph.labels[found]);
+            }
+        }
+        None
+    }
+
+    // Discover the unvisited neighbors of the node at `index`
+    fn expand(&mut self, index: usize) {
+        let next_distance = self.distance[index].unwrap() + 1;
+        for &neighbor in &self.graph.adjacency[index] {
+            if self.distance[neighbor].is_none() {
+                self.parent[neighbor] = Some(index);
+                self.distance[neighbor] = Some(next_distance);
+                self.queue.push_back(neighbor);
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Bfs<'a> {
+    type Item = Vec<Node>;
+
+    /// Expand and return the next level: all remaining nodes that share the
+    /// smallest unexpanded distance.
+    fn next(&mut self) -> Option<Self::Item> {
+        let depth = self.distance[*self.queue.front()?];
+        let mut level = Vec::new();
+        while let Some(&index) = self.queue.front() {
+            if self.distance[index] != depth {
+                break;
+            }
+            self.queue.pop_front();
+            self.expand(index);
+            level.push(self.graph.labels[index]);
+        }
+        Some(level)
+    }
+}
+
 // Data Structures
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Node(u32);
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Edge(u32, u32);
 
+/// A directed graph. Every node, including edge endpoints missing from the
+/// node list, gets a dense index when the graph is built, and the outgoing
+/// edges are stored per index, so looking up the neighbors of a node is O(1).
 #[derive(Clone)]
 pub struct Graph {
     nodes: Vec<Node>,
-    edges: Vec<Edge>,
+    // labels[i] is the node with dense index i
+    labels: Vec<Node>,
+    index: HashMap<Node, usize>,
+    // adjacency[i] holds the indices of the targets of the edges leaving i
+    adjacency: Vec<Vec<usize>>,
 }
 
 impl Graph {
     pub fn new(nodes: Vec<Node>, edges: Vec<Edge>) -> Self {
-        Graph { nodes, edges }
+        let mut graph = Graph {
+            nodes: Vec::new(),
+            labels: Vec::new(),
+            index: HashMap::new(),
+            adjacency: Vec::new(),
+        };
+        for &node in &nodes {
+            graph.intern(node);
+        }
+        for edge in edges {
+            let from = graph.intern(edge.0.into());
+            let to = graph.intern(edge.1.into());
+            graph.adjacency[from].push(to);
+        }
+        graph.nodes = nodes;
+        graph
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// The targets of the edges leaving `node`, in the order the edges were
+    /// given.
+    pub fn neighbors(&self, node: Node) -> impl ExactSizeIterator<Item = Node> + '_ {
+        let adjacency = self.index_of(node).map_or(&[][..], |i| &self.adjacency[i]);
+        adjacency.iter().map(|&i| self.labels[i])
+    }
+
+    fn index_of(&self, node: Node) -> Option<usize> {
+        self.index.get(&node).copied()
+    }
+
+    // The dense index of `node`, assigning the next free one if it is new
+    fn intern(&mut self, node: Node) -> usize {
+        *self.index.entry(node).or_insert_with(|| {
+            self.labels.push(node);
+            self.adjacency.push(Vec::new());
+            self.labels.len() - 1
+        })
     }
 }
 
@@ -77,12 +312,7 @@ impl Node {
     }
 
     pub fn neighbors(&self, graph: &Graph) -> Vec<Node> {
-        graph
-            .edges
-            .iter()
-            .filter(|e| e.0 == self.0)
-            .map(|e| e.1.into())
-            .collect()
+        graph.neighbors(*self).collect()
     }
 }
 
@@ -200,4 +430,141 @@ mod tests {
             Some(expected_path)
         );
     }
+
+    fn nodes(values: &[u32]) -> Vec<Node> {
+        values.iter().map(|&v| v.into()).collect()
+    }
+
+    #[test]
+    fn graph_indexes_neighbors_in_edge_order() {
+        let graph = graph1();
+        assert!(graph.neighbors(2.into()).eq(nodes(&[4, 5])));
+        assert_eq!(graph.neighbors(8.into()).len(), 0);
+        assert_eq!(graph.neighbors(10.into()).len(), 0);
+        assert_eq!(Node::from(3).neighbors(&graph), nodes(&[6, 7]));
+        assert_eq!(graph.nodes().len(), 7);
+    }
+
+    #[test]
+    fn shortest_path_returns_path_instead_of_history() {
+        assert_eq!(
+            shortest_path(&graph1(), 1.into(), 8.into()),
+            Some(vec![1, 2, 5, 8])
+        );
+        assert_eq!(
+            shortest_path(&graph2(), 4.into(), 1.into()),
+            Some(vec![4, 3, 6, 2, 1])
+        );
+        assert_eq!(shortest_path(&graph2(), 5.into(), 5.into()), Some(vec![5]));
+        assert_eq!(shortest_path(&graph2(), 8.into(), 4.into()), None);
+    }
+
+    #[test]
+    fn bfs_records_parents_and_distances() {
+        let graph = graph1();
+        let mut bfs = Bfs::new(&graph, [1.into()]);
+        bfs.run();
+
+        assert_eq!(bfs.distances().len(), 8);
+        assert_eq!(bfs.distance(8.into()), Some(3));
+        assert_eq!(bfs.distance(1.into()), Some(0));
+        assert_eq!(bfs.parent(6.into()), Some(3.into()));
+        assert_eq!(bfs.parent(1.into()), None);
+        assert_eq!(bfs.parents().len(), 7);
+        assert_eq!(bfs.path_to(7.into()), Some(nodes(&[1, 3, 7])));
+        assert_eq!(bfs.path_to(10.into()), None);
+    }
+
+    #[test]
+    fn bfs_from_multiple_sources_uses_nearest_source() {
+        let graph = graph2();
+        let mut bfs = Bfs::new(&graph, nodes(&[1, 4, 1]));
+        bfs.run();
+
+        assert_eq!(bfs.distance(1.into()), Some(0));
+        assert_eq!(bfs.distance(4.into()), Some(0));
+        assert_eq!(bfs.distance(5.into()), Some(2));
+        assert_eq!(bfs.distance(6.into()), Some(2));
+        assert_eq!(bfs.path_to(5.into()), Some(nodes(&[1, 2, 5])));
+        assert_eq!(bfs.path_to(6.into()), Some(nodes(&[1, 2, 6])));
+        assert_eq!(bfs.path_to(7.into()), Some(nodes(&[4, 7])));
+        assert_eq!(bfs.distance(8.into()), None);
+    }
+
+    #[test]
+    fn bfs_run_until_stops_at_nearest_target() {
+        let graph = graph1();
+        let mut bfs = Bfs::new(&graph, [1.into()]);
+        assert_eq!(bfs.run_until(&nodes(&[8, 3])), Some(3.into()));
+        // the search stopped before reaching the deeper levels
+        assert_eq!(bfs.distance(8.into()), None);
+        assert_eq!(bfs.path_to(3.into()), Some(nodes(&[1, 3])));
+
+        // continuing finds the next target
+        assert_eq!(bfs.run_until(&nodes(&[8])), Some(8.into()));
+        assert_eq!(bfs.run_until(&nodes(&[10])), None);
+
+        let mut bfs = Bfs::new(&graph, nodes(&[2, 3]));
+        assert_eq!(bfs.run_until(&nodes(&[6, 3])), Some(3.into()));
+    }
+
+    #[test]
+    fn bfs_iterates_level_by_level() {
+        let graph = graph1();
+        let levels: Vec<Vec<Node>> = Bfs::new(&graph, [1.into()]).collect();
+        assert_eq!(
+            levels,
+            vec![
+                nodes(&[1]),
+                nodes(&[2, 3]),
+                nodes(&[4, 5, 6, 7]),
+                nodes(&[8])
+            ]
+        );
+
+        // friends within 1 hop of 6 in the undirected graph #2, excluding 6 itself
+        let graph = graph2();
+        let mut friends: Vec<Node> = Bfs::new(&graph, [6.into()])
+            .take(2)
+            .flatten()
+            .skip(1)
+            .collect();
+        friends.sort_by_key(|node| node.value());
+        assert_eq!(friends, nodes(&[2, 3, 7]));
+
+        // levels continue from where run_until stopped
+        let mut bfs = Bfs::new(&graph, [1.into()]);
+        assert_eq!(bfs.run_until(&nodes(&[2])), Some(2.into()));
+        assert_eq!(bfs.next(), Some(nodes(&[2])));
+        assert_eq!(bfs.next(), Some(nodes(&[5, 6])));
+    }
+
+    #[test]
+    fn bfs_ignores_nodes_outside_the_graph() {
+        let graph = graph1();
+        let mut bfs = Bfs::new(&graph, nodes(&[u32::MAX, 1]));
+        assert_eq!(bfs.run_until(&nodes(&[u32::MAX])), None);
+        assert_eq!(bfs.distance(u32::MAX.into()), None);
+        assert_eq!(bfs.parent(u32::MAX.into()), None);
+        assert_eq!(bfs.distances().len(), 8);
+
+        let big = 4_000_000_000;
+        let graph = Graph::new(nodes(&[big]), vec![(big, u32::MAX).into()]);
+        let mut bfs = Bfs::new(&graph, [big.into()]);
+        assert_eq!(bfs.run_until(&nodes(&[u32::MAX])), Some(u32::MAX.into()));
+        assert_eq!(bfs.path_to(u32::MAX.into()), Some(nodes(&[big, u32::MAX])));
+    }
+
+    #[test]
+    fn breadth_first_search_handles_large_node_labels() {
+        let big = 4_000_000_000;
+        let graph = Graph::new(
+            nodes(&[1, big, u32::MAX]),
+            vec![(1, big).into(), (big, u32::MAX).into()],
+        );
+        assert_eq!(
+            breadth_first_search(&graph, 1.into(), u32::MAX.into()),
+            Some(vec![1, big, u32::MAX])
+        );
+    }
 }
@@ -0,0 +1,1243 @@
+/*
+    图的导入与导出
+
+    支持四种文本格式：
+    - Graphviz DOT：`digraph { "a" -> "b" [weight=5]; }`，无向图使用 `graph` 和 `--`
+    - 边列表：每行 `u v w`，只有一个字段的行表示孤立的节点，`#` 开头的行是注释
+    - 邻接矩阵：n 行 n 列，`-` 表示没有边，节点编号为 0..n
+    - DIMACS 最短路格式：`p sp n m` 加上 m 行 `a u v w`，节点编号为 1..=n
+
+    所有格式都先转换成中间表示 EdgeList，再由 EdgeList 与
+    `data_structure::graph` 中的 DirectedGraph/UndirectedGraph
+    以及 `dijkstra::Graph<V, E>` 互相转换。
+*/
+use crate::dijkstra;
+use data_structure::graph::Graph as AdjacencyGraph;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::ops::Add;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphIoError {
+    /// 输入不符合格式，行号和列号都从 1 开始
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// 图无法用目标格式表示，例如邻接矩阵中的平行边
+    Unrepresentable(String),
+}
+
+impl Display for GraphIoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphIoError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+            GraphIoError::Unrepresentable(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+fn parse_error<T>(
+    line: usize,
+    column: usize,
+    message: impl Into<String>,
+) -> Result<T, GraphIoError> {
+    Err(GraphIoError::Parse {
+        line,
+        column,
+        message: message.into(),
+    })
+}
+
+/// 与具体存储方式无关的图：节点列表（有序、无重复）、边列表以及是否有向
+///
+/// 无向图中每条边只出现一次。
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeList<N, W> {
+    pub nodes: Vec<N>,
+    pub edges: Vec<(N, N, W)>,
+    pub directed: bool,
+}
+
+impl<N, W> EdgeList<N, W>
+where
+    N: Ord + Clone,
+    W: Clone,
+{
+    /// 根据边构造 EdgeList，节点取边的所有端点再加上 extra_nodes
+    pub fn new(
+        extra_nodes: impl IntoIterator<Item = N>,
+        edges: Vec<(N, N, W)>,
+        directed: bool,
+    ) -> Self {
+        let mut nodes: BTreeSet<N> = extra_nodes.into_iter().collect();
+        for (from, to, _) in &edges {
+            nodes.insert(from.clone());
+            nodes.insert(to.clone());
+        }
+        EdgeList {
+            nodes: nodes.into_iter().collect(),
+            edges,
+            directed,
+        }
+    }
+
+    /// 从 `data_structure::graph` 中的图导出，边按 (from, to) 排序
+    pub fn from_graph<G>(graph: &G) -> Self
+    where
+        G: AdjacencyGraph<N, W>,
+        N: Hash,
+        W: Add<Output = W>,
+    {
+        let directed = graph.is_directed();
+        let mut edges: Vec<(N, N, W)> = Vec::new();
+        for (from, to, weight) in graph.edges() {
+            // 无向图的邻接表中每条边存了两个方向，只保留 from <= to 的那一个
+            if directed || from <= to {
+                edges.push((from.clone(), to.clone(), weight));
+            }
+        }
+        edges.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        let nodes: Vec<N> = graph.nodes().into_iter().cloned().collect();
+        Self::new(nodes, edges, directed)
+    }
+
+    /// 导入到 `data_structure::graph` 中的图，边的方向和平行边的处理方式由 G 决定
+    pub fn into_graph<G>(self) -> G
+    where
+        G: AdjacencyGraph<N, W>,
+        N: Hash,
+        W: Add<Output = W>,
+    {
+        let mut graph = G::new();
+        for node in self.nodes {
+            graph.add_node(node);
+        }
+        for edge in self.edges {
+            graph.add_edge(edge);
+        }
+        graph
+    }
+
+    /// 从 `dijkstra::Graph` 导出，得到的总是有向图
+    pub fn from_dijkstra(graph: &dijkstra::Graph<N, W>) -> Self {
+        let edges = graph
+            .iter()
+            .flat_map(|(from, neighbours)| {
+                neighbours
+                    .iter()
+                    .map(move |(to, weight)| (from.clone(), to.clone(), weight.clone()))
+            })
+            .collect();
+        Self::new(graph.keys().cloned(), edges, true)
+    }
+
+    /// 导入到 `dijkstra::Graph`，无向边会变成两个方向的有向边，平行边只保留最后一条
+    pub fn into_dijkstra(self) -> dijkstra::Graph<N, W> {
+        let mut graph: dijkstra::Graph<N, W> = BTreeMap::new();
+        for node in self.nodes {
+            graph.entry(node).or_default();
+        }
+        for (from, to, weight) in self.edges {
+            if !self.directed {
+                graph
+                    .entry(to.clone())
+                    .or_default()
+                    .insert(from.clone(), weight.clone());
+            }
+            graph.entry(from).or_default().insert(to, weight);
+        }
+        graph
+    }
+
+    /// 把节点重新编号为 base, base + 1, ...，同时返回编号到原节点的映射
+    ///
+    /// 邻接矩阵（base = 0）和 DIMACS（base = 1）只支持整数节点，可以先用它转换。
+    /// nodes 中有重复的节点，或者某条边的端点不在 nodes 中时返回 Unrepresentable。
+    pub fn to_indexed(&self, base: usize) -> Result<(EdgeList<usize, W>, Vec<N>), GraphIoError> {
+        let mut ids: BTreeMap<&N, usize> = BTreeMap::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            if ids.insert(node, base + i).is_some() {
+                return Err(GraphIoError::Unrepresentable(format!(
+                    "node {} appears more than once in the node list",
+                    i
+                )));
+            }
+        }
+        let mut edges = Vec::with_capacity(self.edges.len());
+        for (i, (from, to, weight)) in self.edges.iter().enumerate() {
+            match (ids.get(from), ids.get(to)) {
+                (Some(&from), Some(&to)) => edges.push((from, to, weight.clone())),
+                _ => {
+                    return Err(GraphIoError::Unrepresentable(format!(
+                        "edge {} has an endpoint that is not in the node list",
+                        i
+                    )))
+                }
+            }
+        }
+        let indexed = EdgeList {
+            nodes: (base..base + self.nodes.len()).collect(),
+            edges,
+            directed: self.directed,
+        };
+        Ok((indexed, self.nodes.clone()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// 边列表
+// ---------------------------------------------------------------------------
+
+/// 在一行中切分出以空白分隔的字段，同时记录每个字段的列号（从 1 开始，按字符计）
+fn fields(line: &str) -> Vec<(usize, &str)> {
+    let mut result = Vec::new();
+    let mut start = None;
+    for (column, (offset, c)) in line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((column + 1, offset)),
+            (true, Some((col, begin))) => {
+                result.push((col, &line[begin..offset]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((col, begin)) = start {
+        result.push((col, &line[begin..]));
+    }
+    result
+}
+
+/// 去掉注释和空行后的 (行号, 字段)
+fn content_lines<'a>(
+    input: &'a str,
+    comment: &'a str,
+) -> impl Iterator<Item = (usize, Vec<(usize, &'a str)>)> + 'a {
+    input
+        .lines()
+        .enumerate()
+        .filter(move |(_, line)| !line.trim_start().starts_with(comment))
+        .map(|(i, line)| (i + 1, fields(line)))
+        .filter(|(_, fields)| !fields.is_empty())
+}
+
+fn parse_field<T: FromStr>(
+    line: usize,
+    field: (usize, &str),
+    what: &str,
+) -> Result<T, GraphIoError> {
+    match field.1.parse() {
+        Ok(value) => Ok(value),
+        Err(_) => parse_error(line, field.0, format!("invalid {} `{}`", what, field.1)),
+    }
+}
+
+/// 读取边列表，每行 `u v w` 表示一条边，只有一个字段的行表示一个节点
+pub fn read_edge_list<N, W>(input: &str, directed: bool) -> Result<EdgeList<N, W>, GraphIoError>
+where
+    N: FromStr + Ord + Clone,
+    W: FromStr + Clone,
+{
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for (line, fields) in content_lines(input, "#") {
+        match fields.len() {
+            1 => nodes.push(parse_field(line, fields[0], "node")?),
+            3 => edges.push((
+                parse_field(line, fields[0], "node")?,
+                parse_field(line, fields[1], "node")?,
+                parse_field(line, fields[2], "weight")?,
+            )),
+            2 => {
+                return parse_error(
+                    line,
+                    fields[1].0 + fields[1].1.chars().count(),
+                    "missing weight",
+                )
+            }
+            _ => return parse_error(line, fields[3].0, "expected `from to weight`"),
+        }
+    }
+    Ok(EdgeList::new(nodes, edges, directed))
+}
+
+fn check_token<T: Display>(value: &T, what: &str) -> Result<String, GraphIoError> {
+    let text = value.to_string();
+    if text.is_empty() || text.starts_with('#') || text.contains(char::is_whitespace) {
+        return Err(GraphIoError::Unrepresentable(format!(
+            "{} `{}` cannot be written as a single whitespace-free field",
+            what, text
+        )));
+    }
+    Ok(text)
+}
+
+/// 写出边列表，孤立的节点单独占一行
+pub fn write_edge_list<N, W>(list: &EdgeList<N, W>) -> Result<String, GraphIoError>
+where
+    N: Display + Ord,
+    W: Display,
+{
+    let mut out = String::new();
+    let mut connected = BTreeSet::new();
+    for (from, to, weight) in &list.edges {
+        out.push_str(&format!(
+            "{} {} {}\n",
+            check_token(from, "node")?,
+            check_token(to, "node")?,
+            check_token(weight, "weight")?
+        ));
+        connected.insert(from);
+        connected.insert(to);
+    }
+    for node in &list.nodes {
+        if !connected.contains(node) {
+            out.push_str(&format!("{}\n", check_token(node, "node")?));
+        }
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// 邻接矩阵
+// ---------------------------------------------------------------------------
+
+type MatrixRow<W> = (usize, Vec<(usize, Option<W>)>);
+
+/// 读取邻接矩阵，第 i 行第 j 列是边 i -> j 的权重，`-` 表示没有边
+///
+/// 无向图要求矩阵对称，只读取上三角部分。
+pub fn read_adjacency_matrix<W>(
+    input: &str,
+    directed: bool,
+) -> Result<EdgeList<usize, W>, GraphIoError>
+where
+    W: FromStr + Clone + PartialEq,
+{
+    // 每一行记录 (行号, [(列号, 权重)])
+    let mut rows: Vec<MatrixRow<W>> = Vec::new();
+    for (line, fields) in content_lines(input, "#") {
+        if let Some((_, first)) = rows.first() {
+            if fields.len() != first.len() {
+                let column = fields.get(first.len()).map_or_else(
+                    || fields.last().map_or(1, |f| f.0 + f.1.chars().count()),
+                    |f| f.0,
+                );
+                return parse_error(
+                    line,
+                    column,
+                    format!("expected {} columns, found {}", first.len(), fields.len()),
+                );
+            }
+        }
+        let mut row = Vec::with_capacity(fields.len());
+        for field in fields {
+            let weight = match field.1 {
+                "-" => None,
+                _ => Some(parse_field(line, field, "weight")?),
+            };
+            row.push((field.0, weight));
+        }
+        rows.push((line, row));
+    }
+
+    let n = rows.len();
+    if let Some((line, row)) = rows.last() {
+        if row.len() != n {
+            return parse_error(
+                *line,
+                1,
+                format!("matrix has {} rows but {} columns", n, row.len()),
+            );
+        }
+    }
+
+    let mut edges = Vec::new();
+    for (i, (line, row)) in rows.iter().enumerate() {
+        for (j, (column, weight)) in row.iter().enumerate() {
+            if !directed && rows[j].1[i].1 != *weight {
+                return parse_error(
+                    *line,
+                    *column,
+                    "matrix of an undirected graph must be symmetric",
+                );
+            }
+            if let Some(weight) = weight {
+                if directed || i <= j {
+                    edges.push((i, j, weight.clone()));
+                }
+            }
+        }
+    }
+    Ok(EdgeList::new(0..n, edges, directed))
+}
+
+/// 写出邻接矩阵，要求节点恰好是 0..n 且没有平行边
+pub fn write_adjacency_matrix<W>(list: &EdgeList<usize, W>) -> Result<String, GraphIoError>
+where
+    W: Display,
+{
+    let n = list.nodes.len();
+    if list.nodes.iter().enumerate().any(|(i, &node)| i != node) {
+        return Err(GraphIoError::Unrepresentable(
+            "adjacency matrix nodes must be exactly 0..n".to_string(),
+        ));
+    }
+    let mut matrix: Vec<Vec<Option<String>>> = vec![vec![None; n]; n];
+    for (from, to, weight) in &list.edges {
+        let text = check_token(weight, "weight")?;
+        let mut cells = vec![(*from, *to)];
+        if !list.directed && from != to {
+            cells.push((*to, *from));
+        }
+        for (i, j) in cells {
+            if matrix[i][j].replace(text.clone()).is_some() {
+                return Err(GraphIoError::Unrepresentable(format!(
+                    "parallel edges between {} and {} cannot be stored in an adjacency matrix",
+                    from, to
+                )));
+            }
+        }
+    }
+    let mut out = String::new();
+    for row in matrix {
+        let cells: Vec<String> = row
+            .into_iter()
+            .map(|c| c.unwrap_or_else(|| "-".to_string()))
+            .collect();
+        out.push_str(&cells.join(" "));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// DIMACS
+// ---------------------------------------------------------------------------
+
+/// 读取 DIMACS 最短路格式（`c` 注释行、一行 `p sp n m`、m 行 `a u v w`），得到有向图
+pub fn read_dimacs<W>(input: &str) -> Result<EdgeList<usize, W>, GraphIoError>
+where
+    W: FromStr + Clone,
+{
+    let mut problem: Option<(usize, usize, usize)> = None; // (行号, n, m)
+    let mut edges = Vec::new();
+    for (line, fields) in content_lines(input, "c") {
+        match fields[0].1 {
+            "p" => {
+                if problem.is_some() {
+                    return parse_error(line, fields[0].0, "duplicate problem line");
+                }
+                if fields.len() != 4 || fields[1].1 != "sp" {
+                    return parse_error(line, fields[0].0, "expected `p sp <nodes> <arcs>`");
+                }
+                let n = parse_field(line, fields[2], "node count")?;
+                let m = parse_field(line, fields[3], "arc count")?;
+                problem = Some((line, n, m));
+            }
+            "a" => {
+                let n = match problem {
+                    Some((_, n, _)) => n,
+                    None => return parse_error(line, fields[0].0, "arc before problem line"),
+                };
+                if fields.len() != 4 {
+                    return parse_error(line, fields[0].0, "expected `a <from> <to> <weight>`");
+                }
+                let endpoint = |field: (usize, &str)| -> Result<usize, GraphIoError> {
+                    let node: usize = parse_field(line, field, "node")?;
+                    if node == 0 || node > n {
+                        return parse_error(
+                            line,
+                            field.0,
+                            format!("node {} is out of range 1..={}", node, n),
+                        );
+                    }
+                    Ok(node)
+                };
+                let from = endpoint(fields[1])?;
+                let to = endpoint(fields[2])?;
+                edges.push((from, to, parse_field(line, fields[3], "weight")?));
+            }
+            other => {
+                return parse_error(line, fields[0].0, format!("unknown line type `{}`", other))
+            }
+        }
+    }
+    match problem {
+        None => parse_error(1, 1, "missing problem line"),
+        Some((line, _, m)) if m != edges.len() => parse_error(
+            line,
+            1,
+            format!(
+                "problem line declares {} arcs but {} were found",
+                m,
+                edges.len()
+            ),
+        ),
+        Some((_, n, _)) => Ok(EdgeList::new(1..=n, edges, true)),
+    }
+}
+
+/// 写出 DIMACS 最短路格式，要求节点恰好是 1..=n；无向边会写成两条弧
+pub fn write_dimacs<W>(list: &EdgeList<usize, W>) -> Result<String, GraphIoError>
+where
+    W: Display,
+{
+    if list
+        .nodes
+        .iter()
+        .enumerate()
+        .any(|(i, &node)| i + 1 != node)
+    {
+        return Err(GraphIoError::Unrepresentable(
+            "DIMACS nodes must be exactly 1..=n".to_string(),
+        ));
+    }
+    let mut arcs = Vec::new();
+    for (from, to, weight) in &list.edges {
+        let weight = check_token(weight, "weight")?;
+        if !list.directed && from != to {
+            arcs.push(format!("a {} {} {}\n", to, from, weight));
+        }
+        arcs.push(format!("a {} {} {}\n", from, to, weight));
+    }
+    let mut out = format!("p sp {} {}\n", list.nodes.len(), arcs.len());
+    out.extend(arcs);
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// DOT
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    // quoted 记录 ID 是否带引号，带引号的 `"node"` 是普通 ID 而不是关键字
+    Id { text: String, quoted: bool },
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    Semicolon,
+    Comma,
+    Equals,
+    Colon,
+    Edge(bool), // true 表示 `->`，false 表示 `--`
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Lexer {
+            chars: input.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), GraphIoError> {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('#') => self.skip_line(),
+                Some('/') => {
+                    let (line, column) = (self.line, self.column);
+                    self.bump();
+                    match self.bump() {
+                        Some('/') => self.skip_line(),
+                        Some('*') => {
+                            let mut previous = ' ';
+                            loop {
+                                match self.bump() {
+                                    Some('/') if previous == '*' => break,
+                                    Some(c) => previous = c,
+                                    None => {
+                                        return parse_error(line, column, "unterminated comment")
+                                    }
+                                }
+                            }
+                        }
+                        _ => return parse_error(line, column, "unexpected `/`"),
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while let Some(c) = self.bump() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+
+    /// 返回下一个 token 及其起始位置
+    fn next_token(&mut self) -> Result<Option<(Token, usize, usize)>, GraphIoError> {
+        self.skip_whitespace_and_comments()?;
+        let (line, column) = (self.line, self.column);
+        let c = match self.bump() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+        let token = match c {
+            '{' => Token::LBrace,
+            '}' => Token::RBrace,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            ';' => Token::Semicolon,
+            ',' => Token::Comma,
+            '=' => Token::Equals,
+            ':' => Token::Colon,
+            '-' if self.chars.peek() == Some(&'>') => {
+                self.bump();
+                Token::Edge(true)
+            }
+            '-' if self.chars.peek() == Some(&'-') => {
+                self.bump();
+                Token::Edge(false)
+            }
+            '"' => {
+                let mut text = String::new();
+                loop {
+                    match self.bump() {
+                        Some('"') => break,
+                        Some('\\') => match self.bump() {
+                            Some('"') => text.push('"'),
+                            Some('\\') => text.push('\\'),
+                            Some('\n') => {}
+                            Some(other) => {
+                                text.push('\\');
+                                text.push(other);
+                            }
+                            None => return parse_error(line, column, "unterminated string"),
+                        },
+                        Some(other) => text.push(other),
+                        None => return parse_error(line, column, "unterminated string"),
+                    }
+                }
+                Token::Id { text, quoted: true }
+            }
+            c if is_id_char(c) || c == '-' || c == '.' => {
+                let mut text = c.to_string();
+                while let Some(&next) = self.chars.peek() {
+                    if is_id_char(next) || next == '.' {
+                        text.push(next);
+                        self.bump();
+                    } else {
+                        break;
+                    }
+                }
+                Token::Id {
+                    text,
+                    quoted: false,
+                }
+            }
+            other => return parse_error(line, column, format!("unexpected character `{}`", other)),
+        };
+        Ok(Some((token, line, column)))
+    }
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || !c.is_ascii()
+}
+
+struct DotParser<'a> {
+    lexer: Lexer<'a>,
+    peeked: Option<(Token, usize, usize)>,
+}
+
+impl<'a> DotParser<'a> {
+    fn peek(&mut self) -> Result<Option<&Token>, GraphIoError> {
+        if self.peeked.is_none() {
+            self.peeked = self.lexer.next_token()?;
+        }
+        Ok(self.peeked.as_ref().map(|(token, _, _)| token))
+    }
+
+    fn next(&mut self) -> Result<(Token, usize, usize), GraphIoError> {
+        self.peek()?;
+        match self.peeked.take() {
+            Some(token) => Ok(token),
+            None => parse_error(
+                self.lexer.line,
+                self.lexer.column,
+                "unexpected end of input",
+            ),
+        }
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), GraphIoError> {
+        let (token, line, column) = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            parse_error(line, column, format!("expected {}", what))
+        }
+    }
+
+    fn expect_id(&mut self) -> Result<(String, usize, usize), GraphIoError> {
+        match self.next()? {
+            (Token::Id { text, .. }, line, column) => Ok((text, line, column)),
+            (_, line, column) => parse_error(line, column, "expected an identifier"),
+        }
+    }
+
+    /// 解析零个或多个 `[a=b, c=d]`，返回 (key, value, 行, 列)
+    fn attributes(&mut self) -> Result<Vec<(String, String, usize, usize)>, GraphIoError> {
+        let mut attributes = Vec::new();
+        while self.peek()? == Some(&Token::LBracket) {
+            self.next()?;
+            loop {
+                match self.next()? {
+                    (Token::RBracket, _, _) => break,
+                    (Token::Comma, _, _) | (Token::Semicolon, _, _) => {}
+                    (Token::Id { text: key, .. }, _, _) => {
+                        self.expect(Token::Equals, "`=`")?;
+                        let (value, line, column) = self.expect_id()?;
+                        attributes.push((key, value, line, column));
+                    }
+                    (_, line, column) => return parse_error(line, column, "expected an attribute"),
+                }
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// 解析节点 ID，忽略端口（`a:port`）
+    fn node_id(&mut self) -> Result<(String, usize, usize), GraphIoError> {
+        let id = self.expect_id()?;
+        while self.peek()? == Some(&Token::Colon) {
+            self.next()?;
+            self.expect_id()?;
+        }
+        Ok(id)
+    }
+}
+
+fn is_keyword(text: &str, keyword: &str) -> bool {
+    text.eq_ignore_ascii_case(keyword)
+}
+
+impl Token {
+    /// 只有不带引号的 ID 才可能是关键字
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Id { text, quoted: false } if is_keyword(text, keyword))
+    }
+}
+
+/// 读取 DOT 格式的图，边权取自 `weight` 属性（没有时取 `label`）
+///
+/// 支持节点语句、边语句（包括 `a -> b -> c` 这样的链）、`edge [weight=...]` 默认属性以及注释，
+/// 不支持子图。缺少权重的边使用 `default_weight`，为 None 时视为错误。
+pub fn read_dot<N, W>(
+    input: &str,
+    default_weight: Option<W>,
+) -> Result<EdgeList<N, W>, GraphIoError>
+where
+    N: FromStr + Ord + Clone,
+    W: FromStr + Clone,
+{
+    let mut parser = DotParser {
+        lexer: Lexer::new(input),
+        peeked: None,
+    };
+
+    let (mut keyword, mut line, mut column) = parser.next()?;
+    if keyword.is_keyword("strict") {
+        (keyword, line, column) = parser.next()?;
+    }
+    let directed = if keyword.is_keyword("digraph") {
+        true
+    } else if keyword.is_keyword("graph") {
+        false
+    } else {
+        return parse_error(line, column, "expected `graph` or `digraph`");
+    };
+    if let Some(Token::Id { .. }) = parser.peek()? {
+        parser.next()?;
+    }
+    parser.expect(Token::LBrace, "`{`")?;
+
+    let parse_weight = |value: &str, line: usize, column: usize| -> Result<W, GraphIoError> {
+        value
+            .parse()
+            .or_else(|_| parse_error(line, column, format!("invalid weight `{}`", value)))
+    };
+    let parse_node = |value: &str, line: usize, column: usize| -> Result<N, GraphIoError> {
+        value
+            .parse()
+            .or_else(|_| parse_error(line, column, format!("invalid node `{}`", value)))
+    };
+    let weight_attribute = |attributes: &[(String, String, usize, usize)]| {
+        attributes
+            .iter()
+            .find(|a| a.0 == "weight")
+            .or_else(|| attributes.iter().find(|a| a.0 == "label"))
+            .cloned()
+    };
+
+    let mut default_weight = default_weight;
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    loop {
+        let (token, line, column) = parser.next()?;
+        if token.is_keyword("subgraph") {
+            return parse_error(line, column, "subgraphs are not supported");
+        }
+        if token.is_keyword("graph") || token.is_keyword("node") {
+            parser.attributes()?;
+            continue;
+        }
+        if token.is_keyword("edge") {
+            if let Some((_, value, l, c)) = weight_attribute(&parser.attributes()?) {
+                default_weight = Some(parse_weight(&value, l, c)?);
+            }
+            continue;
+        }
+        let text = match token {
+            Token::RBrace => break,
+            Token::Semicolon => continue,
+            Token::Id { text, .. } => text,
+            _ => return parse_error(line, column, "expected a statement"),
+        };
+        if parser.peek()? == Some(&Token::Equals) {
+            // 图属性 `key = value`
+            parser.next()?;
+            parser.expect_id()?;
+            continue;
+        }
+
+        // 跳过第一个节点上的端口
+        while parser.peek()? == Some(&Token::Colon) {
+            parser.next()?;
+            parser.expect_id()?;
+        }
+        let mut chain = vec![(text, line, column)];
+        while let Some(&Token::Edge(arrow)) = parser.peek()? {
+            let (_, l, c) = parser.next()?;
+            if arrow != directed {
+                let expected = if directed { "->" } else { "--" };
+                return parse_error(l, c, format!("expected `{}` in this graph", expected));
+            }
+            chain.push(parser.node_id()?);
+        }
+        let attributes = parser.attributes()?;
+
+        let mut ids = Vec::with_capacity(chain.len());
+        for (text, l, c) in &chain {
+            ids.push(parse_node(text, *l, *c)?);
+        }
+        if ids.len() == 1 {
+            nodes.push(ids.pop().unwrap());
+            continue;
+        }
+        let weight = match weight_attribute(&attributes) {
+            Some((_, value, l, c)) => parse_weight(&value, l, c)?,
+            None => match &default_weight {
+                Some(weight) => weight.clone(),
+                None => return parse_error(line, column, "edge has no weight attribute"),
+            },
+        };
+        for pair in ids.windows(2) {
+            edges.push((pair[0].clone(), pair[1].clone(), weight.clone()));
+        }
+    }
+    if let Some((_, line, column)) = parser.lexer.next_token()? {
+        return parse_error(line, column, "unexpected content after the closing `}`");
+    }
+    Ok(EdgeList::new(nodes, edges, directed))
+}
+
+/// 在需要时给 DOT 中的 ID 加上引号
+fn dot_id<T: Display>(value: &T) -> String {
+    let text = value.to_string();
+    let is_identifier = text
+        .chars()
+        .next()
+        .is_some_and(|c| !c.is_ascii_digit() && is_id_char(c))
+        && text.chars().all(is_id_char);
+    let is_numeral = text.parse::<f64>().is_ok()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == '-')
+        && !text[1..].contains('-');
+    let is_keyword = ["strict", "graph", "digraph", "node", "edge", "subgraph"]
+        .iter()
+        .any(|k| is_keyword(&text, k));
+    if (is_identifier || is_numeral) && !is_keyword {
+        text
+    } else {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// 写出 DOT 格式，先列出所有节点，再列出所有边
+pub fn write_dot<N, W>(list: &EdgeList<N, W>) -> String
+where
+    N: Display,
+    W: Display,
+{
+    let (keyword, edge_op) = if list.directed {
+        ("digraph", "->")
+    } else {
+        ("graph", "--")
+    };
+    let mut out = format!("{} {{\n", keyword);
+    for node in &list.nodes {
+        out.push_str(&format!("    {};\n", dot_id(node)));
+    }
+    for (from, to, weight) in &list.edges {
+        out.push_str(&format!(
+            "    {} {} {} [weight={}];\n",
+            dot_id(from),
+            edge_op,
+            dot_id(to),
+            dot_id(weight)
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_structure::graph::{DirectedGraph, Graph};
+    use data_structure::graph1::UndirectedGraph;
+
+    fn sample_directed() -> DirectedGraph<String, i32> {
+        let mut graph = DirectedGraph::new();
+        for (from, to, weight) in [("a", "b", 5), ("b", "c", -3), ("a", "c", 12), ("c", "a", 7)] {
+            graph.add_edge((from.to_string(), to.to_string(), weight));
+        }
+        graph.add_edge(("a".to_string(), "b".to_string(), 6));
+        graph.add_node("lonely node".to_string());
+        graph
+    }
+
+    fn sample_undirected() -> UndirectedGraph<u32, f64> {
+        let mut graph = UndirectedGraph::new();
+        graph.add_edge((1, 2, 0.5));
+        graph.add_edge((2, 3, 1.25));
+        graph.add_edge((3, 3, 2.0));
+        graph.add_node(7);
+        graph
+    }
+
+    #[test]
+    fn dot_round_trip_directed() {
+        let list = EdgeList::from_graph(&sample_directed());
+        let text = write_dot(&list);
+        assert!(text.starts_with("digraph {\n"));
+        assert!(text.contains("    \"lonely node\";\n"));
+        assert!(text.contains("    a -> b [weight=5];\n"));
+        assert_eq!(read_dot::<String, i32>(&text, None).unwrap(), list);
+    }
+
+    #[test]
+    fn dot_round_trip_undirected_into_graph() {
+        let list = EdgeList::from_graph(&sample_undirected());
+        assert_eq!(list.edges.len(), 3);
+        let text = write_dot(&list);
+        assert!(text.contains("    1 -- 2 [weight=0.5];\n"));
+        let parsed = read_dot::<u32, f64>(&text, None).unwrap();
+        assert_eq!(parsed, list);
+        let graph: UndirectedGraph<u32, f64> = parsed.into_graph();
+        assert_eq!(EdgeList::from_graph(&graph), list);
+    }
+
+    #[test]
+    fn dot_round_trip_keeps_nodes_named_like_keywords() {
+        let list = EdgeList::new(
+            ["node", "edge", "graph", "subgraph"].map(String::from),
+            vec![
+                ("edge".to_string(), "a".to_string(), 1),
+                ("graph".to_string(), "subgraph".to_string(), 2),
+            ],
+            true,
+        );
+        let text = write_dot(&list);
+        assert!(text.contains("    \"node\";\n"));
+        assert!(text.contains("    \"edge\" -> a [weight=1];\n"));
+        assert_eq!(read_dot::<String, i32>(&text, None).unwrap(), list);
+
+        // 带引号的图类型不是关键字
+        let err = read_dot::<String, i32>("\"digraph\" { a }", None).unwrap_err();
+        assert_eq!(
+            err,
+            GraphIoError::Parse {
+                line: 1,
+                column: 1,
+                message: "expected `graph` or `digraph`".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn dot_reader_handles_common_syntax() {
+        let text = r#"
+            /* a hand-written file */
+            strict digraph "roads" {
+                rankdir = LR;
+                node [shape=box]
+                edge [weight=1]
+                a -> b -> c   // a chain uses the default weight
+                c -> "d e" [color=red, label=9]
+                d:port -> a [weight = 4];
+                # preprocessor-style comment
+                z
+            }
+        "#;
+        let list = read_dot::<String, i32>(text, None).unwrap();
+        assert!(list.directed);
+        assert_eq!(list.nodes, vec!["a", "b", "c", "d", "d e", "z"]);
+        assert_eq!(
+            list.edges,
+            vec![
+                ("a".to_string(), "b".to_string(), 1),
+                ("b".to_string(), "c".to_string(), 1),
+                ("c".to_string(), "d e".to_string(), 9),
+                ("d".to_string(), "a".to_string(), 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn dot_errors_report_position() {
+        let err = read_dot::<String, i32>("digraph {\n  a -> b;\n}", None).unwrap_err();
+        assert_eq!(
+            err,
+            GraphIoError::Parse {
+                line: 2,
+                column: 3,
+                message: "edge has no weight attribute".to_string()
+            }
+        );
+
+        let err = read_dot::<String, i32>("graph {\n  a -> b [weight=1]\n}", None).unwrap_err();
+        assert!(matches!(
+            err,
+            GraphIoError::Parse {
+                line: 2,
+                column: 5,
+                ..
+            }
+        ));
+
+        let err = read_dot::<String, i32>("digraph {\n a -> b [weight=x]\n}", None).unwrap_err();
+        assert_eq!(err.to_string(), "line 2, column 17: invalid weight `x`");
+
+        let err = read_dot::<String, i32>("digraph {\n  a -> \"b\n", None).unwrap_err();
+        assert!(matches!(
+            err,
+            GraphIoError::Parse {
+                line: 2,
+                column: 8,
+                ..
+            }
+        ));
+
+        let err = read_dot::<String, i32>("digraph { a } extra", None).unwrap_err();
+        assert!(matches!(
+            err,
+            GraphIoError::Parse {
+                line: 1,
+                column: 15,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn edge_list_round_trip() {
+        let mut list = EdgeList::from_graph(&sample_directed());
+        // 边列表中节点名不能包含空白
+        list.nodes.retain(|node| node != "lonely node");
+        list.nodes.push("lonely".to_string());
+        let text = write_edge_list(&list).unwrap();
+        assert_eq!(text, "a b 5\na b 6\na c 12\nb c -3\nc a 7\nlonely\n");
+        assert_eq!(read_edge_list::<String, i32>(&text, true).unwrap(), list);
+
+        let undirected = EdgeList::from_graph(&sample_undirected());
+        let text = write_edge_list(&undirected).unwrap();
+        assert_eq!(
+            read_edge_list::<u32, f64>(&text, false).unwrap(),
+            undirected
+        );
+    }
+
+    #[test]
+    fn edge_list_errors() {
+        let list = EdgeList::from_graph(&sample_directed());
+        assert!(matches!(
+            write_edge_list(&list),
+            Err(GraphIoError::Unrepresentable(_))
+        ));
+
+        let err = read_edge_list::<u32, i32>("# header\n1 2 3\n1  x 4\n", true).unwrap_err();
+        assert_eq!(err.to_string(), "line 3, column 4: invalid node `x`");
+        let err = read_edge_list::<u32, i32>("1 2\n", true).unwrap_err();
+        assert!(matches!(
+            err,
+            GraphIoError::Parse {
+                line: 1,
+                column: 4,
+                ..
+            }
+        ));
+        let err = read_edge_list::<u32, i32>("1 2 3 4\n", true).unwrap_err();
+        assert!(matches!(
+            err,
+            GraphIoError::Parse {
+                line: 1,
+                column: 7,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn adjacency_matrix_round_trip() {
+        let (list, labels) = EdgeList::from_graph(&sample_undirected())
+            .to_indexed(0)
+            .unwrap();
+        assert_eq!(labels, vec![1, 2, 3, 7]);
+        let text = write_adjacency_matrix(&list).unwrap();
+        assert_eq!(text, "- 0.5 - -\n0.5 - 1.25 -\n- 1.25 2 -\n- - - -\n");
+        assert_eq!(read_adjacency_matrix::<f64>(&text, false).unwrap(), list);
+
+        let directed = read_adjacency_matrix::<i32>("- 1\n2 -\n", true).unwrap();
+        assert_eq!(directed.edges, vec![(0, 1, 1), (1, 0, 2)]);
+        assert_eq!(write_adjacency_matrix(&directed).unwrap(), "- 1\n2 -\n");
+    }
+
+    #[test]
+    fn adjacency_matrix_errors() {
+        let err = read_adjacency_matrix::<i32>("- 1\n1 - 3\n", true).unwrap_err();
+        assert!(matches!(
+            err,
+            GraphIoError::Parse {
+                line: 2,
+                column: 5,
+                ..
+            }
+        ));
+        let err = read_adjacency_matrix::<i32>("- 1\n2 -\n", false).unwrap_err();
+        assert!(matches!(
+            err,
+            GraphIoError::Parse {
+                line: 1,
+                column: 3,
+                ..
+            }
+        ));
+        let err = read_adjacency_matrix::<i32>("- 1 2\n2 - 3\n", true).unwrap_err();
+        assert!(matches!(err, GraphIoError::Parse { line: 2, .. }));
+
+        let (parallel, _) = EdgeList::from_graph(&sample_directed())
+            .to_indexed(0)
+            .unwrap();
+        assert!(matches!(
+            write_adjacency_matrix(&parallel),
+            Err(GraphIoError::Unrepresentable(_))
+        ));
+    }
+
+    #[test]
+    fn to_indexed_rejects_inconsistent_node_list() {
+        let mut list = EdgeList::new(Vec::new(), vec![("a", "b", 1), ("b", "c", 2)], true);
+        let (indexed, labels) = list.to_indexed(1).unwrap();
+        assert_eq!(indexed.edges, vec![(1, 2, 1), (2, 3, 2)]);
+        assert_eq!(labels, vec!["a", "b", "c"]);
+
+        // 字段是公开的，可以被改成与边不一致的样子
+        list.nodes.retain(|&node| node != "c");
+        assert!(matches!(
+            list.to_indexed(0),
+            Err(GraphIoError::Unrepresentable(_))
+        ));
+        list.nodes = vec!["b", "a", "b", "c"];
+        assert!(matches!(
+            list.to_indexed(0),
+            Err(GraphIoError::Unrepresentable(_))
+        ));
+
+        // 节点没有排序也能正确编号
+        list.nodes = vec!["c", "b", "a"];
+        let (indexed, _) = list.to_indexed(0).unwrap();
+        assert_eq!(indexed.edges, vec![(2, 1, 1), (1, 0, 2)]);
+    }
+
+    #[test]
+    fn dimacs_round_trip_with_dijkstra_graph() {
+        let text = "c sample\np sp 4 4\na 1 2 3\na 1 3 9\na 2 3 4\na 3 1 1\n";
+        let list = read_dimacs::<u64>(text).unwrap();
+        assert_eq!(list.nodes, vec![1, 2, 3, 4]);
+        assert_eq!(write_dimacs(&list).unwrap(), &text["c sample\n".len()..]);
+
+        let graph = list.clone().into_dijkstra();
+        let dists = dijkstra::dijkstra(&graph, &1);
+        assert_eq!(dists[&3], Some((2, 7)));
+        assert_eq!(EdgeList::from_dijkstra(&graph), list);
+    }
+
+    #[test]
+    fn dimacs_errors() {
+        let err = read_dimacs::<u64>("a 1 2 3\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 1: arc before problem line");
+        let err = read_dimacs::<u64>("p sp 2 1\na 1 3 3\n").unwrap_err();
+        assert!(matches!(
+            err,
+            GraphIoError::Parse {
+                line: 2,
+                column: 5,
+                ..
+            }
+        ));
+        let err = read_dimacs::<u64>("c x\np sp 2 2\na 1 2 3\n").unwrap_err();
+        assert!(matches!(
+            err,
+            GraphIoError::Parse {
+                line: 2,
+                column: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn undirected_list_into_dijkstra_adds_both_directions() {
+        let list = EdgeList::new(Vec::new(), vec![('a', 'b', 2), ('b', 'c', 3)], false);
+        let graph = list.into_dijkstra();
+        assert_eq!(dijkstra::dijkstra(&graph, &'c')[&'a'], Some(('b', 5)));
+    }
+}
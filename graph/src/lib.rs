@@ -1,6 +1,8 @@
 pub mod breadth_first_search;
 pub mod depth_first_search;
 pub mod dijkstra;
+pub mod graph_io;
+pub mod mst;
 mod simple_bfs;
 mod simple_dfs;
 
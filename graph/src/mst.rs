@@ -0,0 +1,181 @@
+/*
+    最小生成树（Minimum Spanning Tree）
+
+    在带权无向连通图中，选出 n - 1 条边把所有顶点连起来，并且边权之和最小。
+    如果图不连通，则对每个连通分量分别求最小生成树，合起来称为最小生成森林。
+
+    - Kruskal：把所有边按权重从小到大排序，依次尝试加入，用并查集判断这条边的
+      两个端点是否已经连通，连通则跳过（否则会成环）。复杂度 O(m log m)。
+    - Prim：从一个顶点出发，每次用优先队列取出连接“树内”和“树外”的最小边，
+      把树外的端点加入树中。复杂度 O(m log m)。
+*/
+use data_structure::heap::Heap;
+use std::ops::Add;
+use union_find::UnionFind;
+
+/// 最小生成森林：选中的边 (u, v, w) 以及它们的总权重
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanningForest<W> {
+    pub edges: Vec<(usize, usize, W)>,
+    pub total_weight: W,
+}
+
+/// 顶点编号为 0..n，edges 中的每条边 (u, v, w) 都是无向边。
+/// 顶点不是整数的图可以先用 `graph_io::EdgeList::to_indexed` 转换编号。
+pub fn kruskal<W>(n: usize, edges: &[(usize, usize, W)]) -> SpanningForest<W>
+where
+    W: Ord + Copy + Add<Output = W> + Default,
+{
+    let mut sorted: Vec<&(usize, usize, W)> = edges.iter().collect();
+    sorted.sort_by_key(|&&(_, _, w)| w);
+
+    let mut uf = UnionFind::new(n);
+    let mut forest = SpanningForest {
+        edges: Vec::with_capacity(n.saturating_sub(1)),
+        total_weight: W::default(),
+    };
+    for &&(u, v, w) in &sorted {
+        // 两端已经连通的边会成环，跳过
+        if uf.union(u, v) {
+            forest.edges.push((u, v, w));
+            forest.total_weight = forest.total_weight + w;
+            // 已经连通所有顶点，剩下的边不可能再被选中
+            if uf.num_components() == 1 {
+                break;
+            }
+        }
+    }
+    forest
+}
+
+/// 参数与 kruskal 相同。每个连通分量从其中编号最小的顶点开始生长
+pub fn prim<W>(n: usize, edges: &[(usize, usize, W)]) -> SpanningForest<W>
+where
+    W: Ord + Copy + Add<Output = W> + Default,
+{
+    let mut adjacency: Vec<Vec<(usize, W)>> = vec![Vec::new(); n];
+    for &(u, v, w) in edges {
+        adjacency[u].push((v, w));
+        adjacency[v].push((u, w));
+    }
+
+    let mut in_tree = vec![false; n];
+    let mut forest = SpanningForest {
+        edges: Vec::with_capacity(n.saturating_sub(1)),
+        total_weight: W::default(),
+    };
+    // 堆中的元素为 (边权, 树外端点, 树内端点)，按边权排序
+    let mut heap: Heap<(W, usize, usize)> = Heap::new_min();
+    for start in 0..n {
+        if in_tree[start] {
+            continue;
+        }
+        in_tree[start] = true;
+        for &(next, w) in &adjacency[start] {
+            heap.add((w, next, start));
+        }
+        while let Some((w, v, u)) = heap.pop() {
+            // 懒删除：另一端早已在树中的边直接丢弃
+            if in_tree[v] {
+                continue;
+            }
+            in_tree[v] = true;
+            forest.edges.push((u, v, w));
+            forest.total_weight = forest.total_weight + w;
+            for &(next, w) in &adjacency[v] {
+                if !in_tree[next] {
+                    heap.add((w, next, v));
+                }
+            }
+        }
+    }
+    forest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_small_graph() {
+        //     1
+        //  0 --- 1
+        //  |   / |
+        // 4| 2/  |3
+        //  | /   |
+        //  2 --- 3
+        //     5
+        let edges = [(0, 1, 1), (0, 2, 4), (1, 2, 2), (1, 3, 3), (2, 3, 5)];
+        let expected = SpanningForest {
+            edges: vec![(0, 1, 1), (1, 2, 2), (1, 3, 3)],
+            total_weight: 6,
+        };
+        assert_eq!(kruskal(4, &edges), expected);
+
+        let forest = prim(4, &edges);
+        assert_eq!(forest.total_weight, 6);
+        assert_eq!(forest.edges.len(), 3);
+    }
+
+    #[test]
+    fn test_disconnected_graph() {
+        // {0, 1, 2} 和 {3, 4} 两个连通分量，5 是孤立点；包含自环和平行边
+        let edges = [
+            (0, 1, 7),
+            (1, 2, 3),
+            (2, 0, 1),
+            (3, 4, 9),
+            (4, 3, 2),
+            (2, 2, 0),
+        ];
+        for forest in [kruskal(6, &edges), prim(6, &edges)] {
+            assert_eq!(forest.edges.len(), 3);
+            assert_eq!(forest.total_weight, 6);
+        }
+
+        let empty: SpanningForest<i32> = kruskal(0, &[]);
+        assert!(empty.edges.is_empty());
+        assert_eq!(prim::<i32>(3, &[]).total_weight, 0);
+    }
+
+    #[test]
+    fn test_kruskal_and_prim_agree_on_random_graphs() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..50 {
+            let n = rng.gen_range(1..=40);
+            let m = rng.gen_range(0..3 * n);
+            let edges: Vec<(usize, usize, i64)> = (0..m)
+                .map(|_| {
+                    (
+                        rng.gen_range(0..n),
+                        rng.gen_range(0..n),
+                        rng.gen_range(-20..80),
+                    )
+                })
+                .collect();
+            let by_kruskal = kruskal(n, &edges);
+            let by_prim = prim(n, &edges);
+            assert_eq!(by_kruskal.total_weight, by_prim.total_weight);
+            assert_eq!(by_kruskal.edges.len(), by_prim.edges.len());
+
+            // 森林中的边数 = 顶点数 - 连通分量数，并且选出的边都来自原图、不成环
+            let mut components = UnionFind::new(n);
+            for &(u, v, _) in &edges {
+                components.union(u, v);
+            }
+            assert_eq!(by_kruskal.edges.len(), n - components.num_components());
+            for forest in [&by_kruskal, &by_prim] {
+                let mut uf = UnionFind::new(n);
+                let mut total = 0;
+                for &(u, v, w) in &forest.edges {
+                    assert!(edges.iter().any(|&e| e == (u, v, w) || e == (v, u, w)));
+                    assert!(uf.union(u, v));
+                    total += w;
+                }
+                assert_eq!(total, forest.total_weight);
+            }
+        }
+    }
+}
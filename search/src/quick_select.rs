@@ -52,6 +52,48 @@ fn partition<T: PartialOrd>(arr: &mut [T], lo: usize, hi: usize) -> usize {
     left
 }
 
+/// 按 compare 给出的顺序原地重排 input，使 input[k] 恰好是第 k 小的元素，
+/// 它前面的元素都不大于它，后面的元素都不小于它。
+/// 使用中间位置作为枢轴并做三路划分，已排序或者大量重复的输入也不会退化
+pub fn quick_select_by<T, F>(input: &mut [T], k: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(
+        k < input.len(),
+        "k {} out of bounds for length {}",
+        k,
+        input.len()
+    );
+    let (mut lo, mut hi) = (0, input.len() - 1);
+    while lo < hi {
+        input.swap(lo, lo + (hi - lo) / 2);
+        // [lo, lt) 小于枢轴，[lt, i) 等于枢轴，(gt, hi] 大于枢轴，枢轴始终在 lt 处
+        let (mut lt, mut i, mut gt) = (lo, lo + 1, hi);
+        while i <= gt {
+            match compare(&input[i], &input[lt]) {
+                Ordering::Less => {
+                    input.swap(lt, i);
+                    lt += 1;
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    input.swap(i, gt);
+                    gt -= 1;
+                }
+                Ordering::Equal => i += 1,
+            }
+        }
+        if k < lt {
+            hi = lt - 1;
+        } else if k > gt {
+            lo = gt + 1;
+        } else {
+            return;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,4 +133,33 @@ mod tests {
         let mut arr = vec![5, 1, 5, 3, 7, 3, 5];
         assert_eq!(quick_select(&mut arr, 3), Some(5)); // 排序后第 3 小的元素是 5
     }
+
+    #[test]
+    fn test_quick_select_by_partitions() {
+        let inputs = [
+            vec![5, 1, 5, 3, 7, 3, 5],
+            (0..50).collect(),
+            (0..50).rev().collect(),
+            vec![2; 20],
+            (0..60).map(|i| (i * 37) % 11).collect::<Vec<i32>>(),
+        ];
+        for input in inputs {
+            let mut sorted = input.clone();
+            sorted.sort();
+            for k in 0..input.len() {
+                let mut arr = input.clone();
+                quick_select_by(&mut arr, k, |a, b| a.cmp(b));
+                assert_eq!(arr[k], sorted[k]);
+                assert!(arr[..k].iter().all(|x| *x <= arr[k]));
+                assert!(arr[k + 1..].iter().all(|x| *x >= arr[k]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_quick_select_by_key() {
+        let mut points = vec![(3, 'a'), (1, 'b'), (2, 'c')];
+        quick_select_by(&mut points, 0, |a, b| b.0.cmp(&a.0));
+        assert_eq!(points[0], (3, 'a'));
+    }
 }
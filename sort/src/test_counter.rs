@@ -1,3 +1,4 @@
+use data_structure::fenwick_tree::count_inversions;
 use std::time::Instant;
 
 pub fn test_algorithm_in_place<T: Ord + Copy>(
@@ -33,3 +34,48 @@ pub fn test_is_sorted<T: PartialOrd>(target: &[T]) -> bool {
 
     true
 }
+
+/// 输入的有序程度：逆序对个数占最大可能逆序对个数 n(n-1)/2 的比例，
+/// 0 表示已经有序，1 表示完全逆序
+pub fn presortedness<T: Ord>(target: &[T]) -> f64 {
+    if target.len() < 2 {
+        return 0.0;
+    }
+    inversion_ratio(count_inversions(target), target.len())
+}
+
+// 长度为 n 的序列中有 inversions 个逆序对时的逆序比例
+fn inversion_ratio(inversions: u64, n: usize) -> f64 {
+    let n = n as u64;
+    if n < 2 {
+        return 0.0;
+    }
+    inversions as f64 / (n * (n - 1) / 2) as f64
+}
+
+pub fn report_presortedness<T: Ord>(target: &[T]) {
+    let inversions = count_inversions(target);
+    println!(
+        "input length: {}, inversions: {}, inversion ratio: {:.4}",
+        target.len(),
+        inversions,
+        inversion_ratio(inversions, target.len())
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presortedness() {
+        assert_eq!(presortedness(&[1, 2, 3, 4, 5]), 0.0);
+        assert_eq!(presortedness(&[5, 4, 3, 2, 1]), 1.0);
+        assert_eq!(presortedness::<i32>(&[]), 0.0);
+        assert_eq!(presortedness(&[7]), 0.0);
+        // 逆序对 (3, 1)、(3, 2)，最多 6 个
+        assert_eq!(presortedness(&[3, 1, 2, 4]), 2.0 / 6.0);
+        // 相等的元素不算逆序
+        assert_eq!(presortedness(&[2, 2, 2]), 0.0);
+    }
+}
@@ -1,11 +1,21 @@
 pub fn knuth_morris_pratt(st: String, pat: String) -> Vec<usize> {
-    if st.is_empty() || pat.is_empty() {
+    knuth_morris_pratt_chunks(std::iter::once(st), pat)
+}
+
+/// Searches text that is split into consecutive chunks (e.g. the chunk
+/// iterator of a rope) without joining it. Matches may span chunk
+/// boundaries; returned indices are byte offsets into the whole text.
+pub fn knuth_morris_pratt_chunks<I, P>(chunks: I, pat: P) -> Vec<usize>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+    P: AsRef<[u8]>,
+{
+    let pattern = pat.as_ref();
+    if pattern.is_empty() {
         return vec![];
     }
 
-    let string = st.into_bytes();
-    let pattern = pat.into_bytes();
-
     // build the partial match table
     let mut partial = vec![0];
     for i in 1..pattern.len() {
@@ -16,21 +26,26 @@ pub fn knuth_morris_pratt(st: String, pat: String) -> Vec<usize> {
         partial.push(if pattern[j] == pattern[i] { j + 1 } else { j });
     }
 
-    // and read 'string' to find 'pattern'
+    // and read the chunks to find 'pattern', keeping the match state across chunks
     let mut ret = vec![];
     let mut j = 0;
+    let mut offset = 0;
 
-    for (i, &c) in string.iter().enumerate() {
-        while j > 0 && c != pattern[j] {
-            j = partial[j - 1];
-        }
-        if c == pattern[j] {
-            j += 1;
-        }
-        if j == pattern.len() {
-            ret.push(i + 1 - j);
-            j = partial[j - 1];
+    for chunk in chunks {
+        let chunk = chunk.as_ref();
+        for (i, &c) in chunk.iter().enumerate() {
+            while j > 0 && c != pattern[j] {
+                j = partial[j - 1];
+            }
+            if c == pattern[j] {
+                j += 1;
+            }
+            if j == pattern.len() {
+                ret.push(offset + i + 1 - j);
+                j = partial[j - 1];
+            }
         }
+        offset += chunk.len();
     }
 
     ret
@@ -39,6 +54,7 @@ pub fn knuth_morris_pratt(st: String, pat: String) -> Vec<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use data_structure::rope::Rope;
 
     #[test]
     fn each_letter_matches() {
@@ -94,4 +110,30 @@ mod tests {
         let index = knuth_morris_pratt("".to_string(), "abcdef".to_string());
         assert_eq!(index, vec![]);
     }
+
+    #[test]
+    fn matches_across_chunks() {
+        let chunks = ["ABC AB", "CDAB ABCD", "A", "BCDAB", "DE"];
+        let index = knuth_morris_pratt_chunks(chunks, "ABCDABD");
+        assert_eq!(index, vec![15]);
+        let index = knuth_morris_pratt_chunks(["ab", "", "a", "bab"], "aba");
+        assert_eq!(index, vec![0, 2]);
+        let index = knuth_morris_pratt_chunks(["abc"], "");
+        assert_eq!(index, vec![]);
+    }
+
+    #[test]
+    fn search_rope_chunks() {
+        let mut text = "lorem ipsum dolor sit amet, 中文 needle ".repeat(200);
+        let mut rope = Rope::from(text.as_str());
+        rope.insert(1000, "needle");
+        text.insert_str(rope.char_to_byte(1000), "needle");
+        assert!(rope.chunks().count() > 1);
+
+        let expected = knuth_morris_pratt(text.clone(), "needle".to_string());
+        let index = knuth_morris_pratt_chunks(rope.chunks(), "needle");
+        assert_eq!(index.len(), 201);
+        assert_eq!(index, expected);
+        assert!(index.iter().all(|&b| text[b..].starts_with("needle")));
+    }
 }
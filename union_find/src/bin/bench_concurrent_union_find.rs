@@ -0,0 +1,53 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::thread;
+use std::time::Instant;
+use union_find::{ConcurrentUnionFind, UnionFind};
+
+// 在随机图上比较顺序并查集与多线程无锁并查集的连通分量标注耗时
+fn main() {
+    let n = 2_000_000;
+    let m: usize = 4_000_000;
+    let mut rng = StdRng::seed_from_u64(42);
+    let edges: Vec<(usize, usize)> = (0..m)
+        .map(|_| (rng.gen_range(0..n), rng.gen_range(0..n)))
+        .collect();
+
+    let start = Instant::now();
+    let mut sequential = UnionFind::new(n);
+    for &(u, v) in &edges {
+        sequential.union(u, v);
+    }
+    let baseline = start.elapsed();
+    println!(
+        "sequential: {} ms, {} components",
+        baseline.as_millis(),
+        sequential.num_components()
+    );
+
+    let max_threads = thread::available_parallelism().map_or(4, |p| p.get());
+    let mut threads = 1;
+    while threads <= max_threads {
+        let uf = ConcurrentUnionFind::new(n);
+        let start = Instant::now();
+        thread::scope(|scope| {
+            for chunk in edges.chunks(m.div_ceil(threads)) {
+                let uf = &uf;
+                scope.spawn(move || {
+                    for &(u, v) in chunk {
+                        uf.union(u, v);
+                    }
+                });
+            }
+        });
+        let elapsed = start.elapsed();
+        assert_eq!(uf.num_components(), sequential.num_components());
+        println!(
+            "concurrent, {} threads: {} ms, speedup {:.2}x",
+            threads,
+            elapsed.as_millis(),
+            baseline.as_secs_f64() / elapsed.as_secs_f64()
+        );
+        threads *= 2;
+    }
+}
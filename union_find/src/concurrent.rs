@@ -0,0 +1,169 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 可以在多个线程之间共享的无锁并查集
+///
+/// 父指针保存在 AtomicUsize 中，所有操作只需要 &self。合并时总是把编号较小的根
+/// 挂到编号较大的根下面（按编号链接），因此父指针只会指向更大的编号，不会成环；
+/// 链接通过 CAS 完成，失败说明根已经被其它线程改动，重新查找后再试。
+/// find 使用路径分裂：把路径上的每个节点都指向它的祖父节点，同样用 CAS，失败也无妨。
+pub struct ConcurrentUnionFind {
+    parent: Vec<AtomicUsize>,
+    components: AtomicUsize,
+}
+
+impl ConcurrentUnionFind {
+    pub fn new(n: usize) -> Self {
+        ConcurrentUnionFind {
+            parent: (0..n).map(AtomicUsize::new).collect(),
+            components: AtomicUsize::new(n),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    // 在有其它线程并发合并时只是一个近似值，所有线程结束后是准确的
+    pub fn num_components(&self) -> usize {
+        self.components.load(Ordering::Acquire)
+    }
+
+    // 查找 x 所属集合的根；并发合并时返回的根可能马上就不再是根
+    pub fn find(&self, mut x: usize) -> usize {
+        loop {
+            let parent = self.parent[x].load(Ordering::Acquire);
+            if parent == x {
+                return x;
+            }
+            let grandparent = self.parent[parent].load(Ordering::Acquire);
+            if grandparent != parent {
+                let _ = self.parent[x].compare_exchange_weak(
+                    parent,
+                    grandparent,
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                );
+            }
+            x = parent;
+        }
+    }
+
+    // 判断两个元素是否属于同一个集合。
+    // 如果两次查找得到的根不同，但第一个根在此期间仍然是根，说明查询时刻两者确实不连通
+    pub fn connected(&self, x: usize, y: usize) -> bool {
+        loop {
+            let root_x = self.find(x);
+            let root_y = self.find(y);
+            if root_x == root_y {
+                return true;
+            }
+            if self.parent[root_x].load(Ordering::Acquire) == root_x {
+                return false;
+            }
+        }
+    }
+
+    // 合并 x 和 y 所在的集合，返回是否由本次调用完成了合并
+    pub fn union(&self, x: usize, y: usize) -> bool {
+        loop {
+            let root_x = self.find(x);
+            let root_y = self.find(y);
+            if root_x == root_y {
+                return false;
+            }
+            let (child, root) = if root_x < root_y {
+                (root_x, root_y)
+            } else {
+                (root_y, root_x)
+            };
+            if self.parent[child]
+                .compare_exchange(child, root, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                self.components.fetch_sub(1, Ordering::AcqRel);
+                return true;
+            }
+        }
+    }
+
+    // 每个元素所在集合的根，适合在所有线程结束后给连通分量打标签
+    pub fn labels(&self) -> Vec<usize> {
+        (0..self.parent.len()).map(|x| self.find(x)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UnionFind;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::thread;
+
+    #[test]
+    fn test_single_thread() {
+        let uf = ConcurrentUnionFind::new(6);
+        assert!(uf.union(0, 1));
+        assert!(uf.union(4, 5));
+        assert!(uf.union(1, 5));
+        assert!(!uf.union(0, 4));
+        assert!(uf.connected(0, 5));
+        assert!(!uf.connected(2, 3));
+        assert_eq!(uf.num_components(), 3);
+        assert_eq!(uf.find(0), 5);
+    }
+
+    #[test]
+    fn test_concurrent_stress_against_sequential() {
+        let n = 20_000;
+        let threads = 8;
+        let mut rng = StdRng::seed_from_u64(99);
+        let edges: Vec<(usize, usize)> = (0..n)
+            .map(|_| (rng.gen_range(0..n), rng.gen_range(0..n)))
+            .collect();
+
+        let mut sequential = UnionFind::new(n);
+        for &(u, v) in &edges {
+            sequential.union(u, v);
+        }
+
+        let concurrent = ConcurrentUnionFind::new(n);
+        let merged: usize = thread::scope(|scope| {
+            let handles: Vec<_> = edges
+                .chunks(edges.len().div_ceil(threads))
+                .map(|chunk| {
+                    let uf = &concurrent;
+                    scope.spawn(move || {
+                        let mut merged = 0;
+                        for &(u, v) in chunk {
+                            if uf.union(u, v) {
+                                merged += 1;
+                            }
+                            // 与合并交错执行查询
+                            assert!(uf.connected(u, v));
+                        }
+                        merged
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).sum()
+        });
+
+        // 每次成功合并恰好减少一个集合，不会有两个线程同时完成同一次合并
+        assert_eq!(merged, n - sequential.num_components());
+        assert_eq!(concurrent.num_components(), sequential.num_components());
+        let labels = concurrent.labels();
+        for &(u, v) in &edges {
+            assert_eq!(labels[u], labels[v]);
+        }
+        for x in 0..n {
+            for y in [0, n / 2, n - 1] {
+                assert_eq!(labels[x] == labels[y], sequential.connected(x, y));
+            }
+        }
+    }
+}
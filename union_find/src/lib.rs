@@ -1,89 +1,295 @@
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::hash::Hash;
 
-struct UnionFind<T> {
-    parent: HashMap<T, T>,
-    rank: HashMap<T, usize>,
+pub mod concurrent;
+pub mod rollback;
+pub mod weighted;
+
+pub use concurrent::ConcurrentUnionFind;
+pub use data_structure::invariant::InvariantViolation;
+pub use rollback::{EdgeEvent, OfflineConnectivity, RollbackUnionFind};
+pub use weighted::{Group, InconsistentConstraint, WeightedUnionFind, Xor};
+
+/// 以 0..n 的整数为元素的并查集，使用按大小合并和路径减半
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>, // 只有根节点的 size 有意义，表示整个集合的大小
+    components: usize,
 }
 
-impl<T> UnionFind<T>
-where
-    T: Eq + Hash + Clone,
-{
-    // 创建一个空的并查集
-    fn new() -> Self {
+impl UnionFind {
+    // 创建一个包含 n 个元素的并查集，每个元素各自成为一个集合
+    pub fn new(n: usize) -> Self {
         UnionFind {
-            parent: HashMap::new(),
-            rank: HashMap::new(),
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
         }
     }
 
-    // 动态插入新元素，初始化 parent 和 rank
-    fn insert_element(&mut self, x: T) {
-        self.parent.insert(x.clone(), x.clone()); // 新元素的父节点指向自身
-        self.rank.insert(x, 0); // 新元素的秩初始化为0
+    // 元素个数
+    pub fn len(&self) -> usize {
+        self.parent.len()
     }
 
-    // 查找元素 x 所属的集合，并进行路径压缩
-    fn find(&mut self, x: &T) -> T {
-        if !self.parent.contains_key(x) {
-            self.insert_element(x.clone());
-        }
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    // 集合个数
+    pub fn num_components(&self) -> usize {
+        self.components
+    }
+
+    // 动态插入一个新元素，返回它的编号
+    pub fn make_set(&mut self) -> usize {
+        let x = self.parent.len();
+        self.parent.push(x);
+        self.size.push(1);
+        self.components += 1;
+        x
+    }
 
-        let parent_x = self.parent.get(x).unwrap().clone();
-        if *x != parent_x {
-            let root = self.find(&parent_x);
-            self.parent.insert(x.clone(), root.clone()); // 路径压缩
-            root
-        } else {
-            parent_x
+    // 查找元素 x 所属集合的根，同时进行路径减半：让路径上的每个节点都指向它的祖父节点
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            let grandparent = self.parent[self.parent[x]];
+            self.parent[x] = grandparent;
+            x = grandparent;
         }
+        x
     }
 
     // 判断两个元素是否属于同一个集合
-    fn connected(&mut self, x: T, y: T) -> bool {
-        self.find(&x) == self.find(&y)
-    }
-
-    // 合并 x 和 y 所在的集合，按秩进行合并
-    fn union(&mut self, x: T, y: T) {
-        let root_x = self.find(&x);
-        let root_y = self.find(&y);
-
-        if root_x != root_y {
-            let rank_x = *self.rank.get(&root_x).unwrap();
-            let rank_y = *self.rank.get(&root_y).unwrap();
-
-            if rank_x > rank_y {
-                self.parent.insert(root_y, root_x);
-            } else if rank_x < rank_y {
-                self.parent.insert(root_x, root_y);
-            } else {
-                *self.rank.get_mut(&root_x).unwrap() += 1;
-                self.parent.insert(root_y, root_x);
+    pub fn connected(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    // 合并 x 和 y 所在的集合，把较小的集合挂到较大的集合下面；
+    // 如果两者原本就在同一个集合中则返回 false
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let root_x = self.find(x);
+        let root_y = self.find(y);
+        if root_x == root_y {
+            return false;
+        }
+        self.link(root_x, root_y);
+        true
+    }
+
+    // 按大小合并两个不同的根，返回合并后的根；size 相同时 root_x 成为新的根
+    pub(crate) fn link(&mut self, mut root_x: usize, mut root_y: usize) -> usize {
+        if self.size[root_x] < self.size[root_y] {
+            std::mem::swap(&mut root_x, &mut root_y);
+        }
+        self.parent[root_y] = root_x;
+        self.size[root_x] += self.size[root_y];
+        self.components -= 1;
+        root_x
+    }
+
+    // x 所在集合的大小
+    pub fn component_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    // 返回每个集合的全部成员，集合按最小成员排序，集合内部的成员升序排列
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+        let mut groups: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+        for x in 0..self.parent.len() {
+            let root = self.find(x);
+            let index = *group_of_root.entry(root).or_insert_with(|| {
+                groups.push(Vec::with_capacity(self.size[root]));
+                groups.len() - 1
+            });
+            groups[index].push(x);
+        }
+        groups
+    }
+
+    // 检查父指针构成的是一片森林：每条父节点链都在有限步内走到根（根的父节点是自己），
+    // 根记录的 size 等于实际成员数，components 等于根的个数。不修改结构，O(n)
+    pub fn validate(&self) -> Result<(), InvariantViolation> {
+        const UNVISITED: usize = usize::MAX;
+        const ON_PATH: usize = usize::MAX - 1;
+        let n = self.parent.len();
+        // root_of[x] 是 x 所在树的根，访问中的节点标记为 ON_PATH
+        let mut root_of = vec![UNVISITED; n];
+        let mut path = Vec::new();
+        for start in 0..n {
+            let mut x = start;
+            let root = loop {
+                if root_of[x] == ON_PATH {
+                    return Err(InvariantViolation::new(
+                        "UnionFind",
+                        format!("parent chain of element {} contains a cycle", start),
+                    ));
+                }
+                if root_of[x] != UNVISITED {
+                    break root_of[x];
+                }
+                let parent = self.parent[x];
+                if parent >= n {
+                    return Err(InvariantViolation::new(
+                        "UnionFind",
+                        format!("parent {} of element {} is out of range", parent, x),
+                    ));
+                }
+                if parent == x {
+                    break x;
+                }
+                root_of[x] = ON_PATH;
+                path.push(x);
+                x = parent;
+            };
+            root_of[x] = root;
+            for y in path.drain(..) {
+                root_of[y] = root;
             }
         }
+
+        let mut actual_size = vec![0; n];
+        for &root in &root_of {
+            actual_size[root] += 1;
+        }
+        let mut roots = 0;
+        for (x, &actual) in actual_size.iter().enumerate() {
+            if self.parent[x] == x {
+                roots += 1;
+                if self.size[x] != actual {
+                    return Err(InvariantViolation::new(
+                        "UnionFind",
+                        format!(
+                            "root {} records size {} but has {} members",
+                            x, self.size[x], actual
+                        ),
+                    ));
+                }
+            }
+        }
+        if roots != self.components {
+            return Err(InvariantViolation::new(
+                "UnionFind",
+                format!(
+                    "{} components recorded but there are {} roots",
+                    self.components, roots
+                ),
+            ));
+        }
+        Ok(())
     }
+}
+
+/// 元素可以是任意可哈希类型的并查集，把元素映射成整数编号后交给 UnionFind 处理
+pub struct KeyedUnionFind<T> {
+    ids: HashMap<T, usize>,
+    keys: Vec<T>,
+    inner: UnionFind,
+}
 
-    pub fn get_disjoint_sets(&mut self) -> HashSet<T> {
-        let mut sets = HashSet::new();
-        let keys: Vec<T> = self.parent.keys().cloned().collect();
-        for element in keys {
-            let root = self.find(&element).clone();
-            sets.insert(root);
+impl<T> Default for KeyedUnionFind<T>
+where
+    T: Eq + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> KeyedUnionFind<T>
+where
+    T: Eq + Hash + Clone,
+{
+    // 创建一个空的并查集
+    pub fn new() -> Self {
+        KeyedUnionFind {
+            ids: HashMap::new(),
+            keys: Vec::new(),
+            inner: UnionFind::new(0),
         }
-        sets
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn num_components(&self) -> usize {
+        self.inner.num_components()
+    }
+
+    // 动态插入新元素，返回它的编号；已经存在的元素直接返回原来的编号
+    pub fn insert_element(&mut self, x: T) -> usize {
+        if let Some(&id) = self.ids.get(&x) {
+            return id;
+        }
+        let id = self.inner.make_set();
+        self.ids.insert(x.clone(), id);
+        self.keys.push(x);
+        id
+    }
+
+    // 元素对应的编号
+    pub fn id(&self, x: &T) -> Option<usize> {
+        self.ids.get(x).copied()
+    }
+
+    // 编号对应的元素
+    pub fn key(&self, id: usize) -> Option<&T> {
+        self.keys.get(id)
+    }
+
+    // 查找元素 x 所属集合的根的编号，元素不存在时返回 None
+    pub fn find(&mut self, x: &T) -> Option<usize> {
+        let id = self.id(x)?;
+        Some(self.inner.find(id))
+    }
+
+    // 判断两个元素是否属于同一个集合，不存在的元素不与任何元素相连
+    pub fn connected(&mut self, x: &T, y: &T) -> bool {
+        match (self.id(x), self.id(y)) {
+            (Some(x), Some(y)) => self.inner.connected(x, y),
+            _ => false,
+        }
+    }
+
+    // 合并 x 和 y 所在的集合，不存在的元素会先被插入
+    pub fn union(&mut self, x: T, y: T) -> bool {
+        let x = self.insert_element(x);
+        let y = self.insert_element(y);
+        self.inner.union(x, y)
+    }
+
+    // x 所在集合的大小，元素不存在时返回 None
+    pub fn component_size(&mut self, x: &T) -> Option<usize> {
+        let id = self.id(x)?;
+        Some(self.inner.component_size(id))
+    }
+
+    // 返回每个集合的全部成员，按元素的插入顺序排列
+    pub fn groups(&mut self) -> Vec<Vec<&T>> {
+        self.inner
+            .groups()
+            .into_iter()
+            .map(|group| group.into_iter().map(|id| &self.keys[id]).collect())
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use data_structure::invariant::run_random_ops;
+    use rand::Rng;
 
     #[test]
     fn test_find_and_union() {
-        let mut uf = UnionFind::new();
+        let mut uf = KeyedUnionFind::new();
 
         // 插入两个不同元素
         uf.insert_element(1);
@@ -100,31 +306,33 @@ mod tests {
     }
 
     #[test]
-    fn test_union_by_rank() {
-        let mut uf = UnionFind::new();
+    fn test_union_by_size() {
+        let mut uf = KeyedUnionFind::new();
 
         // 插入多个元素
         uf.insert_element(1);
         uf.insert_element(2);
         uf.insert_element(3);
 
-        // 合并 (1, 2)，此时 rank(1) 和 rank(2) 相同
+        // 合并 (1, 2)，此时两个集合大小相同
         uf.union(1, 2);
 
         // 验证合并后的父节点应该相同
         assert_eq!(uf.find(&1), uf.find(&2));
 
-        // 合并 (1, 3)，根据秩将 3 挂到 1 的集合中
-        uf.union(1, 3);
+        // 合并 (3, 1)，3 所在的集合较小，应该挂到 1 的集合下面
+        let root = uf.find(&1);
+        uf.union(3, 1);
 
-        // 验证 1 和 3 现在也属于同一个集合
-        assert_eq!(uf.find(&1), uf.find(&3));
-        assert_eq!(uf.find(&2), uf.find(&3));
+        // 验证 1 和 3 现在也属于同一个集合，并且根没有变化
+        assert_eq!(uf.find(&3), root);
+        assert_eq!(uf.find(&2), root);
+        assert_eq!(uf.component_size(&3), Some(3));
     }
 
     #[test]
     fn test_path_compression() {
-        let mut uf = UnionFind::new();
+        let mut uf = KeyedUnionFind::new();
 
         // 插入多个元素
         uf.insert_element(1);
@@ -137,7 +345,7 @@ mod tests {
         uf.union(2, 3);
         uf.union(3, 4);
 
-        // 在没有路径压缩的情况下，4 的根应该是 1
+        // 4 的根应该和 1 的根相同
         assert_eq!(uf.find(&4), uf.find(&1));
 
         // 验证路径压缩后，父节点应该指向根
@@ -151,7 +359,7 @@ mod tests {
 
     #[test]
     fn test_disjoint_sets() {
-        let mut uf = UnionFind::new();
+        let mut uf = KeyedUnionFind::new();
 
         // 插入多个元素，分属两个不同集合
         uf.insert_element(1);
@@ -170,18 +378,150 @@ mod tests {
         // 但 (1, 3) 和 (2, 4) 属于不同集合
         assert_ne!(uf.find(&1), uf.find(&3));
         assert_ne!(uf.find(&2), uf.find(&4));
+
+        assert_eq!(uf.num_components(), 2);
+        assert_eq!(uf.groups(), vec![vec![&1, &2], vec![&3, &4]]);
     }
 
     #[test]
     fn test_union_with_self() {
-        let mut uf = UnionFind::new();
+        let mut uf = KeyedUnionFind::new();
 
         uf.insert_element(1);
 
         // 自己与自己合并
-        uf.union(1, 1);
+        assert!(!uf.union(1, 1));
 
         // 验证 find(1) 还是自己
         assert_eq!(uf.find(&1), uf.find(&1));
+        assert_eq!(uf.num_components(), 1);
+    }
+
+    #[test]
+    fn test_keyed_missing_elements() {
+        let mut uf = KeyedUnionFind::new();
+        assert_eq!(uf.find(&"a"), None);
+        assert!(!uf.connected(&"a", &"a"));
+
+        // union 会自动插入不存在的元素
+        assert!(uf.union("a", "b"));
+        assert!(uf.connected(&"a", &"b"));
+        assert_eq!(uf.len(), 2);
+        assert_eq!(uf.key(uf.id(&"b").unwrap()), Some(&"b"));
+    }
+
+    #[test]
+    fn test_dense_union_find() {
+        let mut uf = UnionFind::new(6);
+        assert_eq!(uf.num_components(), 6);
+        assert!(uf.union(0, 1));
+        assert!(uf.union(4, 5));
+        assert!(uf.union(1, 5));
+        assert!(!uf.union(0, 4));
+        assert_eq!(uf.num_components(), 3);
+        assert_eq!(uf.component_size(4), 4);
+        assert_eq!(uf.component_size(2), 1);
+        assert_eq!(uf.groups(), vec![vec![0, 1, 4, 5], vec![2], vec![3]]);
+
+        let x = uf.make_set();
+        assert_eq!(x, 6);
+        uf.union(x, 3);
+        assert!(uf.connected(3, 6));
+        assert_eq!(uf.num_components(), 3);
+    }
+
+    #[test]
+    fn test_many_elements() {
+        // find 是迭代实现的，元素再多也不会栈溢出
+        let n = 1_000_000;
+        let mut uf = UnionFind::new(n);
+        for i in 1..n {
+            uf.union(i, i - 1);
+        }
+        assert_eq!(uf.num_components(), 1);
+        assert_eq!(uf.component_size(n - 1), n);
+        assert!(uf.connected(0, n - 1));
+    }
+
+    #[test]
+    fn test_validate_random_ops() {
+        let mut uf = UnionFind::new(20);
+        // 朴素做法：直接记录每个元素的集合编号，合并时整体改写
+        let mut label: Vec<usize> = (0..20).collect();
+        assert_eq!(uf.validate(), Ok(()));
+        run_random_ops(
+            48,
+            3000,
+            &mut uf,
+            |rng, uf| {
+                let n = uf.len();
+                match rng.gen_range(0..10) {
+                    0 => {
+                        let x = uf.make_set();
+                        label.push(x);
+                        format!("make_set() = {}", x)
+                    }
+                    1..=4 => {
+                        let (x, y) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                        let (from, to) = (label[y], label[x]);
+                        assert_eq!(uf.union(x, y), from != to);
+                        for l in label.iter_mut().filter(|l| **l == from) {
+                            *l = to;
+                        }
+                        format!("union({}, {})", x, y)
+                    }
+                    _ => {
+                        let (x, y) = (rng.gen_range(0..n), rng.gen_range(0..n));
+                        assert_eq!(uf.connected(x, y), label[x] == label[y]);
+                        let members = label.iter().filter(|&&l| l == label[x]).count();
+                        assert_eq!(uf.component_size(x), members);
+                        format!("connected({}, {})", x, y)
+                    }
+                }
+            },
+            UnionFind::validate,
+        );
+    }
+
+    #[test]
+    fn test_validate_detects_corruption() {
+        let mut uf = UnionFind::new(4);
+        uf.union(0, 1);
+        uf.union(2, 3);
+        assert_eq!(uf.validate(), Ok(()));
+
+        // 两个根互相指向对方，形成环
+        let (a, b) = (uf.find(0), uf.find(2));
+        uf.parent[a] = b;
+        uf.parent[b] = a;
+        assert!(uf.validate().is_err());
+
+        let mut uf = UnionFind::new(3);
+        uf.parent[1] = 7;
+        assert_eq!(
+            uf.validate(),
+            Err(InvariantViolation::new(
+                "UnionFind",
+                "parent 7 of element 1 is out of range"
+            ))
+        );
+
+        let mut uf = UnionFind::new(3);
+        uf.parent[1] = 0;
+        assert_eq!(
+            uf.validate(),
+            Err(InvariantViolation::new(
+                "UnionFind",
+                "root 0 records size 1 but has 2 members"
+            ))
+        );
+        uf.size[0] = 2;
+        assert_eq!(
+            uf.validate(),
+            Err(InvariantViolation::new(
+                "UnionFind",
+                "3 components recorded but there are 2 roots"
+            ))
+        );
     }
 }
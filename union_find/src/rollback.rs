@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+
+/// 可以撤销合并操作的并查集
+///
+/// 路径压缩会修改很多节点的父指针，无法撤销，所以这里只使用按秩合并，
+/// find 的复杂度为 O(log n)。每次成功的合并都会记录在历史中，
+/// rollback 按相反的顺序撤销。
+pub struct RollbackUnionFind {
+    parent: Vec<usize>,
+    rank: Vec<usize>,
+    components: usize,
+    // 每次成功合并记录 (被挂上去的根, 新的根, 新根的秩是否增加了)
+    history: Vec<(usize, usize, bool)>,
+}
+
+impl RollbackUnionFind {
+    pub fn new(n: usize) -> Self {
+        RollbackUnionFind {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            components: n,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn num_components(&self) -> usize {
+        self.components
+    }
+
+    // 不做路径压缩，因此只需要不可变引用
+    pub fn find(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+        x
+    }
+
+    pub fn connected(&self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    // 合并 x 和 y 所在的集合，如果两者原本就在同一个集合中则返回 false（不记录历史）
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let mut root_x = self.find(x);
+        let mut root_y = self.find(y);
+        if root_x == root_y {
+            return false;
+        }
+        if self.rank[root_x] < self.rank[root_y] {
+            std::mem::swap(&mut root_x, &mut root_y);
+        }
+        let rank_increased = self.rank[root_x] == self.rank[root_y];
+        if rank_increased {
+            self.rank[root_x] += 1;
+        }
+        self.parent[root_y] = root_x;
+        self.components -= 1;
+        self.history.push((root_y, root_x, rank_increased));
+        true
+    }
+
+    // 当前状态的快照，传给 rollback 可以回到这个状态
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    // 撤销快照 to 之后的所有合并；to 必须不晚于当前状态
+    pub fn rollback(&mut self, to: usize) {
+        assert!(
+            to <= self.history.len(),
+            "cannot roll back to a future snapshot"
+        );
+        while self.history.len() > to {
+            let (child, root, rank_increased) = self.history.pop().unwrap();
+            self.parent[child] = child;
+            if rank_increased {
+                self.rank[root] -= 1;
+            }
+            self.components += 1;
+        }
+    }
+}
+
+/// 时间线上的一个事件，边是无向的
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeEvent {
+    Add(usize, usize),
+    Remove(usize, usize),
+}
+
+/// 离线动态连通性：给定一条加边、删边的时间线，回答任意时刻两点是否连通
+///
+/// 时刻 t 表示执行完前 t 个事件之后的状态（0 <= t <= 事件个数）。
+/// 每条边在一段连续的时间区间内存在，把这些区间挂到以时间为下标的线段树上，
+/// 然后深度优先遍历线段树：进入节点时合并挂在上面的边，离开时回滚，
+/// 到达叶子时回答该时刻的查询。总复杂度 O((m + q) log m log n)。
+///
+/// 单个查询用 connected(u, v, t)，一批查询用 solve 一次遍历全部回答。
+pub struct OfflineConnectivity {
+    n: usize,
+    times: usize,
+    // 线段树每个节点上挂着的边
+    tree: Vec<Vec<(usize, usize)>>,
+}
+
+impl OfflineConnectivity {
+    // 同一条边可以被重复添加，删除时去掉其中一条；删除不存在的边会被忽略
+    pub fn new(n: usize, events: &[EdgeEvent]) -> Self {
+        let times = events.len() + 1;
+        let mut solver = OfflineConnectivity {
+            n,
+            times,
+            tree: vec![Vec::new(); 4 * times],
+        };
+        // 每条边尚未删除的各个副本的加入时刻
+        let mut alive: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for (i, event) in events.iter().enumerate() {
+            match *event {
+                EdgeEvent::Add(u, v) => {
+                    alive.entry(normalize(u, v)).or_default().push(i + 1);
+                }
+                EdgeEvent::Remove(u, v) => {
+                    let edge = normalize(u, v);
+                    if let Some(start) = alive.get_mut(&edge).and_then(|starts| starts.pop()) {
+                        solver.insert(1, 0, times, start, i + 1, edge);
+                    }
+                }
+            }
+        }
+        for (edge, starts) in alive {
+            for start in starts {
+                solver.insert(1, 0, times, start, times, edge);
+            }
+        }
+        solver
+    }
+
+    // 把存在于 [from, to) 时间段内的边挂到线段树节点 node（负责 [lo, hi)）上
+    fn insert(
+        &mut self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        from: usize,
+        to: usize,
+        edge: (usize, usize),
+    ) {
+        if to <= lo || hi <= from {
+            return;
+        }
+        if from <= lo && hi <= to {
+            self.tree[node].push(edge);
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        self.insert(2 * node, lo, mid, from, to, edge);
+        self.insert(2 * node + 1, mid, hi, from, to, edge);
+    }
+
+    // 时刻 t 时 u 和 v 是否连通。时刻 t 存在的边恰好是线段树中从根到叶子 t 的路径上
+    // 挂着的边，只需要合并这些边，复杂度 O(n + m log n)；查询很多时用 solve 批量回答更快
+    pub fn connected(&self, u: usize, v: usize, t: usize) -> bool {
+        assert!(t < self.times, "query time {} is out of range", t);
+        let mut uf = RollbackUnionFind::new(self.n);
+        let (mut node, mut lo, mut hi) = (1, 0, self.times);
+        loop {
+            for &(a, b) in &self.tree[node] {
+                uf.union(a, b);
+            }
+            if hi - lo == 1 {
+                return uf.connected(u, v);
+            }
+            let mid = (lo + hi) / 2;
+            if t < mid {
+                node *= 2;
+                hi = mid;
+            } else {
+                node = 2 * node + 1;
+                lo = mid;
+            }
+        }
+    }
+
+    // 回答一组查询 (u, v, t)：时刻 t 时 u 和 v 是否连通
+    pub fn solve(&self, queries: &[(usize, usize, usize)]) -> Vec<bool> {
+        let mut queries_at: Vec<Vec<usize>> = vec![Vec::new(); self.times];
+        for (i, &(_, _, t)) in queries.iter().enumerate() {
+            assert!(t < self.times, "query time {} is out of range", t);
+            queries_at[t].push(i);
+        }
+        let mut answers = vec![false; queries.len()];
+        let mut uf = RollbackUnionFind::new(self.n);
+        self.dfs(
+            1,
+            0,
+            self.times,
+            &mut uf,
+            queries,
+            &queries_at,
+            &mut answers,
+        );
+        answers
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn dfs(
+        &self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        uf: &mut RollbackUnionFind,
+        queries: &[(usize, usize, usize)],
+        queries_at: &[Vec<usize>],
+        answers: &mut [bool],
+    ) {
+        let snapshot = uf.snapshot();
+        for &(u, v) in &self.tree[node] {
+            uf.union(u, v);
+        }
+        if hi - lo == 1 {
+            for &i in &queries_at[lo] {
+                let (u, v, _) = queries[i];
+                answers[i] = uf.connected(u, v);
+            }
+        } else {
+            let mid = (lo + hi) / 2;
+            self.dfs(2 * node, lo, mid, uf, queries, queries_at, answers);
+            self.dfs(2 * node + 1, mid, hi, uf, queries, queries_at, answers);
+        }
+        uf.rollback(snapshot);
+    }
+}
+
+fn normalize(u: usize, v: usize) -> (usize, usize) {
+    if u <= v {
+        (u, v)
+    } else {
+        (v, u)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UnionFind;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_snapshot_and_rollback() {
+        let mut uf = RollbackUnionFind::new(5);
+        assert!(uf.union(0, 1));
+        let snapshot = uf.snapshot();
+        assert!(uf.union(1, 2));
+        assert!(uf.union(3, 4));
+        assert!(!uf.union(0, 2));
+        assert!(uf.connected(0, 2));
+        assert_eq!(uf.num_components(), 2);
+
+        uf.rollback(snapshot);
+        assert!(uf.connected(0, 1));
+        assert!(!uf.connected(0, 2));
+        assert!(!uf.connected(3, 4));
+        assert_eq!(uf.num_components(), 4);
+
+        uf.rollback(0);
+        assert!(!uf.connected(0, 1));
+        assert_eq!(uf.num_components(), 5);
+    }
+
+    #[test]
+    fn test_offline_connectivity() {
+        let events = [
+            EdgeEvent::Add(0, 1),    // t = 1
+            EdgeEvent::Add(1, 2),    // t = 2
+            EdgeEvent::Remove(1, 0), // t = 3
+            EdgeEvent::Add(0, 2),    // t = 4
+            EdgeEvent::Remove(3, 4), // t = 5，删除不存在的边
+        ];
+        let solver = OfflineConnectivity::new(5, &events);
+        let queries = [
+            (0, 1, 0),
+            (0, 1, 1),
+            (0, 2, 2),
+            (0, 2, 3),
+            (0, 1, 4),
+            (3, 4, 5),
+        ];
+        let expected = vec![false, true, true, false, true, false];
+        assert_eq!(solver.solve(&queries), expected);
+        for (&(u, v, t), &answer) in queries.iter().zip(&expected) {
+            assert_eq!(solver.connected(u, v, t), answer);
+        }
+    }
+
+    #[test]
+    fn test_offline_connectivity_against_brute_force() {
+        let n = 12;
+        let mut rng = StdRng::seed_from_u64(2024);
+
+        // 随机生成时间线，删除时只删除当前存在的边（也包括重复添加的边）
+        let mut events = Vec::new();
+        let mut present: Vec<(usize, usize)> = Vec::new();
+        for _ in 0..300 {
+            if present.is_empty() || !rng.gen_ratio(1, 3) {
+                let edge = (rng.gen_range(0..n), rng.gen_range(0..n));
+                present.push(edge);
+                events.push(EdgeEvent::Add(edge.0, edge.1));
+            } else {
+                let (u, v) = present.swap_remove(rng.gen_range(0..present.len()));
+                events.push(EdgeEvent::Remove(v, u));
+            }
+        }
+        let queries: Vec<(usize, usize, usize)> = (0..500)
+            .map(|_| {
+                (
+                    rng.gen_range(0..n),
+                    rng.gen_range(0..n),
+                    rng.gen_range(0..=events.len()),
+                )
+            })
+            .collect();
+
+        let solver = OfflineConnectivity::new(n, &events);
+        let answers = solver.solve(&queries);
+        for (&(u, v, t), &answer) in queries.iter().zip(&answers) {
+            assert_eq!(solver.connected(u, v, t), answer);
+            // 暴力重放前 t 个事件
+            let mut edges: Vec<(usize, usize)> = Vec::new();
+            for event in &events[..t] {
+                match *event {
+                    EdgeEvent::Add(a, b) => edges.push(normalize(a, b)),
+                    EdgeEvent::Remove(a, b) => {
+                        let i = edges.iter().position(|&e| e == normalize(a, b)).unwrap();
+                        edges.swap_remove(i);
+                    }
+                }
+            }
+            let mut uf = UnionFind::new(n);
+            for (a, b) in edges {
+                uf.union(a, b);
+            }
+            assert_eq!(uf.connected(u, v), answer, "query ({}, {}, {})", u, v, t);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::UnionFind;
+use std::fmt;
+use std::ops::BitXor;
+
+/// 带权并查集中边权所在的交换群
+///
+/// 要求 op 满足结合律、交换律，identity 是单位元，inverse 是逆元。
+pub trait Group: Clone + PartialEq {
+    fn identity() -> Self;
+    fn op(&self, other: &Self) -> Self;
+    fn inverse(&self) -> Self;
+}
+
+macro_rules! impl_additive_group {
+    ($($t:ty),*) => {
+        $(
+            impl Group for $t {
+                fn identity() -> Self {
+                    0
+                }
+                fn op(&self, other: &Self) -> Self {
+                    self.wrapping_add(*other)
+                }
+                fn inverse(&self) -> Self {
+                    self.wrapping_neg()
+                }
+            }
+        )*
+    };
+}
+
+// 整数在加法下构成群（按补码回绕，不会溢出 panic）
+impl_additive_group!(i8, i16, i32, i64, i128, isize);
+
+/// 异或群，例如用 Xor(1) 表示“奇偶性不同”
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Xor<T>(pub T);
+
+impl<T> Group for Xor<T>
+where
+    T: BitXor<Output = T> + Default + Clone + PartialEq,
+{
+    fn identity() -> Self {
+        Xor(T::default())
+    }
+    fn op(&self, other: &Self) -> Self {
+        Xor(self.0.clone() ^ other.0.clone())
+    }
+    fn inverse(&self) -> Self {
+        self.clone()
+    }
+}
+
+/// 新约束与已有约束矛盾：已知 diff(x, y) 为 expected，却要求它等于 found
+#[derive(Debug, Clone, PartialEq)]
+pub struct InconsistentConstraint<W> {
+    pub expected: W,
+    pub found: W,
+}
+
+impl<W: fmt::Debug> fmt::Display for InconsistentConstraint<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "inconsistent constraint: difference is already {:?}, got {:?}",
+            self.expected, self.found
+        )
+    }
+}
+
+/// 带权（势能）并查集：每个元素记录自己相对于父节点的偏移量，
+/// 从而可以维护形如 value(y) - value(x) = diff 的约束并发现矛盾
+///
+/// 父指针、集合大小和按大小合并都直接使用内部的 UnionFind，只有 find 需要单独实现：
+/// 路径减半改变父节点时必须同时把偏移量累加上去，不能调用 UnionFind::find。
+pub struct WeightedUnionFind<W> {
+    inner: UnionFind,
+    weight: Vec<W>, // value(x) = value(parent[x]) op weight[x]
+}
+
+impl<W: Group> WeightedUnionFind<W> {
+    // 创建一个包含 n 个元素的并查集，每个元素各自成为一个集合
+    pub fn new(n: usize) -> Self {
+        WeightedUnionFind {
+            inner: UnionFind::new(n),
+            weight: vec![W::identity(); n],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn num_components(&self) -> usize {
+        self.inner.num_components()
+    }
+
+    // 动态插入一个新元素，返回它的编号
+    pub fn make_set(&mut self) -> usize {
+        self.weight.push(W::identity());
+        self.inner.make_set()
+    }
+
+    // 查找 x 所属集合的根，同时进行路径减半，并把偏移量累加到新的父节点上
+    pub fn find(&mut self, mut x: usize) -> usize {
+        let parent = &mut self.inner.parent;
+        while parent[x] != x {
+            let p = parent[x];
+            let grandparent = parent[p];
+            self.weight[x] = self.weight[x].op(&self.weight[p]);
+            parent[x] = grandparent;
+            x = grandparent;
+        }
+        x
+    }
+
+    // 返回 (根, value(x) 相对于根的偏移量)
+    fn find_with_potential(&mut self, x: usize) -> (usize, W) {
+        let root = self.find(x);
+        let mut potential = W::identity();
+        let mut node = x;
+        while node != root {
+            potential = potential.op(&self.weight[node]);
+            node = self.inner.parent[node];
+        }
+        (root, potential)
+    }
+
+    pub fn connected(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    // 返回 value(y) - value(x)；两者不在同一个集合时无法确定，返回 None
+    pub fn diff(&mut self, x: usize, y: usize) -> Option<W> {
+        let (root_x, potential_x) = self.find_with_potential(x);
+        let (root_y, potential_y) = self.find_with_potential(y);
+        if root_x != root_y {
+            return None;
+        }
+        Some(potential_y.op(&potential_x.inverse()))
+    }
+
+    // 加入约束 value(y) - value(x) = diff。
+    // 返回 Ok(true) 表示合并了两个集合，Ok(false) 表示约束已经隐含在已有约束中，
+    // 与已有约束矛盾时返回错误，并查集保持不变
+    pub fn union(
+        &mut self,
+        x: usize,
+        y: usize,
+        diff: W,
+    ) -> Result<bool, InconsistentConstraint<W>> {
+        let (root_x, potential_x) = self.find_with_potential(x);
+        let (root_y, potential_y) = self.find_with_potential(y);
+        if root_x == root_y {
+            let expected = potential_y.op(&potential_x.inverse());
+            if expected != diff {
+                return Err(InconsistentConstraint {
+                    expected,
+                    found: diff,
+                });
+            }
+            return Ok(false);
+        }
+        // value(root_y) - value(root_x) = potential_x + diff - potential_y
+        let offset = potential_x.op(&diff).op(&potential_y.inverse());
+        if self.inner.link(root_x, root_y) == root_x {
+            self.weight[root_y] = offset;
+        } else {
+            self.weight[root_x] = offset.inverse();
+        }
+        Ok(true)
+    }
+
+    pub fn component_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.inner.size[root]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_additive_constraints() {
+        let mut uf: WeightedUnionFind<i64> = WeightedUnionFind::new(5);
+
+        // value(1) = value(0) + 5, value(2) = value(1) - 3
+        assert_eq!(uf.union(0, 1, 5), Ok(true));
+        assert_eq!(uf.union(1, 2, -3), Ok(true));
+        assert_eq!(uf.diff(0, 2), Some(2));
+        assert_eq!(uf.diff(2, 0), Some(-2));
+        assert_eq!(uf.diff(0, 3), None);
+
+        // 已经隐含的约束
+        assert_eq!(uf.union(2, 0, -2), Ok(false));
+
+        // 矛盾的约束
+        let err = uf.union(0, 2, 7).unwrap_err();
+        assert_eq!(
+            err,
+            InconsistentConstraint {
+                expected: 2,
+                found: 7
+            }
+        );
+        assert_eq!(uf.diff(0, 2), Some(2));
+
+        // 合并两个已经有内部约束的集合
+        assert_eq!(uf.union(3, 4, 10), Ok(true));
+        assert_eq!(uf.union(2, 4, 1), Ok(true));
+        assert_eq!(uf.diff(0, 3), Some(-7));
+        assert_eq!(uf.num_components(), 1);
+        assert_eq!(uf.component_size(3), 5);
+    }
+
+    #[test]
+    fn test_parity_constraints() {
+        let mut uf: WeightedUnionFind<Xor<u8>> = WeightedUnionFind::new(4);
+
+        // 0 和 1 奇偶性不同，1 和 2 奇偶性不同，所以 0 和 2 奇偶性相同
+        assert!(uf.union(0, 1, Xor(1)).is_ok());
+        assert!(uf.union(1, 2, Xor(1)).is_ok());
+        assert_eq!(uf.diff(0, 2), Some(Xor(0)));
+        assert!(uf.union(2, 0, Xor(1)).is_err());
+
+        let x = uf.make_set();
+        assert!(uf.union(x, 3, Xor(0)).is_ok());
+        assert!(uf.union(3, 0, Xor(1)).is_ok());
+        assert_eq!(uf.diff(x, 1), Some(Xor(0)));
+    }
+
+    #[test]
+    fn test_against_assigned_values() {
+        // 先给每个元素随机指定一个值，再按值之差加入约束，
+        // 任何已连通的两个元素的 diff 都必须与真实的差一致
+        let n = 200;
+        let mut rng = StdRng::seed_from_u64(12345);
+        let values: Vec<i64> = (0..n).map(|_| rng.gen_range(-500..500)).collect();
+        let mut uf: WeightedUnionFind<i64> = WeightedUnionFind::new(n);
+        for _ in 0..400 {
+            let (x, y) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            assert!(uf.union(x, y, values[y] - values[x]).is_ok());
+            let (x, y) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            match uf.diff(x, y) {
+                Some(d) => assert_eq!(d, values[y] - values[x]),
+                None => assert!(!uf.connected(x, y)),
+            }
+            // 故意给出一个错误的约束
+            if uf.connected(x, y) {
+                assert!(uf.union(x, y, values[y] - values[x] + 1).is_err());
+            }
+        }
+        for x in 0..n {
+            assert_eq!(uf.diff(x, x), Some(0));
+        }
+    }
+}